@@ -0,0 +1,53 @@
+//! Throughput of order book updates - run with `cargo bench`.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+use market_finance::book::{ BookMessage, BookOrder, Level, LevelBook, LevelUpdate, OrderBook };
+use market_finance::orders::Side;
+
+const UPDATES: u64 = 2_000_000;
+
+fn side(index: u64) -> Side { if index % 2 == 1 { Side::Sell } else { Side::Buy } }
+
+/// A price within 50 ticks of the touch for the side
+fn price(index: u64) -> f64 {
+   let offset = (index.wrapping_mul(2_654_435_761) % 50) as f64 * 0.01;
+   match side(index) {
+      Side::Buy => 99.99 - offset,
+      Side::Sell => 100.01 + offset
+   }
+}
+
+fn report(name: &str, start: Instant) {
+   let seconds = start.elapsed().as_secs_f64();
+   println!("{}: {} updates in {:.3}s - {:.0} updates per second", name, UPDATES, seconds, UPDATES as f64 / seconds);
+}
+
+fn main() {
+   let mut levels = LevelBook::new();
+   levels.apply_snapshot(0, &[ Level { price: 99.99, size: 100.0 } ], &[ Level { price: 100.01, size: 100.0 } ]);
+   let start = Instant::now();
+   for sequence in 1..=UPDATES {
+      let size = (sequence % 7) as f64 * 100.0;
+      levels.apply(&LevelUpdate { sequence, side: side(sequence), price: price(sequence), size }).unwrap();
+   }
+   report("level book", start);
+
+   // adds until 1,000 orders rest, then alternately adds and fully executes or cancels the oldest order
+   let mut orders = OrderBook::new();
+   orders.apply_snapshot(0, &[]);
+   let mut live = VecDeque::new();
+   let start = Instant::now();
+   for sequence in 1..=UPDATES {
+      let message = if sequence % 2 == 0 && live.len() >= 1_000 {
+         let id = live.pop_front().unwrap();
+         if sequence % 4 == 0 { BookMessage::Cancel { id } } else { BookMessage::Execute { id, size: 100.0 } }
+      } else {
+         live.push_back(sequence);
+         BookMessage::Add(BookOrder { id: sequence, side: side(sequence), price: price(sequence), size: 100.0 })
+      };
+      orders.apply(sequence, &message).unwrap();
+   }
+   assert_eq!(live.len(), orders.len());
+   report("order book", start);
+}
\ No newline at end of file
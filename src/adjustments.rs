@@ -0,0 +1,210 @@
+//! Corporate action adjustments for bar series.
+//!
+//! Back-adjusted series keep the most recent bar as traded and rewrite history, while forward-adjusted
+//! series keep the first bar as traded and rewrite everything after it.  Only actions that fall between
+//! the first and last bar of a series affect it.
+
+use crate::{ Bar, Timestamped };
+
+/// A stock split - a 4 for 1 split has a `numerator` of 4 and a `denominator` of 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Split {
+   /// The timestamp of the first bar trading on the split basis (the ex-date)
+   pub timestamp: i64,
+
+   /// The number of shares after the split
+   pub numerator: f64,
+
+   /// The number of shares before the split
+   pub denominator: f64
+}
+impl Split {
+   /// The number of new shares for each old share
+   pub fn ratio(&self) -> f64 { self.numerator / self.denominator }
+}
+impl Timestamped for Split {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// A cash dividend
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dividend {
+   /// The timestamp of the first bar trading without the dividend (the ex-date)
+   pub timestamp: i64,
+
+   /// The dividend paid per share
+   pub amount: f64
+}
+impl Timestamped for Dividend {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// Which corporate actions are adjusted for
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdjustmentMode {
+   /// Only splits - prices remain comparable to what was traded, less any splits
+   Splits,
+
+   /// Splits and dividends - price changes reflect the total return of holding the shares
+   TotalReturn
+}
+
+/// The amount to multiply a bar's prices and volume by to adjust it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdjustmentFactor {
+   /// The timestamp of the bar the factor applies to
+   pub timestamp: i64,
+
+   /// The multiplier for the open, high, low and close
+   pub price: f64,
+
+   /// The multiplier for the volume - only splits affect volume
+   pub volume: f64
+}
+
+/// The splits and dividends of a single symbol
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CorporateActions {
+   /// The splits in any order
+   pub splits: Vec<Split>,
+
+   /// The cash dividends in any order
+   pub dividends: Vec<Dividend>
+}
+impl CorporateActions {
+   /// Creates a new set of corporate actions
+   pub fn new(splits: Vec<Split>, dividends: Vec<Dividend>) -> Self { Self { splits, dividends } }
+
+   /// Calculates the back-adjustment factors for each bar of a series sorted by timestamp.
+   ///
+   /// The factor for the last bar is always 1.
+   pub fn factors(&self, bars: &[Bar], mode: AdjustmentMode) -> Vec<AdjustmentFactor> {
+      let mut factors = vec![];
+      let mut price = 1.0;
+      let mut volume = 1.0;
+
+      for (index, bar) in bars.iter().enumerate().rev() {
+         factors.push(AdjustmentFactor { timestamp: bar.timestamp, price, volume });
+
+         // apply the actions that take effect after the previous bar, up to and including this one
+         if let Some(previous) = index.checked_sub(1).map(|previous| &bars[previous]) {
+            let applies = |timestamp: i64| timestamp > previous.timestamp && timestamp <= bar.timestamp;
+
+            let ratio: f64 = self.splits.iter().filter(|split| applies(split.timestamp)).map(Split::ratio).product();
+            price /= ratio;
+            volume *= ratio;
+
+            // a dividend on the same bar as a split is paid on the new shares, so it's compared to
+            // the previous close on the split basis
+            if mode == AdjustmentMode::TotalReturn {
+               for dividend in self.dividends.iter().filter(|dividend| applies(dividend.timestamp)) {
+                  price *= 1.0 - dividend.amount * ratio / previous.close;
+               }
+            }
+         }
+      }
+
+      factors.reverse();
+      factors
+   }
+
+   /// Adjusts the history of a series sorted by timestamp so that it's comparable to the last bar
+   pub fn back_adjust(&self, bars: &[Bar], mode: AdjustmentMode) -> Vec<Bar> {
+      let factors = self.factors(bars, mode);
+      bars.iter().zip(factors.iter()).map(|(bar, factor)| adjust(bar, factor.price, factor.volume)).collect()
+   }
+
+   /// Adjusts a series sorted by timestamp so that it's comparable to the first bar
+   pub fn forward_adjust(&self, bars: &[Bar], mode: AdjustmentMode) -> Vec<Bar> {
+      let factors = self.factors(bars, mode);
+      let first = match factors.first() {
+         Some(first) => *first,
+         None => return vec![]
+      };
+
+      bars.iter()
+         .zip(factors.iter())
+         .map(|(bar, factor)| adjust(bar, factor.price / first.price, factor.volume / first.volume))
+         .collect()
+   }
+}
+
+fn adjust(bar: &Bar, price: f64, volume: f64) -> Bar {
+   Bar {
+      timestamp: bar.timestamp,
+      open: bar.open * price,
+      high: bar.high * price,
+      low: bar.low * price,
+      close: bar.close * price,
+      volume: bar.volume.map(|value| (value as f64 * volume).round() as u64)
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn bar(timestamp: i64, close: f64, volume: u64) -> Bar {
+      Bar { timestamp, open: close, high: close, low: close, close, volume: Some(volume) }
+   }
+
+   fn closes(bars: &[Bar]) -> Vec<f64> { bars.iter().map(|bar| bar.close).collect() }
+
+   #[test]
+   fn verify_split_back_adjust() {
+      let bars = vec![ bar(1, 100.0, 10), bar(2, 102.0, 10), bar(3, 51.0, 20), bar(4, 52.0, 20) ];
+      let actions = CorporateActions::new(vec![ Split { timestamp: 3, numerator: 2.0, denominator: 1.0 } ], vec![]);
+
+      let adjusted = actions.back_adjust(&bars, AdjustmentMode::Splits);
+      assert_eq!(vec![ 50.0, 51.0, 51.0, 52.0 ], closes(&adjusted));
+      assert_eq!(vec![ Some(20), Some(20), Some(20), Some(20) ], adjusted.iter().map(|bar| bar.volume).collect::<Vec<_>>());
+   }
+
+   #[test]
+   fn verify_split_forward_adjust() {
+      let bars = vec![ bar(1, 100.0, 10), bar(2, 102.0, 10), bar(3, 51.0, 20), bar(4, 52.0, 20) ];
+      let actions = CorporateActions::new(vec![ Split { timestamp: 3, numerator: 2.0, denominator: 1.0 } ], vec![]);
+
+      let adjusted = actions.forward_adjust(&bars, AdjustmentMode::Splits);
+      assert_eq!(vec![ 100.0, 102.0, 102.0, 104.0 ], closes(&adjusted));
+      assert_eq!(Some(10), adjusted[3].volume);
+   }
+
+   #[test]
+   fn verify_total_return_factors() {
+      let bars = vec![ bar(1, 100.0, 10), bar(2, 99.0, 10), bar(3, 49.5, 20) ];
+      let actions = CorporateActions::new(
+         vec![ Split { timestamp: 3, numerator: 2.0, denominator: 1.0 } ],
+         vec![ Dividend { timestamp: 2, amount: 1.0 } ]
+      );
+
+      let factors = actions.factors(&bars, AdjustmentMode::TotalReturn);
+      let prices: Vec<f64> = factors.iter().map(|factor| factor.price).collect();
+      assert_eq!(vec![ 0.5 * 0.99, 0.5, 1.0 ], prices);
+      assert_eq!(vec![ 2.0, 2.0, 1.0 ], factors.iter().map(|factor| factor.volume).collect::<Vec<f64>>());
+
+      // dividends are ignored when only adjusting for splits
+      let factors = actions.factors(&bars, AdjustmentMode::Splits);
+      assert_eq!(0.5, factors[0].price);
+   }
+
+   #[test]
+   fn verify_split_and_dividend_on_the_same_bar() {
+      // a 2 for 1 split and a dividend of 1 on the new shares - 100 before is 50 on the split basis
+      let bars = vec![ bar(1, 100.0, 10), bar(2, 49.0, 20), bar(3, 49.0, 20) ];
+      let actions = CorporateActions::new(
+         vec![ Split { timestamp: 2, numerator: 2.0, denominator: 1.0 } ],
+         vec![ Dividend { timestamp: 2, amount: 1.0 } ]
+      );
+
+      let factors = actions.factors(&bars, AdjustmentMode::TotalReturn);
+      assert_eq!(0.5 * 0.98, factors[0].price);
+
+      // holding through both is flat, so the adjusted series is too
+      let adjusted = actions.back_adjust(&bars, AdjustmentMode::TotalReturn);
+      assert_eq!(vec![ 49.0, 49.0, 49.0 ], closes(&adjusted));
+   }
+}
\ No newline at end of file
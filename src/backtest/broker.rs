@@ -0,0 +1,333 @@
+use crate::orders::{ Fill, Order, OrderType, Position, Side, TimeInForce };
+
+/// How the price of market and stop fills moves against the order
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Slippage {
+   None,
+
+   /// A fixed amount per unit
+   Fixed(f64),
+
+   /// A fraction of the price
+   Percent(f64)
+}
+impl Slippage {
+   /// Moves a price against the given side
+   pub fn apply(&self, side: Side, price: f64) -> f64 {
+      let amount = match self {
+         Self::None => 0.0,
+         Self::Fixed(amount) => *amount,
+         Self::Percent(fraction) => price * fraction
+      };
+      price + side.sign() * amount
+   }
+}
+
+/// How the commission for a fill is charged
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Commission {
+   None,
+
+   /// A fixed amount per fill
+   Fixed(f64),
+
+   /// An amount per unit filled, with a minimum per fill
+   PerUnit { rate: f64, minimum: f64 },
+
+   /// A fraction of the value filled
+   Percent(f64)
+}
+impl Commission {
+   /// The commission for filling a quantity at a price
+   pub fn calculate(&self, quantity: f64, price: f64) -> f64 {
+      match self {
+         Self::None => 0.0,
+         Self::Fixed(amount) => *amount,
+         Self::PerUnit { rate, minimum } => (quantity * rate).max(*minimum),
+         Self::Percent(fraction) => quantity * price * fraction
+      }
+   }
+}
+
+/// A closed position - or the closed part of one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Trade {
+   /// `Buy` for a long position and `Sell` for a short one
+   pub side: Side,
+
+   /// The quantity closed - always positive
+   pub quantity: f64,
+
+   /// The timestamp the position was opened
+   pub entry_timestamp: i64,
+
+   /// The average price the position was opened at
+   pub entry_price: f64,
+
+   /// The timestamp the position was closed
+   pub exit_timestamp: i64,
+
+   /// The price the position was closed at
+   pub exit_price: f64,
+
+   /// The profit or loss, before commission
+   pub pnl: f64
+}
+impl Trade {
+   /// The profit or loss as a fraction of the entry value
+   pub fn return_fraction(&self) -> f64 { self.pnl / (self.quantity * self.entry_price) }
+}
+
+/// The state of an order waiting to be filled
+#[derive(Clone, Copy, Debug)]
+struct Working {
+   /// The best price since the order was placed - for trailing stops
+   best: f64,
+
+   /// Whether the stop of a stop limit order has been reached
+   triggered: bool,
+
+   /// Whether the order hasn't had a chance to fill yet
+   fresh: bool
+}
+impl Working {
+   fn is_stopped(&self, order: &Order, price: f64) -> bool {
+      match self.stop_price(order) {
+         Some(stop) => (price - stop) * order.side.sign() >= 0.0,
+         None => false
+      }
+   }
+
+   fn stop_price(&self, order: &Order) -> Option<f64> {
+      match order.order_type {
+         OrderType::Stop(stop) | OrderType::StopLimit { stop, .. } => Some(stop),
+         OrderType::TrailingStop(trail) => Some(trail.stop_price(order.side, self.best)),
+         _ => None
+      }
+   }
+
+   fn follow(&mut self, order: &Order, price: f64) {
+      self.best = match order.side {
+         Side::Buy => self.best.min(price),
+         Side::Sell => self.best.max(price)
+      };
+   }
+
+   /// The price the order fills at when the price gaps to a price
+   fn fill_at(&mut self, order: &Order, price: f64) -> Option<f64> {
+      match order.order_type {
+         OrderType::Market => Some(price),
+         OrderType::Limit(_) => Some(price).filter(|price| order.is_marketable(*price)),
+         OrderType::Stop(_) | OrderType::TrailingStop(_) if self.is_stopped(order, price) => Some(price),
+         OrderType::StopLimit { .. } => {
+            self.triggered = self.triggered || self.is_stopped(order, price);
+            Some(price).filter(|price| self.triggered && order.is_marketable(*price))
+         },
+         _ => {
+            self.follow(order, price);
+            None
+         }
+      }
+   }
+
+   /// The price the order fills at when the price moves continuously between two prices
+   fn fill_through(&mut self, order: &Order, from: f64, to: f64) -> Option<f64> {
+      let within = |price: f64| from.min(to) <= price && price <= from.max(to);
+      match order.order_type {
+         OrderType::Market => Some(from),
+         OrderType::Limit(limit) => Some(limit).filter(|limit| within(*limit)),
+         OrderType::Stop(stop) => Some(stop).filter(|stop| within(*stop)),
+         OrderType::TrailingStop(_) => {
+            let stop = self.stop_price(order).filter(|stop| within(*stop));
+            if stop.is_none() { self.follow(order, to) }
+            stop
+         },
+         OrderType::StopLimit { stop, limit } => {
+            let start = if self.triggered { from } else if within(stop) { stop } else { return None };
+            self.triggered = true;
+            if order.is_marketable(start) {
+               Some(start)
+            } else {
+               Some(limit).filter(|limit| start.min(to) <= *limit && *limit <= start.max(to))
+            }
+         }
+      }
+   }
+}
+
+/// Whether fills of an order are moved against it by slippage
+fn slips(order: &Order) -> bool { matches!(order.order_type, OrderType::Market | OrderType::Stop(_) | OrderType::TrailingStop(_)) }
+
+/// A simulated broker for a single instrument.
+///
+/// Orders are filled in full in the order they were submitted once the simulated price reaches them.
+/// Day orders expire at the end of the first bar they could fill in, and immediate or cancel and fill
+/// or kill orders are cancelled if they can't fill at the first price after they were submitted.
+/// There's no limit on the quantity available at a price, so an immediate or cancel order never
+/// fills in part and behaves the same as fill or kill.  There are no margin requirements, so cash can
+/// go negative and positions can be short.
+#[derive(Clone, Debug)]
+pub struct Broker {
+   cash: f64,
+   position: Position,
+
+   /// The timestamp and price of the latest simulated trade
+   timestamp: i64,
+   price: f64,
+
+   slippage: Slippage,
+   commission: Commission,
+
+   next_id: usize,
+   pending: Vec<(usize, Order)>,
+
+   /// The state of each pending order, in the same order
+   working: Vec<Working>,
+   fills: Vec<Fill>,
+   trades: Vec<Trade>
+}
+impl Broker {
+   pub(super) fn new(cash: f64, slippage: Slippage, commission: Commission) -> Self {
+      Self {
+         cash,
+         position: Position::default(),
+         timestamp: 0,
+         price: 0.0,
+         slippage,
+         commission,
+         next_id: 0,
+         pending: vec![],
+         working: vec![],
+         fills: vec![],
+         trades: vec![]
+      }
+   }
+
+   /// The cash balance
+   pub fn cash(&self) -> f64 { self.cash }
+
+   /// The quantity held - negative when short
+   pub fn position(&self) -> f64 { self.position.quantity }
+
+   /// The average price of the open position - 0 when flat
+   pub fn average_price(&self) -> f64 { self.position.average_price }
+
+   /// The open position, with its average price and realized profit or loss
+   pub fn open_position(&self) -> &Position { &self.position }
+
+   /// The latest simulated price
+   pub fn price(&self) -> f64 { self.price }
+
+   /// The cash plus the value of the position at the latest price
+   pub fn equity(&self) -> f64 { self.cash + self.position.market_value(self.price) }
+
+   /// The orders waiting to be filled, with their ids
+   pub fn orders(&self) -> &[(usize, Order)] { &self.pending }
+
+   /// All of the fills so far
+   pub fn fills(&self) -> &[Fill] { &self.fills }
+
+   /// All of the closed trades so far
+   pub fn trades(&self) -> &[Trade] { &self.trades }
+
+   /// Submits an order, returning its id.  The order is timestamped with the current time.
+   pub fn submit(&mut self, mut order: Order) -> usize {
+      let id = self.next_id;
+      self.next_id += 1;
+      order.timestamp = self.timestamp;
+      self.pending.push((id, order));
+      self.working.push(Working { best: self.price, triggered: false, fresh: true });
+      id
+   }
+
+   /// Cancels an order waiting to be filled - `false` if there wasn't one with the id
+   pub fn cancel(&mut self, id: usize) -> bool {
+      let count = self.pending.len();
+      self.retain(|(pending, _), _| *pending != id);
+      self.pending.len() != count
+   }
+
+   /// Cancels all of the orders waiting to be filled
+   pub fn cancel_all(&mut self) {
+      self.pending.clear();
+      self.working.clear();
+   }
+
+   pub(super) fn into_parts(self) -> (Vec<Fill>, Vec<Trade>) { (self.fills, self.trades) }
+
+   /// Moves the simulated price to a price, which may have gapped from the previous one
+   pub(super) fn trade_at(&mut self, timestamp: i64, price: f64) {
+      self.timestamp = timestamp;
+      self.price = price;
+      self.retain(|(_, order), _| match order.time_in_force {
+         TimeInForce::GoodTillDate(end) => timestamp <= end,
+         _ => true
+      });
+
+      self.execute(|order, working| working.fill_at(order, price));
+      self.retain(|(_, order), working| {
+         !(working.fresh && matches!(order.time_in_force, TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill))
+      });
+      for working in self.working.iter_mut() { working.fresh = false }
+   }
+
+   /// Moves the simulated price continuously from the current price to a price, trading every price between
+   pub(super) fn trade_through(&mut self, price: f64) {
+      let from = self.price;
+      self.price = price;
+      self.execute(|order, working| working.fill_through(order, from, price));
+   }
+
+   /// Expires day orders at the end of a bar
+   pub(super) fn end_bar(&mut self) {
+      self.retain(|(_, order), _| order.time_in_force != TimeInForce::Day);
+   }
+
+   /// Keeps the pending orders matching a predicate, along with their state
+   fn retain(&mut self, keep: impl Fn(&(usize, Order), &Working) -> bool) {
+      let (pending, working) = std::mem::take(&mut self.pending).into_iter()
+         .zip(std::mem::take(&mut self.working))
+         .filter(|(order, working)| keep(order, working))
+         .unzip();
+      self.pending = pending;
+      self.working = working;
+   }
+
+   fn execute(&mut self, mut price_for: impl FnMut(&Order, &mut Working) -> Option<f64>) {
+      let orders = std::mem::take(&mut self.pending).into_iter().zip(std::mem::take(&mut self.working));
+      for ((id, order), mut working) in orders {
+         match price_for(&order, &mut working) {
+            Some(price) => {
+               let price = if slips(&order) { self.slippage.apply(order.side, price) } else { price };
+               self.fill(order.side, order.quantity, price);
+            },
+            None => {
+               self.pending.push((id, order));
+               self.working.push(working);
+            }
+         }
+      }
+   }
+
+   fn fill(&mut self, side: Side, quantity: f64, price: f64) {
+      let commission = self.commission.calculate(quantity, price);
+      let fill = Fill { timestamp: self.timestamp, side, quantity, price, commission };
+      self.cash -= fill.signed_quantity() * price + commission;
+      self.fills.push(fill);
+
+      let before = self.position;
+      self.position.apply(&fill);
+      if let Some(held) = before.side().filter(|held| *held != side) {
+         let closed = quantity.min(before.quantity.abs());
+         self.trades.push(Trade {
+            side: held,
+            quantity: closed,
+            entry_timestamp: before.opened,
+            entry_price: before.average_price,
+            exit_timestamp: self.timestamp,
+            exit_price: price,
+            pnl: closed * (price - before.average_price) * held.sign()
+         });
+      }
+   }
+}
\ No newline at end of file
@@ -0,0 +1,306 @@
+//! Event driven backtesting of strategies against a series of bars.
+//!
+//! A [`Strategy`] is given each bar once it has completed and can submit orders to the simulated
+//! [`Broker`].  Orders are only filled against later prices, so a strategy never trades on a price
+//! it hasn't seen yet.  Within a bar the price is assumed to move from the open to the nearest of the
+//! high and low, then to the other, then to the close.
+
+mod broker;
+pub use broker::{ Broker, Commission, Slippage, Trade };
+
+use crate::Bar;
+use crate::metrics::{ EquityPoint, Performance };
+use crate::orders::Fill;
+
+/// A trading strategy run by a backtest
+pub trait Strategy {
+   /// Called once each bar has completed - usually where indicators are committed
+   fn on_bar(&mut self, bar: &Bar, broker: &mut Broker);
+
+   /// Called as a bar is forming, with the bar so far - usually where indicators are updated.
+   ///
+   /// Only called when the backtest has intra-bar updates enabled.
+   fn on_update(&mut self, _bar: &Bar, _broker: &mut Broker) {}
+}
+impl<F: FnMut(&Bar, &mut Broker)> Strategy for F {
+   fn on_bar(&mut self, bar: &Bar, broker: &mut Broker) { self(bar, broker) }
+}
+
+/// The settings for running a backtest
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Backtest {
+   /// The cash at the start of the backtest
+   pub initial_cash: f64,
+
+   /// How market and stop fills move against the order
+   pub slippage: Slippage,
+
+   /// How commission is charged for each fill
+   pub commission: Commission,
+
+   /// Whether the strategy is called with intra-bar updates for the open, high and low
+   pub intrabar_updates: bool
+}
+impl Backtest {
+   /// Creates a backtest with no slippage, commission or intra-bar updates
+   pub fn new(initial_cash: f64) -> Self {
+      Self { initial_cash, slippage: Slippage::None, commission: Commission::None, intrabar_updates: false }
+   }
+
+   /// Runs a strategy against bars sorted by timestamp
+   pub fn run<S: Strategy>(&self, strategy: &mut S, bars: &[Bar]) -> BacktestResult {
+      let mut broker = Broker::new(self.initial_cash, self.slippage, self.commission);
+      let mut equity_curve = Vec::with_capacity(bars.len());
+
+      for bar in bars {
+         let path = price_path(bar);
+         broker.trade_at(bar.timestamp, path[0]);
+         for (index, price) in path.iter().enumerate() {
+            if index > 0 { broker.trade_through(*price) }
+            if self.intrabar_updates && index < path.len() - 1 {
+               strategy.on_update(&partial_bar(bar, &path[..=index]), &mut broker);
+               broker.trade_at(bar.timestamp, *price);
+            }
+         }
+
+         broker.end_bar();
+         strategy.on_bar(bar, &mut broker);
+         equity_curve.push(EquityPoint { timestamp: bar.timestamp, value: broker.equity() });
+      }
+
+      let (cash, position) = (broker.cash(), broker.position());
+      let (fills, trades) = broker.into_parts();
+      BacktestResult { fills, trades, equity_curve, cash, position }
+   }
+}
+
+/// The outcome of a backtest
+#[derive(Clone, Debug)]
+pub struct BacktestResult {
+   /// Every fill in the order they happened
+   pub fills: Vec<Fill>,
+
+   /// Every closed trade in the order they closed
+   pub trades: Vec<Trade>,
+
+   /// The equity at the close of each bar
+   pub equity_curve: Vec<EquityPoint>,
+
+   /// The cash at the end of the backtest
+   pub cash: f64,
+
+   /// The position at the end of the backtest
+   pub position: f64
+}
+impl BacktestResult {
+   /// The equity at the close of the last bar
+   pub fn final_equity(&self) -> Option<f64> { self.equity_curve.last().map(|point| point.value) }
+
+   /// The total commission paid
+   pub fn commission(&self) -> f64 { self.fills.iter().map(|fill| fill.commission).sum() }
+
+   /// The performance metrics of the equity curve, for bars with the given number of periods per year
+   pub fn performance(&self, periods_per_year: f64) -> Performance { Performance::new(&self.equity_curve, periods_per_year) }
+}
+
+/// The prices a bar is assumed to have traded through, in order
+fn price_path(bar: &Bar) -> [f64; 4] {
+   if bar.high - bar.open < bar.open - bar.low {
+      [ bar.open, bar.high, bar.low, bar.close ]
+   } else {
+      [ bar.open, bar.low, bar.high, bar.close ]
+   }
+}
+
+/// The bar so far, having traded through the given prices
+fn partial_bar(bar: &Bar, prices: &[f64]) -> Bar {
+   let last = prices[prices.len() - 1];
+   Bar {
+      timestamp: bar.timestamp,
+      open: bar.open,
+      high: prices.iter().copied().fold(f64::MIN, f64::max),
+      low: prices.iter().copied().fold(f64::MAX, f64::min),
+      close: last,
+      volume: None
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::orders::{ Order, Side, TimeInForce, Trail };
+
+   fn assert_close(expected: f64, actual: f64) {
+      assert!((expected - actual).abs() < 1e-9, "expected {} but was {}", expected, actual);
+   }
+
+   fn bar(timestamp: i64, open: f64, high: f64, low: f64, close: f64) -> Bar {
+      Bar { timestamp, open, high, low, close, volume: None }
+   }
+
+   fn bars() -> Vec<Bar> {
+      vec![
+         bar(0, 100.0, 101.0, 99.0, 100.0),
+         bar(1, 102.0, 106.0, 101.0, 105.0),
+         bar(2, 104.0, 108.0, 96.0, 97.0),
+         bar(3, 98.0, 99.0, 90.0, 92.0)
+      ]
+   }
+
+   #[test]
+   fn verify_market_orders() {
+      let mut strategy = |bar: &Bar, broker: &mut Broker| match bar.timestamp {
+         0 => { broker.submit(Order::market(Side::Buy, 10.0)); },
+         1 => { broker.submit(Order::market(Side::Sell, 10.0)); },
+         _ => {}
+      };
+      let backtest = Backtest { commission: Commission::Fixed(1.0), slippage: Slippage::Fixed(0.5), ..Backtest::new(10_000.0) };
+      let result = backtest.run(&mut strategy, &bars());
+
+      // bought at the next open plus slippage and sold at the following open less slippage
+      assert_eq!(2, result.fills.len());
+      assert_eq!(102.5, result.fills[0].price);
+      assert_eq!(103.5, result.fills[1].price);
+      assert_eq!(1, result.trades.len());
+      assert_close(10.0, result.trades[0].pnl);
+      assert_eq!((1, 2), (result.trades[0].entry_timestamp, result.trades[0].exit_timestamp));
+
+      assert_eq!(0.0, result.position);
+      assert_close(10_008.0, result.cash);
+      assert_close(2.0, result.commission());
+      assert_eq!(10_000.0, result.equity_curve[0].value);
+      assert_close(10_000.0 - 1.0 + 25.0, result.equity_curve[1].value);
+      assert_eq!(Some(result.cash), result.final_equity());
+   }
+
+   #[test]
+   fn verify_limit_and_stop_orders() {
+      let mut strategy = |bar: &Bar, broker: &mut Broker| if bar.timestamp == 0 {
+         broker.submit(Order::limit(Side::Buy, 1.0, 101.5));
+         broker.submit(Order::stop(Side::Buy, 1.0, 107.0));
+         broker.submit(Order::limit(Side::Sell, 1.0, 200.0));
+      };
+      let result = Backtest::new(1_000.0).run(&mut strategy, &bars());
+
+      // the limit fills at its price as the bar falls, the stop fills on the second bar and the last never fills
+      assert_eq!(vec![ (1, 101.5), (2, 107.0) ], result.fills.iter().map(|fill| (fill.timestamp, fill.price)).collect::<Vec<_>>());
+      assert_eq!(2.0, result.position);
+   }
+
+   #[test]
+   fn verify_gaps_fill_at_open() {
+      let mut strategy = |bar: &Bar, broker: &mut Broker| if bar.timestamp == 1 {
+         broker.submit(Order::stop(Side::Sell, 1.0, 99.0));
+         broker.submit(Order::limit(Side::Buy, 1.0, 99.0));
+      };
+      let result = Backtest::new(1_000.0).run(&mut strategy, &bars());
+
+      // both prices are reached as the third bar falls from its high, and the fourth bar's open misses neither
+      assert_eq!(vec![ 99.0, 99.0 ], result.fills.iter().map(|fill| fill.price).collect::<Vec<f64>>());
+
+      let mut strategy = |bar: &Bar, broker: &mut Broker| if bar.timestamp == 2 {
+         broker.submit(Order::stop(Side::Sell, 1.0, 99.5));
+         broker.submit(Order::limit(Side::Buy, 1.0, 99.5));
+      };
+      let result = Backtest::new(1_000.0).run(&mut strategy, &bars());
+      assert_eq!(vec![ 98.0, 98.0 ], result.fills.iter().map(|fill| fill.price).collect::<Vec<f64>>());
+   }
+
+   #[test]
+   fn verify_short_and_reverse() {
+      let mut strategy = |bar: &Bar, broker: &mut Broker| match bar.timestamp {
+         0 => { broker.submit(Order::market(Side::Sell, 2.0)); },
+         1 => { broker.submit(Order::market(Side::Buy, 3.0)); },
+         _ => {}
+      };
+      let result = Backtest::new(1_000.0).run(&mut strategy, &bars());
+
+      let trade = result.trades[0];
+      assert_eq!(Side::Sell, trade.side);
+      assert_eq!(2.0, trade.quantity);
+      assert_close(-4.0, trade.pnl);
+      assert_eq!(1.0, result.position);
+      assert_close(1_000.0 - 4.0 - 104.0 + 92.0, result.final_equity().unwrap());
+   }
+
+   #[test]
+   fn verify_intrabar_updates() {
+      struct Breakout { updates: Vec<f64>, order: Option<usize> }
+      impl Strategy for Breakout {
+         fn on_bar(&mut self, _bar: &Bar, _broker: &mut Broker) {}
+
+         fn on_update(&mut self, bar: &Bar, broker: &mut Broker) {
+            self.updates.push(bar.close);
+            if bar.high > 105.0 && self.order.is_none() {
+               self.order = Some(broker.submit(Order::market(Side::Buy, 1.0)));
+            }
+         }
+      }
+
+      let mut strategy = Breakout { updates: vec![], order: None };
+      let backtest = Backtest { intrabar_updates: true, ..Backtest::new(1_000.0) };
+      let result = backtest.run(&mut strategy, &bars());
+
+      // open, low then high for the rising second bar - buying at the high it was seen at
+      assert_eq!(vec![ 100.0, 99.0, 101.0, 102.0, 101.0, 106.0 ], strategy.updates[..6].to_vec());
+      assert_eq!(1, result.fills.len());
+      assert_eq!((1, 106.0), (result.fills[0].timestamp, result.fills[0].price));
+   }
+
+   #[test]
+   fn verify_stop_limit_and_trailing_stop() {
+      let mut strategy = |bar: &Bar, broker: &mut Broker| if bar.timestamp == 0 {
+         broker.submit(Order::stop_limit(Side::Buy, 1.0, 105.5, 105.0));
+         broker.submit(Order::trailing_stop(Side::Sell, 1.0, Trail::Amount(5.0)));
+      };
+      let result = Backtest::new(1_000.0).run(&mut strategy, &bars());
+
+      // the stop limit triggers on the rise to 106 and fills on the way back to 105, and the stop
+      // trails from the high of 108 until the fall to 103
+      assert_eq!(vec![ (1, 105.0), (2, 103.0) ], result.fills.iter().map(|fill| (fill.timestamp, fill.price)).collect::<Vec<_>>());
+   }
+
+   #[test]
+   fn verify_time_in_force() {
+      let mut strategy = |bar: &Bar, broker: &mut Broker| if bar.timestamp == 0 {
+         broker.submit(Order { time_in_force: TimeInForce::Day, ..Order::limit(Side::Buy, 1.0, 101.5) });
+         broker.submit(Order { time_in_force: TimeInForce::Day, ..Order::limit(Side::Buy, 1.0, 97.0) });
+         broker.submit(Order { time_in_force: TimeInForce::ImmediateOrCancel, ..Order::limit(Side::Buy, 1.0, 101.5) });
+         broker.submit(Order { time_in_force: TimeInForce::GoodTillDate(1), ..Order::limit(Side::Sell, 1.0, 107.0) });
+      };
+      let result = Backtest::new(1_000.0).run(&mut strategy, &bars());
+
+      // only the first day order fills - the immediate order can't fill at the open and the others expire
+      assert_eq!(vec![ 101.5 ], result.fills.iter().map(|fill| fill.price).collect::<Vec<f64>>());
+   }
+
+   #[test]
+   fn verify_immediate_orders() {
+      // with no limit on the quantity at a price, fill or kill is the same as immediate or cancel -
+      // a limit of 102.5 fills in full at the open of 102, and a limit of 101.5 is cancelled
+      for time_in_force in [ TimeInForce::ImmediateOrCancel, TimeInForce::FillOrKill ].iter().copied() {
+         let mut strategy = |bar: &Bar, broker: &mut Broker| if bar.timestamp == 0 {
+            broker.submit(Order { time_in_force, ..Order::limit(Side::Buy, 5.0, 102.5) });
+            broker.submit(Order { time_in_force, ..Order::limit(Side::Buy, 1.0, 101.5) });
+         };
+         let result = Backtest::new(1_000.0).run(&mut strategy, &bars());
+
+         assert_eq!(vec![ (1, 5.0, 102.0) ], result.fills.iter().map(|fill| (fill.timestamp, fill.quantity, fill.price)).collect::<Vec<_>>());
+         assert_eq!(5.0, result.position);
+      }
+   }
+
+   #[test]
+   fn verify_cancel() {
+      let mut strategy = |bar: &Bar, broker: &mut Broker| if bar.timestamp == 0 {
+         let id = broker.submit(Order::limit(Side::Buy, 1.0, 50.0));
+         assert!(broker.cancel(id));
+         assert!(!broker.cancel(id));
+         assert!(broker.orders().is_empty());
+      };
+      let result = Backtest::new(1_000.0).run(&mut strategy, &bars());
+      assert!(result.fills.is_empty());
+      assert_eq!(4, result.performance(252.0).returns().len() + 1);
+   }
+}
\ No newline at end of file
@@ -6,7 +6,7 @@ use super::Timestamped;
 ///
 ///  * Bars are ordered by the timestamp.
 ///  * The unit of time is defined by whatever function returns the Bar, but is
-/// generally one day.
+///    generally one day.
 #[derive(Clone, Copy, Debug)]
 pub struct Bar {
    /// A time during the unit of time - usually the time at open.
@@ -46,13 +46,13 @@ impl Timestamped for Bar {
 #[cfg(test)]
 mod tests {
    #[cfg(feature = "formatting")]
-   use chrono::{ TimeZone, Utc };
+   use chrono::{ NaiveDate, TimeZone, Utc };
    use super::{ Bar, Timestamped };
 
    #[cfg(feature = "formatting")]
    #[test]
    fn verify_datetime() {
-      let dt = Utc.ymd(1970, 1, 1).and_hms_milli(0, 0, 3, 123);
+      let dt = Utc.from_utc_datetime(&NaiveDate::from_ymd_opt(1970, 1, 1).unwrap().and_hms_milli_opt(0, 0, 3, 123).unwrap());
       let bar = Bar { timestamp: 3123, open: 0.0, high: 0.0, low: 0.0, close: 0.0, volume: None };
       assert_eq!(dt, bar.datetime())
    }
@@ -0,0 +1,400 @@
+use chrono::NaiveDate;
+use crate::math::brent;
+use super::{ Bond, DayCount, Frequency, coupon_dates };
+
+/// The most passes made over the instruments when bootstrapping with a non-local interpolation
+const MAX_PASSES: usize = 50;
+
+/// How discount factors are interpolated between the nodes of a curve
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Interpolation {
+   /// Zero rates are linear in time
+   Linear,
+
+   /// The log of the discount factor is linear in time - forward rates are constant between nodes
+   LogLinearDiscount,
+
+   /// Hagan and West's monotone convex method - forward rates are continuous and keep the
+   /// monotonicity of the discrete forwards
+   MonotoneConvex
+}
+
+/// A quoted instrument a curve is bootstrapped from - all starting on the curve's reference date
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurveInstrument {
+   /// A deposit paying simple interest at maturity
+   Deposit { maturity: NaiveDate, rate: f64, day_count: DayCount },
+
+   /// An interest rate future priced at 100 less its rate - without a convexity adjustment
+   Future { start: NaiveDate, end: NaiveDate, price: f64, day_count: DayCount },
+
+   /// A par swap exchanging a fixed rate for floating rates on the same curve
+   Swap { maturity: NaiveDate, rate: f64, frequency: Frequency, day_count: DayCount },
+
+   /// A bond at a clean price
+   Bond { bond: Bond, price: f64 }
+}
+impl CurveInstrument {
+   /// The date of the last payment, where the curve gets a node for the instrument
+   pub fn maturity(&self) -> NaiveDate {
+      match self {
+         Self::Deposit { maturity, .. } | Self::Swap { maturity, .. } => *maturity,
+         Self::Future { end, .. } => *end,
+         Self::Bond { bond, .. } => bond.maturity
+      }
+   }
+
+   /// The value of the instrument on a curve - zero when the curve prices it at its quote
+   pub fn value(&self, curve: &YieldCurve) -> f64 {
+      let reference = curve.reference();
+      match *self {
+         Self::Deposit { maturity, rate, day_count } => curve.discount(maturity) * (1.0 + rate * day_count.year_fraction(reference, maturity)) - 1.0,
+         Self::Future { start, end, price, day_count } => {
+            let rate = (100.0 - price) / 100.0;
+            curve.discount(end) * (1.0 + rate * day_count.year_fraction(start, end)) - curve.discount(start)
+         },
+         Self::Swap { maturity, rate, frequency, day_count } => {
+            let dates = coupon_dates(reference, maturity, frequency);
+            let annuity: f64 = dates.windows(2).map(|period| day_count.year_fraction(period[0], period[1]) * curve.discount(period[1])).sum();
+            rate * annuity - (1.0 - curve.discount(maturity))
+         },
+         Self::Bond { bond, price } => {
+            let value: f64 = bond.cash_flows(reference).iter().map(|(date, amount)| amount * curve.discount(*date)).sum();
+            value - price - bond.accrued_interest(reference)
+         }
+      }
+   }
+}
+
+/// Discount factors from a reference date, with continuously compounded zero and forward rates
+#[derive(Clone, Debug, PartialEq)]
+pub struct YieldCurve {
+   reference: NaiveDate,
+   day_count: DayCount,
+   interpolation: Interpolation,
+
+   /// The times in years and discount factors of the nodes, sorted by time
+   nodes: Vec<(f64, f64)>
+}
+impl YieldCurve {
+   /// Creates a curve from discount factors
+   pub fn new(reference: NaiveDate, day_count: DayCount, interpolation: Interpolation, discounts: &[(NaiveDate, f64)]) -> Self {
+      let mut nodes: Vec<(f64, f64)> = discounts.iter()
+         .map(|(date, discount)| (day_count.year_fraction(reference, *date), *discount))
+         .filter(|(time, _)| *time > 0.0)
+         .collect();
+      nodes.sort_by(|a, b| a.0.total_cmp(&b.0));
+      nodes.dedup_by(|a, b| a.0 == b.0);
+      Self { reference, day_count, interpolation, nodes }
+   }
+
+   /// Creates a curve from continuously compounded zero rates
+   pub fn from_zero_rates(reference: NaiveDate, day_count: DayCount, interpolation: Interpolation, rates: &[(NaiveDate, f64)]) -> Self {
+      let discounts: Vec<(NaiveDate, f64)> = rates.iter()
+         .map(|(date, rate)| (*date, (-rate * day_count.year_fraction(reference, *date)).exp()))
+         .collect();
+      Self::new(reference, day_count, interpolation, &discounts)
+   }
+
+   /// Builds a curve with a node at the maturity of each instrument so that each is priced at its
+   /// quote - `None` if an instrument can't be matched
+   pub fn bootstrap(reference: NaiveDate, day_count: DayCount, interpolation: Interpolation, instruments: &[CurveInstrument]) -> Option<Self> {
+      let mut instruments = instruments.to_vec();
+      instruments.sort_by_key(|instrument| instrument.maturity());
+
+      let mut curve = Self::new(reference, day_count, interpolation, &[]);
+      for instrument in instruments.iter() {
+         let time = day_count.year_fraction(reference, instrument.maturity());
+         let guess = curve.discount_at(time);
+         curve.nodes.push((time, guess));
+         curve.solve(instrument, curve.nodes.len() - 1)?;
+      }
+
+      // the monotone convex forwards at a node depend on the next node, so earlier nodes are
+      // solved again until the curve settles
+      if interpolation == Interpolation::MonotoneConvex {
+         for _ in 0..MAX_PASSES {
+            let before = curve.nodes.clone();
+            for (index, instrument) in instruments.iter().enumerate() { curve.solve(instrument, index)? }
+
+            let change = before.iter().zip(curve.nodes.iter()).map(|(a, b)| (a.1 - b.1).abs()).fold(0.0, f64::max);
+            if change < 1e-14 { break }
+         }
+      }
+
+      Some(curve)
+   }
+
+   /// The date the curve discounts to
+   pub fn reference(&self) -> NaiveDate { self.reference }
+
+   /// How dates are turned into times
+   pub fn day_count(&self) -> DayCount { self.day_count }
+
+   /// How discount factors are interpolated between nodes
+   pub fn interpolation(&self) -> Interpolation { self.interpolation }
+
+   /// The times in years and discount factors of the nodes
+   pub fn nodes(&self) -> &[(f64, f64)] { &self.nodes }
+
+   /// The time in years from the reference date to a date
+   pub fn time(&self, date: NaiveDate) -> f64 { self.day_count.year_fraction(self.reference, date) }
+
+   /// The value today of a unit paid on a date
+   pub fn discount(&self, date: NaiveDate) -> f64 { self.discount_at(self.time(date)) }
+
+   /// The value today of a unit paid at a time in years
+   pub fn discount_at(&self, time: f64) -> f64 { self.log_discount(time).exp() }
+
+   /// The continuously compounded zero rate to a date
+   pub fn zero_rate(&self, date: NaiveDate) -> f64 { self.zero_rate_at(self.time(date)) }
+
+   /// The continuously compounded zero rate to a time in years
+   pub fn zero_rate_at(&self, time: f64) -> f64 {
+      if time <= 0.0 { return self.nodes.first().map_or(0.0, |(time, discount)| -discount.ln() / time) }
+      -self.log_discount(time) / time
+   }
+
+   /// The continuously compounded forward rate between two dates
+   pub fn forward_rate(&self, start: NaiveDate, end: NaiveDate) -> f64 { self.forward_rate_at(self.time(start), self.time(end)) }
+
+   /// The continuously compounded forward rate between two times in years
+   pub fn forward_rate_at(&self, start: f64, end: f64) -> f64 {
+      if end <= start { return self.forward_rate_at(start, start + 1e-6) }
+      (self.log_discount(start) - self.log_discount(end)) / (end - start)
+   }
+
+   /// The curve with every zero rate moved by an amount - e.g. 0.0001 for a basis point
+   pub fn shifted(&self, amount: f64) -> Self {
+      let nodes = self.nodes.iter().map(|(time, discount)| (*time, discount * (-amount * time).exp())).collect();
+      Self { nodes, ..self.clone() }
+   }
+
+   /// The curve with the zero rate of a single node moved by an amount, for key rate sensitivities
+   pub fn bumped(&self, node: usize, amount: f64) -> Self {
+      let mut curve = self.clone();
+      if let Some((time, discount)) = curve.nodes.get_mut(node) { *discount *= (-amount * *time).exp() }
+      curve
+   }
+
+   /// Moves the discount factor of a node until the instrument is priced at its quote
+   fn solve(&mut self, instrument: &CurveInstrument, node: usize) -> Option<()> {
+      let value = |discount: f64| {
+         let mut curve = self.clone();
+         curve.nodes[node].1 = discount;
+         instrument.value(&curve)
+      };
+      self.nodes[node].1 = brent(value, 1e-8, 10.0, 1e-15, 200)?;
+      Some(())
+   }
+
+   /// The log of the discount factor at a time in years
+   fn log_discount(&self, time: f64) -> f64 {
+      if time <= 0.0 || self.nodes.is_empty() { return 0.0 }
+
+      // every interpolation goes through the reference date with a discount factor of one
+      let times: Vec<f64> = std::iter::once(0.0).chain(self.nodes.iter().map(|node| node.0)).collect();
+      let logs: Vec<f64> = std::iter::once(0.0).chain(self.nodes.iter().map(|node| node.1.ln())).collect();
+      let last = times.len() - 1;
+      let segment = times.partition_point(|t| *t < time).clamp(1, last);
+
+      match self.interpolation {
+         Interpolation::Linear => {
+            let zero = |index: usize| -logs[index] / times[index];
+            let rate = match segment {
+               _ if time >= times[last] => zero(last),
+               1 => zero(1),
+               _ => {
+                  let weight = (time - times[segment - 1]) / (times[segment] - times[segment - 1]);
+                  zero(segment - 1) * (1.0 - weight) + zero(segment) * weight
+               }
+            };
+            -rate * time
+         },
+         Interpolation::LogLinearDiscount => {
+            let slope = (logs[segment] - logs[segment - 1]) / (times[segment] - times[segment - 1]);
+            logs[segment - 1] + slope * (time - times[segment - 1])
+         },
+         Interpolation::MonotoneConvex => monotone_convex(&times, &logs, time)
+      }
+   }
+}
+
+/// The log discount factor at a time with Hagan and West's monotone convex interpolation of the
+/// forwards between nodes, extrapolating the forward at the last node
+fn monotone_convex(times: &[f64], logs: &[f64], time: f64) -> f64 {
+   let last = times.len() - 1;
+
+   // the discrete forward over each segment, and the instantaneous forward at each node
+   let discrete: Vec<f64> = (1..=last).map(|index| (logs[index - 1] - logs[index]) / (times[index] - times[index - 1])).collect();
+   let discrete = |index: usize| discrete[index - 1];
+   let mut node = vec![ 0.0; last + 1 ];
+   for index in 1..last {
+      let span = times[index + 1] - times[index - 1];
+      node[index] = (times[index] - times[index - 1]) / span * discrete(index + 1) + (times[index + 1] - times[index]) / span * discrete(index);
+   }
+   if last > 1 {
+      node[0] = discrete(1) - (node[1] - discrete(1)) / 2.0;
+      node[last] = discrete(last) - (node[last - 1] - discrete(last)) / 2.0;
+   } else {
+      node = vec![ discrete(1); 2 ];
+   }
+
+   if time >= times[last] { return logs[last] - node[last] * (time - times[last]) }
+
+   let segment = times.partition_point(|t| *t < time).clamp(1, last);
+   let width = times[segment] - times[segment - 1];
+   let x = (time - times[segment - 1]) / width;
+   let (g0, g1) = (node[segment - 1] - discrete(segment), node[segment] - discrete(segment));
+
+   // the integral from the start of the segment of the forward less the discrete forward
+   let integral = if g0 == 0.0 && g1 == 0.0 {
+      0.0
+   } else if (g0 < 0.0 && -0.5 * g0 <= g1 && g1 <= -2.0 * g0) || (g0 > 0.0 && -0.5 * g0 >= g1 && g1 >= -2.0 * g0) {
+      g0 * (x - 2.0 * x * x + x.powi(3)) + g1 * (x.powi(3) - x * x)
+   } else if (g0 < 0.0 && g1 > -2.0 * g0) || (g0 > 0.0 && g1 < -2.0 * g0) {
+      let eta = (g1 + 2.0 * g0) / (g1 - g0);
+      if x <= eta { g0 * x } else { g0 * x + (g1 - g0) * (x - eta).powi(3) / (3.0 * (1.0 - eta).powi(2)) }
+   } else if (g0 > 0.0 && 0.0 > g1 && g1 > -0.5 * g0) || (g0 < 0.0 && 0.0 < g1 && g1 < -0.5 * g0) {
+      let eta = 3.0 * g1 / (g1 - g0);
+      if x < eta { g1 * x + (g0 - g1) * eta / 3.0 * (1.0 - ((eta - x) / eta).powi(3)) } else { g1 * x + (g0 - g1) * eta / 3.0 }
+   } else {
+      let eta = g1 / (g1 + g0);
+      let a = -g0 * g1 / (g0 + g1);
+      if x <= eta {
+         a * x + (g0 - a) * eta / 3.0 * (1.0 - ((eta - x) / eta).powi(3))
+      } else {
+         a * x + (g0 - a) * eta / 3.0 + (g1 - a) * (1.0 - eta) / 3.0 * ((x - eta) / (1.0 - eta)).powi(3)
+      }
+   };
+
+   logs[segment - 1] - discrete(segment) * (time - times[segment - 1]) - width * integral
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn date(year: i32, month: u32, day: u32) -> NaiveDate { NaiveDate::from_ymd_opt(year, month, day).unwrap() }
+
+   fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+      assert!((expected - actual).abs() < tolerance, "expected {} but was {}", expected, actual);
+   }
+
+   const INTERPOLATIONS: [Interpolation; 3] = [ Interpolation::Linear, Interpolation::LogLinearDiscount, Interpolation::MonotoneConvex ];
+
+   #[test]
+   fn verify_bond_bootstrap() {
+      // Hull - Options, Futures and Other Derivatives, table 4.3 - zero coupon bonds for 3 months to
+      // a year, then semi-annual coupon bonds, with a face value of 100
+      let reference = date(2024, 1, 1);
+      let bond = |months: u32, coupon: f64, frequency: Frequency, price: f64| {
+         let bond = Bond { day_count: DayCount::Thirty360, ..Bond::new(reference, reference + chrono::Months::new(months), coupon, frequency) };
+         CurveInstrument::Bond { bond, price }
+      };
+      let instruments = [
+         bond(3, 0.0, Frequency::Quarterly, 97.5),
+         bond(6, 0.0, Frequency::SemiAnnual, 94.9),
+         bond(12, 0.0, Frequency::Annual, 90.0),
+         bond(18, 0.08, Frequency::SemiAnnual, 96.0),
+         bond(24, 0.12, Frequency::SemiAnnual, 101.6)
+      ];
+
+      for interpolation in INTERPOLATIONS.iter().copied() {
+         let curve = YieldCurve::bootstrap(reference, DayCount::Thirty360, interpolation, &instruments).unwrap();
+         let zeros: Vec<f64> = [ 0.25, 0.5, 1.0, 1.5, 2.0 ].iter().map(|time| curve.zero_rate_at(*time)).collect();
+         for (expected, actual) in [ 0.10127, 0.10469, 0.10536, 0.10681, 0.10808 ].iter().zip(zeros) {
+            assert_close(*expected, actual, 0.000005);
+         }
+      }
+   }
+
+   #[test]
+   fn verify_swap_bootstrap() {
+      // Hull - Options, Futures and Other Derivatives, the swap zero curve example - zero rates of
+      // 4%, 4.5% and 4.8% for 6 to 18 months and a 2 year semi-annual swap rate of 5%
+      let reference = date(2024, 1, 1);
+      let deposit = |months: u32, zero: f64| {
+         let time = months as f64 / 12.0;
+         CurveInstrument::Deposit { maturity: reference + chrono::Months::new(months), rate: ((zero * time).exp() - 1.0) / time, day_count: DayCount::Thirty360 }
+      };
+      let instruments = [
+         deposit(6, 0.04),
+         deposit(12, 0.045),
+         deposit(18, 0.048),
+         CurveInstrument::Swap { maturity: date(2026, 1, 1), rate: 0.05, frequency: Frequency::SemiAnnual, day_count: DayCount::Thirty360 }
+      ];
+
+      let curve = YieldCurve::bootstrap(reference, DayCount::Thirty360, Interpolation::LogLinearDiscount, &instruments).unwrap();
+      assert_close(0.045, curve.zero_rate(date(2025, 1, 1)), 1e-12);
+      assert_close(0.04953, curve.zero_rate(date(2026, 1, 1)), 0.000005);
+   }
+
+   #[test]
+   fn verify_mixed_bootstrap() {
+      let reference = date(2024, 3, 20);
+      let instruments = [
+         CurveInstrument::Deposit { maturity: date(2024, 4, 22), rate: 0.053, day_count: DayCount::Actual360 },
+         CurveInstrument::Deposit { maturity: date(2024, 6, 20), rate: 0.0535, day_count: DayCount::Actual360 },
+         CurveInstrument::Future { start: date(2024, 6, 19), end: date(2024, 9, 18), price: 94.7, day_count: DayCount::Actual360 },
+         CurveInstrument::Future { start: date(2024, 9, 18), end: date(2024, 12, 18), price: 95.0, day_count: DayCount::Actual360 },
+         CurveInstrument::Swap { maturity: date(2027, 3, 20), rate: 0.045, frequency: Frequency::Annual, day_count: DayCount::Thirty360 },
+         CurveInstrument::Swap { maturity: date(2029, 3, 20), rate: 0.042, frequency: Frequency::Annual, day_count: DayCount::Thirty360 },
+         CurveInstrument::Swap { maturity: date(2034, 3, 20), rate: 0.041, frequency: Frequency::Annual, day_count: DayCount::Thirty360 }
+      ];
+
+      for interpolation in INTERPOLATIONS.iter().copied() {
+         let curve = YieldCurve::bootstrap(reference, DayCount::Actual365Fixed, interpolation, &instruments).unwrap();
+         assert_eq!(7, curve.nodes().len());
+         for instrument in instruments.iter() { assert_close(0.0, instrument.value(&curve), 1e-12) }
+      }
+   }
+
+   #[test]
+   fn verify_interpolation() {
+      let reference = date(2024, 1, 1);
+      let rates = [ (date(2025, 1, 1), 0.03), (date(2026, 1, 1), 0.04), (date(2028, 1, 1), 0.042) ];
+      let curve = |interpolation| YieldCurve::from_zero_rates(reference, DayCount::Thirty360, interpolation, &rates);
+
+      for interpolation in INTERPOLATIONS.iter().copied() {
+         let curve = curve(interpolation);
+         for (date, rate) in rates.iter() { assert_close(*rate, curve.zero_rate(*date), 1e-12) }
+      }
+
+      let linear = curve(Interpolation::Linear);
+      assert_close(0.035, linear.zero_rate_at(1.5), 1e-12);
+      assert_close(0.03, linear.zero_rate_at(0.5), 1e-12);
+
+      // constant forwards between nodes - 3% for the first year then 5% for the second
+      let log_linear = curve(Interpolation::LogLinearDiscount);
+      assert_close(0.05, log_linear.forward_rate_at(1.2, 1.7), 1e-12);
+      assert_close(0.05, log_linear.forward_rate(date(2025, 1, 1), date(2026, 1, 1)), 1e-12);
+
+      // continuous forwards that still average to the discrete forward of each segment
+      let convex = curve(Interpolation::MonotoneConvex);
+      for node in [ 1.0, 2.0 ].iter() {
+         assert_close(convex.forward_rate_at(node - 1e-7, *node), convex.forward_rate_at(*node, node + 1e-7), 1e-5);
+      }
+      assert_close(0.05, convex.forward_rate_at(1.0, 2.0), 1e-12);
+      assert!((0..100).all(|step| convex.forward_rate_at(step as f64 * 0.04, step as f64 * 0.04 + 0.01) > 0.0));
+   }
+
+   #[test]
+   fn verify_bumps() {
+      let reference = date(2024, 1, 1);
+      let rates = [ (date(2025, 1, 1), 0.03), (date(2026, 1, 1), 0.04), (date(2028, 1, 1), 0.042) ];
+      let curve = YieldCurve::from_zero_rates(reference, DayCount::Thirty360, Interpolation::Linear, &rates);
+
+      let shifted = curve.shifted(0.0001);
+      assert_close(0.0301, shifted.zero_rate_at(1.0), 1e-12);
+      assert_close(0.0351, shifted.zero_rate_at(1.5), 1e-12);
+      assert_close(curve.forward_rate_at(1.0, 2.0) + 0.0001, shifted.forward_rate_at(1.0, 2.0), 1e-12);
+
+      let bumped = curve.bumped(1, 0.0001);
+      assert_close(0.03, bumped.zero_rate_at(1.0), 1e-12);
+      assert_close(0.03505, bumped.zero_rate_at(1.5), 1e-12);
+      assert_close(0.0401, bumped.zero_rate_at(2.0), 1e-12);
+      assert_close(0.042, bumped.zero_rate_at(4.0), 1e-12);
+   }
+}
\ No newline at end of file
@@ -0,0 +1,142 @@
+use chrono::{ Datelike, NaiveDate };
+use super::Frequency;
+use super::schedule::{ add_months, is_month_end };
+
+/// How the time between two dates is counted as a fraction of a year
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DayCount {
+   /// 30/360 bond basis - the ISDA 30/360
+   Thirty360,
+
+   /// 30/360 US - bond basis with the end of February counted as the 30th
+   Thirty360Us,
+
+   /// 30E/360 - the Eurobond basis
+   ThirtyE360,
+
+   /// 30E/360 ISDA - the last day of any month counted as the 30th
+   ThirtyE360Isda,
+
+   /// Actual days over 360
+   Actual360,
+
+   /// Actual days over a fixed 365
+   Actual365Fixed,
+
+   /// Actual days, split between leap and other years
+   ActualActualIsda,
+
+   /// Actual days over the actual days of the coupon period
+   ActualActualIcma
+}
+impl DayCount {
+   /// The fraction of a year between two dates.
+   ///
+   /// Actual/Actual ICMA needs the coupon period - without one a period of a year from the start
+   /// is used.
+   pub fn year_fraction(&self, start: NaiveDate, end: NaiveDate) -> f64 {
+      match self {
+         Self::ActualActualIcma => self.accrual_fraction(start, end, start, add_months(start, 12), Frequency::Annual),
+         _ => self.fraction(start, end)
+      }
+   }
+
+   /// The fraction of a year between two dates within a coupon period, where the period is a whole
+   /// number of periods of the frequency from its end
+   pub fn accrual_fraction(&self, start: NaiveDate, end: NaiveDate, period_start: NaiveDate, period_end: NaiveDate, frequency: Frequency) -> f64 {
+      match self {
+         Self::ActualActualIcma => {
+            let days = (period_end - period_start).num_days() as f64;
+            if days <= 0.0 { return 0.0 }
+            (end - start).num_days() as f64 / (days * frequency.per_year() as f64)
+         },
+         _ => self.fraction(start, end)
+      }
+   }
+
+   fn fraction(&self, start: NaiveDate, end: NaiveDate) -> f64 {
+      let days = (end - start).num_days() as f64;
+      let is_last_of_february = |date: NaiveDate| date.month() == 2 && is_month_end(date);
+
+      let (d1, d2) = (start.day(), end.day());
+      let (d1, d2) = match self {
+         Self::Thirty360 => (d1.min(30), if d1 >= 30 && d2 == 31 { 30 } else { d2 }),
+         Self::Thirty360Us => {
+            let d2 = if is_last_of_february(start) && is_last_of_february(end) { 30 } else { d2 };
+            let d1 = if is_last_of_february(start) { 30 } else { d1.min(30) };
+            (d1, if d1 == 30 && d2 == 31 { 30 } else { d2 })
+         },
+         Self::ThirtyE360 => (d1.min(30), d2.min(30)),
+         Self::ThirtyE360Isda => (if is_month_end(start) { 30 } else { d1 }, if is_month_end(end) { 30 } else { d2 }),
+         Self::Actual360 => return days / 360.0,
+         Self::Actual365Fixed => return days / 365.0,
+         Self::ActualActualIsda | Self::ActualActualIcma => return actual_actual_isda(start, end)
+      };
+
+      let years = (end.year() - start.year()) as f64;
+      let months = end.month() as f64 - start.month() as f64;
+      (360.0 * years + 30.0 * months + d2 as f64 - d1 as f64) / 360.0
+   }
+}
+
+/// The days in leap years over 366 plus the other days over 365
+fn actual_actual_isda(start: NaiveDate, end: NaiveDate) -> f64 {
+   if end < start { return -actual_actual_isda(end, start) }
+
+   let days_in_year = |year: i32| if NaiveDate::from_ymd_opt(year, 2, 29).is_some() { 366.0 } else { 365.0 };
+   let new_year = |year: i32| NaiveDate::from_ymd_opt(year, 1, 1).unwrap_or(start);
+
+   if start.year() == end.year() { return (end - start).num_days() as f64 / days_in_year(start.year()) }
+   let first = (new_year(start.year() + 1) - start).num_days() as f64 / days_in_year(start.year());
+   let last = (end - new_year(end.year())).num_days() as f64 / days_in_year(end.year());
+   first + (end.year() - start.year() - 1) as f64 + last
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn date(year: i32, month: u32, day: u32) -> NaiveDate { NaiveDate::from_ymd_opt(year, month, day).unwrap() }
+
+   fn assert_close(expected: f64, actual: f64) {
+      assert!((expected - actual).abs() < 1e-10, "expected {} but was {}", expected, actual);
+   }
+
+   #[test]
+   fn verify_isda_examples() {
+      // ISDA - EMU and market conventions, the Actual/Actual and 30/360 examples
+      let periods = [
+         (date(2007, 12, 28), date(2008, 2, 28), 0.16666666666667, 0.16666666666667, 0.16942884946478),
+         (date(2007, 12, 28), date(2008, 2, 29), 0.16944444444444, 0.16944444444444, 0.17216108990194),
+         (date(2007, 10, 31), date(2008, 11, 30), 1.08333333333333, 1.08333333333333, 1.08243131970956),
+         (date(2008, 2, 1), date(2009, 5, 31), 1.33333333333333, 1.33055555555556, 1.32625945055768)
+      ];
+      for (start, end, bond, eurobond, isda) in periods.iter().copied() {
+         assert_close(bond, DayCount::Thirty360.year_fraction(start, end));
+         assert_close(eurobond, DayCount::ThirtyE360.year_fraction(start, end));
+         assert_close(isda, DayCount::ActualActualIsda.year_fraction(start, end));
+      }
+   }
+
+   #[test]
+   fn verify_thirty_360_variants() {
+      let (start, end) = (date(2008, 2, 29), date(2008, 8, 31));
+      assert_close(182.0 / 360.0, DayCount::Thirty360.year_fraction(start, end));
+      assert_close(180.0 / 360.0, DayCount::Thirty360Us.year_fraction(start, end));
+      assert_close(181.0 / 360.0, DayCount::ThirtyE360.year_fraction(start, end));
+      assert_close(180.0 / 360.0, DayCount::ThirtyE360Isda.year_fraction(start, end));
+   }
+
+   #[test]
+   fn verify_actual() {
+      let (start, end) = (date(2024, 1, 15), date(2024, 7, 15));
+      assert_close(182.0 / 360.0, DayCount::Actual360.year_fraction(start, end));
+      assert_close(182.0 / 365.0, DayCount::Actual365Fixed.year_fraction(start, end));
+
+      // half of a semi-annual coupon period is a quarter of a year, whatever its length in days
+      let fraction = DayCount::ActualActualIcma.accrual_fraction(start, date(2024, 4, 15), start, end, Frequency::SemiAnnual);
+      assert_close(91.0 / 364.0, fraction);
+      assert_close(0.5, DayCount::ActualActualIcma.accrual_fraction(start, end, start, end, Frequency::SemiAnnual));
+   }
+}
\ No newline at end of file
@@ -0,0 +1,257 @@
+//! Fixed income - day counts, coupon schedules, bond pricing and yield curves.
+//!
+//! Prices and yields follow the street convention: yields compound at the coupon frequency, and the
+//! time to the next coupon is the fraction of the coupon period left in actual days.  Dates are
+//! calendar dates rather than timestamps, so this needs the `bonds` feature for `chrono`.
+
+mod curve;
+pub use curve::{ CurveInstrument, Interpolation, YieldCurve };
+
+mod day_count;
+pub use day_count::DayCount;
+
+mod schedule;
+pub use schedule::{ BusinessCalendar, BusinessDayConvention, Weekends, coupon_dates };
+
+use chrono::NaiveDate;
+use crate::math::brent;
+use schedule::{ add_months, is_month_end, month_end };
+
+/// How often a bond pays coupons
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Frequency {
+   Annual,
+   SemiAnnual,
+   Quarterly,
+   Monthly
+}
+impl Frequency {
+   /// The number of coupons in a year
+   pub fn per_year(&self) -> u32 {
+      match self {
+         Self::Annual => 1,
+         Self::SemiAnnual => 2,
+         Self::Quarterly => 4,
+         Self::Monthly => 12
+      }
+   }
+
+   /// The number of months between coupons
+   pub fn months(&self) -> u32 { 12 / self.per_year() }
+}
+
+/// A fixed rate bond
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bond {
+   /// The date interest starts accruing from
+   pub issue: NaiveDate,
+
+   /// The date the face value is repaid
+   pub maturity: NaiveDate,
+
+   /// The annual coupon rate - e.g. 0.05 for 5%
+   pub coupon: f64,
+
+   /// How often coupons are paid
+   pub frequency: Frequency,
+
+   /// How interest accrues
+   pub day_count: DayCount,
+
+   /// The amount repaid at maturity
+   pub face: f64,
+
+   /// How payment dates that aren't business days are moved
+   pub convention: BusinessDayConvention
+}
+impl Bond {
+   /// Creates a bond with a face value of 100, accruing Actual/Actual ICMA with payments on the
+   /// following business day
+   pub fn new(issue: NaiveDate, maturity: NaiveDate, coupon: f64, frequency: Frequency) -> Self {
+      Self { issue, maturity, coupon, frequency, day_count: DayCount::ActualActualIcma, face: 100.0, convention: BusinessDayConvention::Following }
+   }
+
+   /// The unadjusted coupon dates, starting with the issue date
+   pub fn schedule(&self) -> Vec<NaiveDate> { coupon_dates(self.issue, self.maturity, self.frequency) }
+
+   /// The dates coupons are paid, adjusted to business days of a calendar
+   pub fn payment_dates(&self, calendar: &impl BusinessCalendar) -> Vec<NaiveDate> {
+      self.schedule().into_iter().skip(1).map(|date| self.convention.adjust(date, calendar)).collect()
+   }
+
+   /// The unadjusted dates and amounts of the payments after a settlement date
+   pub fn cash_flows(&self, settlement: NaiveDate) -> Vec<(NaiveDate, f64)> {
+      let schedule = self.schedule();
+      let mut flows: Vec<(NaiveDate, f64)> = schedule.windows(2)
+         .filter(|period| period[1] > settlement)
+         .map(|period| (period[1], self.face * self.coupon * self.accrual(period[0], period[1], period[1])))
+         .collect();
+
+      if let Some(last) = flows.last_mut() { last.1 += self.face }
+      flows
+   }
+
+   /// The interest accrued since the last coupon
+   pub fn accrued_interest(&self, settlement: NaiveDate) -> f64 {
+      match self.period(settlement) {
+         Some((start, end)) => self.face * self.coupon * self.accrual(start, settlement, end),
+         None => 0.0
+      }
+   }
+
+   /// The price including accrued interest at a yield
+   pub fn dirty_price(&self, yield_rate: f64, settlement: NaiveDate) -> f64 {
+      self.discounted(yield_rate, settlement).iter().map(|(_, value)| value).sum()
+   }
+
+   /// The quoted price without accrued interest at a yield
+   pub fn clean_price(&self, yield_rate: f64, settlement: NaiveDate) -> f64 {
+      self.dirty_price(yield_rate, settlement) - self.accrued_interest(settlement)
+   }
+
+   /// The yield that gives a clean price - `None` if no yield between -50% and 200% does
+   pub fn yield_to_maturity(&self, clean_price: f64, settlement: NaiveDate) -> Option<f64> {
+      if self.cash_flows(settlement).is_empty() { return None }
+      brent(|yield_rate| self.clean_price(yield_rate, settlement) - clean_price, -0.5, 2.0, 1e-12, 200)
+   }
+
+   /// The average time in years to the payments, weighted by their present values
+   pub fn macaulay_duration(&self, yield_rate: f64, settlement: NaiveDate) -> f64 {
+      let discounted = self.discounted(yield_rate, settlement);
+      let price: f64 = discounted.iter().map(|(_, value)| value).sum();
+      discounted.iter().map(|(time, value)| time * value).sum::<f64>() / price
+   }
+
+   /// The fractional change in the price for a change in the yield
+   pub fn modified_duration(&self, yield_rate: f64, settlement: NaiveDate) -> f64 {
+      self.macaulay_duration(yield_rate, settlement) / (1.0 + yield_rate / self.per_year())
+   }
+
+   /// The fractional change in the modified duration for a change in the yield
+   pub fn convexity(&self, yield_rate: f64, settlement: NaiveDate) -> f64 {
+      let discounted = self.discounted(yield_rate, settlement);
+      let price: f64 = discounted.iter().map(|(_, value)| value).sum();
+      let periodic = 1.0 + yield_rate / self.per_year();
+      discounted.iter().map(|(time, value)| time * (time + 1.0 / self.per_year()) * value).sum::<f64>() / (price * periodic * periodic)
+   }
+
+   /// The change in the dirty price for a one basis point fall in the yield
+   pub fn dv01(&self, yield_rate: f64, settlement: NaiveDate) -> f64 {
+      self.modified_duration(yield_rate, settlement) * self.dirty_price(yield_rate, settlement) / 10_000.0
+   }
+
+   /// The time in years and present value of each payment after settlement
+   fn discounted(&self, yield_rate: f64, settlement: NaiveDate) -> Vec<(f64, f64)> {
+      let (start, end) = match self.period(settlement) {
+         Some(period) => period,
+         None => return vec![]
+      };
+
+      // the fraction of a regular coupon period until the next coupon
+      let reference = self.reference_start(end);
+      let remaining = (end - settlement).num_days() as f64 / (end - reference.min(start)).num_days().max(1) as f64;
+      let periodic = 1.0 + yield_rate / self.per_year();
+
+      self.cash_flows(settlement).iter().enumerate().map(|(index, (_, amount))| {
+         let periods = remaining + index as f64;
+         (periods / self.per_year(), amount / periodic.powf(periods))
+      }).collect()
+   }
+
+   /// The coupon period holding a settlement date
+   fn period(&self, settlement: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+      self.schedule().windows(2).find(|period| period[0] <= settlement && settlement < period[1]).map(|period| (period[0], period[1]))
+   }
+
+   /// The fraction of a year accrued from a date to another within the period ending on a coupon date
+   fn accrual(&self, start: NaiveDate, end: NaiveDate, coupon_date: NaiveDate) -> f64 {
+      let reference = self.reference_start(coupon_date);
+      self.day_count.accrual_fraction(start, end, reference, coupon_date, self.frequency)
+   }
+
+   /// The start of the regular coupon period ending on a coupon date, kept on month ends when maturity is one
+   fn reference_start(&self, coupon_date: NaiveDate) -> NaiveDate {
+      let start = add_months(coupon_date, -(self.frequency.months() as i32));
+      if is_month_end(self.maturity) { month_end(start) } else { start }
+   }
+
+   fn per_year(&self) -> f64 { self.frequency.per_year() as f64 }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn date(year: i32, month: u32, day: u32) -> NaiveDate { NaiveDate::from_ymd_opt(year, month, day).unwrap() }
+
+   fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+      assert!((expected - actual).abs() < tolerance, "expected {} but was {}", expected, actual);
+   }
+
+   fn bond() -> Bond { Bond::new(date(2020, 5, 15), date(2030, 5, 15), 0.05, Frequency::SemiAnnual) }
+
+   #[test]
+   fn verify_cash_flows() {
+      let bond = bond();
+      let flows = bond.cash_flows(date(2029, 6, 1));
+      assert_eq!(vec![ (date(2029, 11, 15), 2.5), (date(2030, 5, 15), 102.5) ], flows);
+      assert_eq!(21, bond.schedule().len());
+
+      // the 15th of November 2025 is a Saturday
+      assert_eq!(date(2025, 11, 17), bond.payment_dates(&Weekends)[10]);
+
+      // 78 of the 184 days from the 15th of May to the 15th of November 2024
+      assert_close(2.5 * 78.0 / 184.0, bond.accrued_interest(date(2024, 8, 1)), 1e-12);
+      assert_eq!(0.0, bond.accrued_interest(date(2024, 5, 15)));
+
+      // periods on month ends are whole periods whatever their length in days
+      let month_end = Bond::new(date(2024, 2, 29), date(2026, 2, 28), 0.04, Frequency::SemiAnnual);
+      let flows = month_end.cash_flows(date(2024, 2, 29));
+      assert_eq!(vec![ date(2024, 8, 31), date(2025, 2, 28), date(2025, 8, 31), date(2026, 2, 28) ], flows.iter().map(|(date, _)| *date).collect::<Vec<_>>());
+      for (index, (_, amount)) in flows.iter().enumerate() { assert_close(if index == 3 { 102.0 } else { 2.0 }, *amount, 1e-12) }
+   }
+
+   #[test]
+   fn verify_prices() {
+      let bond = bond();
+
+      // on a coupon date a bond yielding its coupon is priced at par
+      assert_close(100.0, bond.clean_price(0.05, date(2024, 5, 15)), 1e-10);
+      let month_end = Bond::new(date(2024, 2, 29), date(2026, 2, 28), 0.04, Frequency::SemiAnnual);
+      assert_close(100.0, month_end.clean_price(0.04, date(2024, 8, 31)), 1e-10);
+      assert_close(100.0, month_end.clean_price(0.04, date(2025, 2, 28)), 1e-10);
+
+      // between coupons the clean price of a par bond stays close to par
+      let settlement = date(2024, 8, 1);
+      assert_close(100.0, bond.clean_price(0.05, settlement), 0.05);
+
+      // 11 coupons discounted at 3% a period, from 106 of 184 days before the next coupon
+      let remaining = 106.0 / 184.0;
+      let dirty: f64 = (0..12).map(|index| {
+         let amount = if index == 11 { 102.5 } else { 2.5 };
+         amount / 1.03_f64.powf(remaining + index as f64)
+      }).sum();
+      assert_close(dirty, bond.dirty_price(0.06, settlement), 1e-10);
+      assert_close(dirty - bond.accrued_interest(settlement), bond.clean_price(0.06, settlement), 1e-10);
+
+      let price = bond.clean_price(0.0425, settlement);
+      assert_close(0.0425, bond.yield_to_maturity(price, settlement).unwrap(), 1e-10);
+      assert_eq!(None, bond.yield_to_maturity(price, date(2031, 1, 1)));
+   }
+
+   #[test]
+   fn verify_risk() {
+      let (bond, settlement, yield_rate, bump) = (bond(), date(2024, 8, 1), 0.06, 1e-5);
+      let price = bond.dirty_price(yield_rate, settlement);
+      let (up, down) = (bond.dirty_price(yield_rate + bump, settlement), bond.dirty_price(yield_rate - bump, settlement));
+
+      assert_close((down - up) / (2.0 * bump * price), bond.modified_duration(yield_rate, settlement), 1e-6);
+      assert_close((up + down - 2.0 * price) / (bump * bump * price), bond.convexity(yield_rate, settlement), 1e-3);
+      assert_close((down - up) / (2.0 * bump) / 10_000.0, bond.dv01(yield_rate, settlement), 1e-6);
+
+      // a zero coupon bond's Macaulay duration is its time to maturity
+      let zero = Bond { coupon: 0.0, ..bond };
+      assert_close((106.0 / 184.0 + 11.0) / 2.0, zero.macaulay_duration(yield_rate, settlement), 1e-12);
+   }
+}
\ No newline at end of file
@@ -0,0 +1,133 @@
+use chrono::{ Datelike, Duration, Months, NaiveDate, Weekday };
+use super::Frequency;
+
+/// The number of days searched for a business day before giving up
+const SEARCH_DAYS: i64 = 366;
+
+/// Which days payments can be made on
+pub trait BusinessCalendar {
+   fn is_business_day(&self, date: NaiveDate) -> bool;
+}
+
+/// A calendar where every weekday is a business day
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Weekends;
+impl BusinessCalendar for Weekends {
+   fn is_business_day(&self, date: NaiveDate) -> bool { !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) }
+}
+
+#[cfg(feature = "calendar")]
+impl BusinessCalendar for crate::calendar::ExchangeCalendar {
+   fn is_business_day(&self, date: NaiveDate) -> bool { self.is_trading_day(date) }
+}
+
+/// How a date that isn't a business day is moved to one
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BusinessDayConvention {
+   /// The date isn't moved
+   Unadjusted,
+
+   /// The next business day
+   Following,
+
+   /// The next business day, unless that is in the next month when it is the previous business day
+   ModifiedFollowing,
+
+   /// The previous business day
+   Preceding,
+
+   /// The previous business day, unless that is in the previous month when it is the next business day
+   ModifiedPreceding
+}
+impl BusinessDayConvention {
+   /// Moves a date to a business day of a calendar - the date is kept when there is no business day within a year
+   pub fn adjust(&self, date: NaiveDate, calendar: &impl BusinessCalendar) -> NaiveDate {
+      let roll = |step: i64| {
+         (0..=SEARCH_DAYS).map(|days| date + Duration::days(days * step)).find(|rolled| calendar.is_business_day(*rolled)).unwrap_or(date)
+      };
+
+      match self {
+         Self::Unadjusted => date,
+         Self::Following => roll(1),
+         Self::Preceding => roll(-1),
+         Self::ModifiedFollowing => { let rolled = roll(1); if rolled.month() == date.month() { rolled } else { roll(-1) } },
+         Self::ModifiedPreceding => { let rolled = roll(-1); if rolled.month() == date.month() { rolled } else { roll(1) } }
+      }
+   }
+}
+
+/// The unadjusted coupon dates from a start date to maturity, starting with the start date.
+///
+/// Dates are counted back from maturity in whole periods, leaving any short stub at the start.  When
+/// maturity is the end of a month every coupon date is the end of its month.
+pub fn coupon_dates(start: NaiveDate, maturity: NaiveDate, frequency: Frequency) -> Vec<NaiveDate> {
+   let months = frequency.months();
+   let end_of_month = is_month_end(maturity);
+
+   let mut dates = vec![ maturity ];
+   for periods in 1.. {
+      let date = add_months(maturity, -(periods * months as i32));
+      let date = if end_of_month { month_end(date) } else { date };
+      if date <= start { break }
+      dates.push(date);
+   }
+
+   if start < maturity { dates.push(start) }
+   dates.reverse();
+   dates
+}
+
+/// Adds a number of months, moving to the end of the month when the day doesn't exist
+pub(crate) fn add_months(date: NaiveDate, months: i32) -> NaiveDate {
+   let moved = if months >= 0 { date.checked_add_months(Months::new(months as u32)) } else { date.checked_sub_months(Months::new(months.unsigned_abs())) };
+   moved.unwrap_or(date)
+}
+
+/// Whether a date is the last day of its month
+pub(crate) fn is_month_end(date: NaiveDate) -> bool { !matches!(date.succ_opt(), Some(next) if next.month() == date.month()) }
+
+/// The last day of the month of a date
+pub(crate) fn month_end(date: NaiveDate) -> NaiveDate {
+   let first = date.with_day(1).unwrap_or(date);
+   add_months(first, 1).pred_opt().unwrap_or(date)
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn date(year: i32, month: u32, day: u32) -> NaiveDate { NaiveDate::from_ymd_opt(year, month, day).unwrap() }
+
+   #[test]
+   fn verify_adjustments() {
+      // Saturday the 31st of August 2024
+      let saturday = date(2024, 8, 31);
+      assert_eq!(saturday, BusinessDayConvention::Unadjusted.adjust(saturday, &Weekends));
+      assert_eq!(date(2024, 9, 2), BusinessDayConvention::Following.adjust(saturday, &Weekends));
+      assert_eq!(date(2024, 8, 30), BusinessDayConvention::ModifiedFollowing.adjust(saturday, &Weekends));
+      assert_eq!(date(2024, 8, 30), BusinessDayConvention::Preceding.adjust(saturday, &Weekends));
+
+      // Sunday the 1st of September 2024
+      let sunday = date(2024, 9, 1);
+      assert_eq!(date(2024, 9, 2), BusinessDayConvention::ModifiedPreceding.adjust(sunday, &Weekends));
+
+      // a calendar without business days leaves the date alone
+      struct Closed;
+      impl BusinessCalendar for Closed {
+         fn is_business_day(&self, _: NaiveDate) -> bool { false }
+      }
+      assert_eq!(sunday, BusinessDayConvention::Following.adjust(sunday, &Closed));
+      assert_eq!(sunday, BusinessDayConvention::ModifiedPreceding.adjust(sunday, &Closed));
+   }
+
+   #[test]
+   fn verify_coupon_dates() {
+      let dates = coupon_dates(date(2024, 3, 1), date(2025, 11, 15), Frequency::SemiAnnual);
+      assert_eq!(vec![ date(2024, 3, 1), date(2024, 5, 15), date(2024, 11, 15), date(2025, 5, 15), date(2025, 11, 15) ], dates);
+
+      // maturity at the end of a month keeps every coupon at the end of its month
+      let dates = coupon_dates(date(2024, 2, 29), date(2025, 2, 28), Frequency::Quarterly);
+      assert_eq!(vec![ date(2024, 2, 29), date(2024, 5, 31), date(2024, 8, 31), date(2024, 11, 30), date(2025, 2, 28) ], dates);
+   }
+}
\ No newline at end of file
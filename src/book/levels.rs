@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use crate::orders::Side;
+use super::{ BookError, Level, Price, Sequencer };
+
+/// A change to the total size at a price
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LevelUpdate {
+   /// The sequence number of the update in the feed
+   pub sequence: u64,
+
+   /// `Buy` for the bids and `Sell` for the asks
+   pub side: Side,
+
+   pub price: f64,
+
+   /// The new total size at the price - 0 removes the level
+   pub size: f64
+}
+
+/// A price level (L2) order book - the total size at each price on each side
+#[derive(Clone, Debug, Default)]
+pub struct LevelBook {
+   bids: BTreeMap<Price, f64>,
+   asks: BTreeMap<Price, f64>,
+   sequencer: Sequencer
+}
+impl LevelBook {
+   /// Creates an empty book that needs a snapshot before it accepts updates
+   pub fn new() -> Self { Self::default() }
+
+   /// The sequence number of the latest update applied - `None` if the book needs a snapshot
+   pub fn sequence(&self) -> Option<u64> { self.sequencer.last }
+
+   /// Replaces the book with a snapshot of the levels as of a sequence number
+   pub fn apply_snapshot(&mut self, sequence: u64, bids: &[Level], asks: &[Level]) {
+      self.clear();
+      for level in bids { self.set(Side::Buy, level.price, level.size) }
+      for level in asks { self.set(Side::Sell, level.price, level.size) }
+      self.sequencer.reset(sequence);
+   }
+
+   /// Applies an update, ignoring any that are older than the book
+   pub fn apply(&mut self, update: &LevelUpdate) -> Result<(), BookError> {
+      if self.sequencer.accept(update.sequence)? { self.set(update.side, update.price, update.size) }
+      Ok(())
+   }
+
+   /// The highest bid
+   pub fn best_bid(&self) -> Option<Level> { self.bids.iter().next_back().map(|(price, size)| Level { price: price.0, size: *size }) }
+
+   /// The lowest ask
+   pub fn best_ask(&self) -> Option<Level> { self.asks.iter().next().map(|(price, size)| Level { price: price.0, size: *size }) }
+
+   /// The difference between the best ask and best bid
+   pub fn spread(&self) -> Option<f64> { self.best().map(|(bid, ask)| ask.price - bid.price) }
+
+   /// The price halfway between the best bid and best ask
+   pub fn mid(&self) -> Option<f64> { self.best().map(|(bid, ask)| (bid.price + ask.price) / 2.0) }
+
+   /// The mid price weighted towards the side with less size - where the price is more likely to move
+   pub fn microprice(&self) -> Option<f64> {
+      self.best().map(|(bid, ask)| (bid.price * ask.size + ask.price * bid.size) / (bid.size + ask.size))
+   }
+
+   /// The total size at a price on a side
+   pub fn depth_at(&self, side: Side, price: f64) -> f64 { self.levels(side).get(&Price(price)).copied().unwrap_or(0.0) }
+
+   /// The best levels on a side, from the best price
+   pub fn depth(&self, side: Side, count: usize) -> Vec<Level> {
+      let level = |(price, size): (&Price, &f64)| Level { price: price.0, size: *size };
+      match side {
+         Side::Buy => self.bids.iter().rev().take(count).map(level).collect(),
+         Side::Sell => self.asks.iter().take(count).map(level).collect()
+      }
+   }
+
+   /// The size imbalance over the best levels of each side, from -1 (all asks) to 1 (all bids)
+   pub fn imbalance(&self, count: usize) -> Option<f64> {
+      let bids: f64 = self.depth(Side::Buy, count).iter().map(|level| level.size).sum();
+      let asks: f64 = self.depth(Side::Sell, count).iter().map(|level| level.size).sum();
+      if bids + asks == 0.0 { None } else { Some((bids - asks) / (bids + asks)) }
+   }
+
+   /// Removes every level
+   pub fn clear(&mut self) {
+      self.bids.clear();
+      self.asks.clear();
+   }
+
+   /// Sets the total size at a price, removing the level when it is 0
+   pub(super) fn set(&mut self, side: Side, price: f64, size: f64) {
+      let levels = self.levels_mut(side);
+      if size > 0.0 { levels.insert(Price(price), size); } else { levels.remove(&Price(price)); }
+   }
+
+   /// Changes the total size at a price by an amount
+   pub(super) fn change(&mut self, side: Side, price: f64, amount: f64) {
+      let size = self.depth_at(side, price) + amount;
+      self.set(side, price, size)
+   }
+
+   pub(super) fn sequencer(&mut self) -> &mut Sequencer { &mut self.sequencer }
+
+   fn best(&self) -> Option<(Level, Level)> { self.best_bid().zip(self.best_ask()) }
+
+   fn levels(&self, side: Side) -> &BTreeMap<Price, f64> {
+      match side {
+         Side::Buy => &self.bids,
+         Side::Sell => &self.asks
+      }
+   }
+
+   fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<Price, f64> {
+      match side {
+         Side::Buy => &mut self.bids,
+         Side::Sell => &mut self.asks
+      }
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn book() -> LevelBook {
+      let mut book = LevelBook::new();
+      book.apply_snapshot(10,
+         &[ Level { price: 99.0, size: 300.0 }, Level { price: 100.0, size: 100.0 } ],
+         &[ Level { price: 101.0, size: 300.0 }, Level { price: 102.0, size: 500.0 } ]);
+      book
+   }
+
+   #[test]
+   fn verify_prices() {
+      let book = book();
+      assert_eq!(Some(Level { price: 100.0, size: 100.0 }), book.best_bid());
+      assert_eq!(Some(Level { price: 101.0, size: 300.0 }), book.best_ask());
+      assert_eq!(Some(1.0), book.spread());
+      assert_eq!(Some(100.5), book.mid());
+      assert_eq!(Some(100.25), book.microprice());
+      assert_eq!(Some(-0.5), book.imbalance(1));
+      assert_eq!(Some((400.0 - 800.0) / 1_200.0), book.imbalance(5));
+      assert_eq!(vec![ 100.0, 99.0 ], book.depth(Side::Buy, 5).iter().map(|level| level.price).collect::<Vec<f64>>());
+      assert_eq!(500.0, book.depth_at(Side::Sell, 102.0));
+   }
+
+   #[test]
+   fn verify_updates() {
+      let mut book = book();
+      book.apply(&LevelUpdate { sequence: 11, side: Side::Buy, price: 100.0, size: 0.0 }).unwrap();
+      book.apply(&LevelUpdate { sequence: 12, side: Side::Sell, price: 100.5, size: 50.0 }).unwrap();
+      book.apply(&LevelUpdate { sequence: 12, side: Side::Sell, price: 100.5, size: 75.0 }).unwrap();
+      assert_eq!(Some(99.0), book.best_bid().map(|level| level.price));
+      assert_eq!(Some(Level { price: 100.5, size: 50.0 }), book.best_ask());
+
+      let update = LevelUpdate { sequence: 14, side: Side::Buy, price: 99.5, size: 10.0 };
+      assert_eq!(Err(BookError::SequenceGap { expected: 13, received: 14 }), book.apply(&update));
+      assert_eq!(Err(BookError::NotSynced), book.apply(&LevelUpdate { sequence: 15, ..update }));
+      assert_eq!(None, book.sequence());
+   }
+}
\ No newline at end of file
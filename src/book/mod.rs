@@ -0,0 +1,88 @@
+//! Limit order books.
+//!
+//! A [`LevelBook`] keeps the total size at each price (L2), and an [`OrderBook`] keeps every order
+//! in price / time priority (L3) as well as the totals.  Both are kept in step with a feed by applying
+//! a snapshot and then the deltas that follow it, where a gap in the sequence numbers means the book
+//! has missed an update and needs a new snapshot.
+
+mod levels;
+pub use levels::{ LevelBook, LevelUpdate };
+
+mod orders;
+pub use orders::{ BookMessage, BookOrder, OrderBook };
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// The size available at a single price
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Level {
+   pub price: f64,
+   pub size: f64
+}
+
+/// Why an update couldn't be applied to a book
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BookError {
+   /// There hasn't been a snapshot since the book was created or last missed an update
+   NotSynced,
+
+   /// An update was missed - the book needs a new snapshot
+   SequenceGap { expected: u64, received: u64 },
+
+   /// An order was added with an id that is already in the book
+   DuplicateOrder(u64),
+
+   /// An order that isn't in the book was changed
+   UnknownOrder(u64)
+}
+impl fmt::Display for BookError {
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+         Self::NotSynced => write!(f, "the book needs a snapshot"),
+         Self::SequenceGap { expected, received } => write!(f, "expected sequence {} but received {}", expected, received),
+         Self::DuplicateOrder(id) => write!(f, "order {} is already in the book", id),
+         Self::UnknownOrder(id) => write!(f, "order {} isn't in the book", id)
+      }
+   }
+}
+impl std::error::Error for BookError {}
+
+/// A price that can be used as a key in ordered maps
+#[derive(Clone, Copy, Debug)]
+struct Price(f64);
+impl Eq for Price {}
+impl Ord for Price {
+   fn cmp(&self, other: &Self) -> Ordering { self.0.total_cmp(&other.0) }
+}
+impl PartialOrd for Price {
+   fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+impl PartialEq for Price {
+   fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+
+/// Tracks the sequence numbers of a feed
+#[derive(Clone, Copy, Debug, Default)]
+struct Sequencer {
+   last: Option<u64>
+}
+impl Sequencer {
+   fn reset(&mut self, sequence: u64) { self.last = Some(sequence) }
+
+   /// Marks the feed as needing a snapshot
+   fn clear(&mut self) { self.last = None }
+
+   /// Whether an update with a sequence number should be applied - `false` for updates already seen
+   fn accept(&mut self, sequence: u64) -> Result<bool, BookError> {
+      let last = self.last.ok_or(BookError::NotSynced)?;
+      if sequence <= last { return Ok(false) }
+      if sequence != last + 1 {
+         self.last = None;
+         return Err(BookError::SequenceGap { expected: last + 1, received: sequence })
+      }
+
+      self.last = Some(sequence);
+      Ok(true)
+   }
+}
\ No newline at end of file
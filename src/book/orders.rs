@@ -0,0 +1,207 @@
+use std::collections::{ HashMap, VecDeque };
+use crate::orders::Side;
+use super::{ BookError, LevelBook };
+
+/// A resting order in an order book
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BookOrder {
+   pub id: u64,
+
+   /// `Buy` for a bid and `Sell` for an ask
+   pub side: Side,
+
+   pub price: f64,
+
+   /// The size still resting
+   pub size: f64
+}
+
+/// A change to the orders in an order book
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BookMessage {
+   /// A new order at the back of its price level
+   Add(BookOrder),
+
+   /// A change of price or size - the order keeps its priority only if its price is the same and its size is reduced
+   Modify { id: u64, price: f64, size: f64 },
+
+   /// Removes an order
+   Cancel { id: u64 },
+
+   /// A trade against an order, removing it once nothing remains
+   Execute { id: u64, size: f64 }
+}
+
+/// A per order (L3) order book, keeping each price level in time priority
+#[derive(Clone, Debug, Default)]
+pub struct OrderBook {
+   orders: HashMap<u64, BookOrder>,
+
+   /// The ids of the orders at each price, in priority order - keyed by side and the bits of the price
+   queues: HashMap<(bool, u64), VecDeque<u64>>,
+
+   levels: LevelBook
+}
+impl OrderBook {
+   /// Creates an empty book that needs a snapshot before it accepts messages
+   pub fn new() -> Self { Self::default() }
+
+   /// The total size at each price
+   pub fn levels(&self) -> &LevelBook { &self.levels }
+
+   /// The sequence number of the latest message applied - `None` if the book needs a snapshot
+   pub fn sequence(&self) -> Option<u64> { self.levels.sequence() }
+
+   /// The resting order with an id
+   pub fn order(&self, id: u64) -> Option<&BookOrder> { self.orders.get(&id) }
+
+   /// The number of resting orders
+   pub fn len(&self) -> usize { self.orders.len() }
+
+   /// Whether there are no resting orders
+   pub fn is_empty(&self) -> bool { self.orders.is_empty() }
+
+   /// The orders at a price in priority order
+   pub fn queue(&self, side: Side, price: f64) -> Vec<&BookOrder> {
+      self.queues.get(&key(side, price)).map_or(vec![], |queue| queue.iter().filter_map(|id| self.orders.get(id)).collect())
+   }
+
+   /// Replaces the book with a snapshot of the resting orders as of a sequence number, given in priority order
+   pub fn apply_snapshot(&mut self, sequence: u64, orders: &[BookOrder]) {
+      self.orders.clear();
+      self.queues.clear();
+      self.levels.clear();
+      for order in orders {
+         // a snapshot is trusted, so a repeated id replaces the earlier order
+         let _ = self.remove(order.id);
+         self.insert(*order);
+      }
+      self.levels.sequencer().reset(sequence);
+   }
+
+   /// Applies a message, ignoring any that are older than the book - a message that can't be applied leaves the book
+   /// needing a snapshot, as it no longer matches the feed
+   pub fn apply(&mut self, sequence: u64, message: &BookMessage) -> Result<(), BookError> {
+      if !self.levels.sequencer().accept(sequence)? { return Ok(()) }
+
+      let result = self.apply_message(message);
+      if result.is_err() { self.levels.sequencer().clear() }
+      result
+   }
+
+   fn apply_message(&mut self, message: &BookMessage) -> Result<(), BookError> {
+      match *message {
+         BookMessage::Add(order) => {
+            if self.orders.contains_key(&order.id) { return Err(BookError::DuplicateOrder(order.id)) }
+            self.insert(order);
+         },
+         BookMessage::Modify { id, price, size } => {
+            let order = *self.orders.get(&id).ok_or(BookError::UnknownOrder(id))?;
+            if order.price == price && size <= order.size {
+               self.reduce(id, order.size - size)?;
+            } else {
+               self.remove(id)?;
+               self.insert(BookOrder { price, size, ..order });
+            }
+         },
+         BookMessage::Cancel { id } => { self.remove(id)?; },
+         BookMessage::Execute { id, size } => self.reduce(id, size)?
+      }
+
+      Ok(())
+   }
+
+   fn insert(&mut self, order: BookOrder) {
+      if order.size <= 0.0 { return }
+
+      self.queues.entry(key(order.side, order.price)).or_default().push_back(order.id);
+      self.levels.change(order.side, order.price, order.size);
+      self.orders.insert(order.id, order);
+   }
+
+   fn remove(&mut self, id: u64) -> Result<BookOrder, BookError> {
+      let order = self.orders.remove(&id).ok_or(BookError::UnknownOrder(id))?;
+      let key = key(order.side, order.price);
+      if let Some(queue) = self.queues.get_mut(&key) {
+         queue.retain(|queued| *queued != id);
+         if queue.is_empty() { self.queues.remove(&key); }
+      }
+      self.levels.change(order.side, order.price, -order.size);
+      Ok(order)
+   }
+
+   fn reduce(&mut self, id: u64, amount: f64) -> Result<(), BookError> {
+      let order = self.orders.get_mut(&id).ok_or(BookError::UnknownOrder(id))?;
+      let amount = amount.min(order.size);
+      order.size -= amount;
+
+      let order = *order;
+      self.levels.change(order.side, order.price, -amount);
+      if order.size <= 0.0 { self.remove(id)?; }
+      Ok(())
+   }
+}
+
+fn key(side: Side, price: f64) -> (bool, u64) { (side == Side::Buy, price.to_bits()) }
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn order(id: u64, side: Side, price: f64, size: f64) -> BookOrder { BookOrder { id, side, price, size } }
+
+   fn book() -> OrderBook {
+      let mut book = OrderBook::new();
+      book.apply_snapshot(1, &[
+         order(1, Side::Buy, 100.0, 10.0),
+         order(2, Side::Buy, 100.0, 20.0),
+         order(3, Side::Sell, 101.0, 5.0)
+      ]);
+      book
+   }
+
+   fn ids(book: &OrderBook, side: Side, price: f64) -> Vec<u64> { book.queue(side, price).iter().map(|order| order.id).collect() }
+
+   #[test]
+   fn verify_messages() {
+      let mut book = book();
+      book.apply(2, &BookMessage::Add(order(4, Side::Buy, 100.0, 5.0))).unwrap();
+      assert_eq!(vec![ 1, 2, 4 ], ids(&book, Side::Buy, 100.0));
+      assert_eq!(35.0, book.levels().depth_at(Side::Buy, 100.0));
+
+      // reducing keeps priority, increasing loses it
+      book.apply(3, &BookMessage::Modify { id: 2, price: 100.0, size: 15.0 }).unwrap();
+      book.apply(4, &BookMessage::Modify { id: 1, price: 100.0, size: 12.0 }).unwrap();
+      assert_eq!(vec![ 2, 4, 1 ], ids(&book, Side::Buy, 100.0));
+
+      book.apply(5, &BookMessage::Execute { id: 2, size: 15.0 }).unwrap();
+      book.apply(6, &BookMessage::Execute { id: 4, size: 2.0 }).unwrap();
+      book.apply(7, &BookMessage::Cancel { id: 3 }).unwrap();
+      assert_eq!(vec![ 4, 1 ], ids(&book, Side::Buy, 100.0));
+      assert_eq!(15.0, book.levels().depth_at(Side::Buy, 100.0));
+      assert_eq!(None, book.levels().best_ask());
+      assert_eq!(2, book.len());
+
+      book.apply(8, &BookMessage::Modify { id: 1, price: 100.5, size: 12.0 }).unwrap();
+      assert_eq!(Some(100.5), book.levels().best_bid().map(|level| level.price));
+   }
+
+   #[test]
+   fn verify_errors() {
+      let mut duplicate = book();
+      assert_eq!(Err(BookError::DuplicateOrder(1)), duplicate.apply(2, &BookMessage::Add(order(1, Side::Buy, 99.0, 1.0))));
+      assert_eq!(None, duplicate.sequence());
+      assert_eq!(Err(BookError::NotSynced), duplicate.apply(3, &BookMessage::Cancel { id: 1 }));
+
+      let mut unknown = book();
+      assert_eq!(Err(BookError::UnknownOrder(9)), unknown.apply(2, &BookMessage::Cancel { id: 9 }));
+      assert_eq!(None, unknown.sequence());
+      assert_eq!(3, unknown.len());
+
+      let mut stale = book();
+      assert_eq!(Ok(()), stale.apply(1, &BookMessage::Cancel { id: 9 }));
+      assert_eq!(Err(BookError::SequenceGap { expected: 2, received: 5 }), stale.apply(5, &BookMessage::Cancel { id: 1 }));
+      assert_eq!(Err(BookError::NotSynced), OrderBook::new().apply(1, &BookMessage::Cancel { id: 1 }));
+   }
+}
\ No newline at end of file
@@ -0,0 +1,182 @@
+use chrono::{ Datelike, Duration, NaiveDate, Weekday };
+use chrono_tz::{ America, Europe, Tz };
+use super::rules::*;
+use super::TradingHours;
+
+/// An exchange with a known trading calendar
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Exchange {
+   /// New York Stock Exchange
+   NYSE,
+
+   /// NASDAQ - shares the NYSE holiday schedule
+   NASDAQ,
+
+   /// Toronto Stock Exchange
+   TSX,
+
+   /// London Stock Exchange
+   LSE,
+
+   /// Deutsche Börse Xetra
+   XETRA,
+
+   /// Chicago Mercantile Exchange - equity index futures on Globex
+   CME
+}
+impl Exchange {
+   /// The time zone that the exchange's hours are quoted in
+   pub fn timezone(&self) -> Tz {
+      match self {
+         Self::NYSE | Self::NASDAQ => America::New_York,
+         Self::TSX => America::Toronto,
+         Self::LSE => Europe::London,
+         Self::XETRA => Europe::Berlin,
+         Self::CME => America::Chicago
+      }
+   }
+
+   /// The hours of a normal, full trading day
+   pub fn regular_hours(&self) -> TradingHours {
+      match self {
+         Self::NYSE | Self::NASDAQ => TradingHours::new(4 * 60, 9 * 60 + 30, 16 * 60, 20 * 60),
+         Self::TSX => TradingHours::new(7 * 60, 9 * 60 + 30, 16 * 60, 17 * 60),
+         Self::LSE => TradingHours::new(8 * 60, 8 * 60, 16 * 60 + 30, 16 * 60 + 30),
+         Self::XETRA => TradingHours::new(9 * 60, 9 * 60, 17 * 60 + 30, 17 * 60 + 30),
+         Self::CME => TradingHours::new(-7 * 60, 8 * 60 + 30, 15 * 60, 16 * 60)
+      }
+   }
+
+   /// The hours of an early close trading day
+   fn early_close_hours(&self) -> TradingHours {
+      let hours = self.regular_hours();
+      match self {
+         Self::NYSE | Self::NASDAQ => TradingHours { close: 13 * 60, post_close: 17 * 60, ..hours },
+         Self::TSX => TradingHours { close: 13 * 60, post_close: 13 * 60, ..hours },
+         Self::LSE => TradingHours { close: 12 * 60 + 30, post_close: 12 * 60 + 30, ..hours },
+         Self::XETRA => TradingHours { close: 14 * 60, post_close: 14 * 60, ..hours },
+         Self::CME => TradingHours { close: 12 * 60, post_close: 12 * 60, ..hours }
+      }
+   }
+
+   /// Calculates the full day market holidays for a year.
+   ///
+   /// Holidays that land on a weekend are only included when they are observed on a weekday.
+   /// One-off closures (national days of mourning, royal events, etc.) are not included.
+   pub fn holidays(&self, year: i32) -> Vec<NaiveDate> {
+      let mut holidays = match self {
+         Self::NYSE | Self::NASDAQ | Self::CME => us_holidays(year),
+         Self::TSX => canadian_holidays(year),
+         Self::LSE => uk_holidays(year),
+         Self::XETRA => german_holidays(year)
+      };
+
+      holidays.retain(|date| !is_weekend(*date));
+      holidays.sort();
+      holidays.dedup();
+      holidays
+   }
+
+   /// Calculates the early close days for a year along with their trading hours
+   pub fn early_closes(&self, year: i32) -> Vec<(NaiveDate, TradingHours)> {
+      let mut days = match self {
+         Self::NYSE | Self::NASDAQ | Self::CME => us_early_closes(year),
+         Self::TSX => vec![ date(year, 12, 24) ],
+         Self::LSE => vec![ date(year, 12, 24), date(year, 12, 31) ],
+         Self::XETRA => vec![]
+      };
+
+      days.sort();
+
+      let holidays = self.holidays(year);
+      days.into_iter()
+         .filter(|day| !is_weekend(*day) && !holidays.contains(day))
+         .map(|day| (day, self.early_close_hours()))
+         .collect()
+   }
+}
+
+fn us_holidays(year: i32) -> Vec<NaiveDate> {
+   let mut holidays = vec![
+      nth_weekday(year, 2, Weekday::Mon, 3),           // Washington's Birthday
+      easter_sunday(year) - Duration::days(2),         // Good Friday
+      last_weekday(year, 5, Weekday::Mon),             // Memorial Day
+      observed_nearest(date(year, 7, 4)),              // Independence Day
+      nth_weekday(year, 9, Weekday::Mon, 1),           // Labor Day
+      nth_weekday(year, 11, Weekday::Thu, 4),          // Thanksgiving Day
+      observed_nearest(date(year, 12, 25))             // Christmas Day
+   ];
+
+   // New Year's Day on a Saturday is not observed on the prior Friday
+   let new_years = date(year, 1, 1);
+   if new_years.weekday() != Weekday::Sat { holidays.push(observed_nearest(new_years)) }
+
+   if year >= 1998 { holidays.push(nth_weekday(year, 1, Weekday::Mon, 3)) }      // Martin Luther King Jr. Day
+   if year >= 2022 { holidays.push(observed_nearest(date(year, 6, 19))) }        // Juneteenth
+
+   holidays
+}
+
+fn us_early_closes(year: i32) -> Vec<NaiveDate> {
+   let mut days = vec![ nth_weekday(year, 11, Weekday::Thu, 4) + Duration::days(1) ];
+
+   // The day before Independence Day, when it's a weekday before a weekday holiday
+   let july_3 = date(year, 7, 3);
+   if july_3.weekday().num_days_from_monday() <= Weekday::Thu.num_days_from_monday() { days.push(july_3) }
+
+   // Christmas Eve, unless it's the observed Christmas holiday
+   let christmas_eve = date(year, 12, 24);
+   if christmas_eve.weekday() != Weekday::Fri { days.push(christmas_eve) }
+
+   days
+}
+
+fn canadian_holidays(year: i32) -> Vec<NaiveDate> {
+   let (christmas, boxing_day) = christmas_and_boxing_day(year);
+   let mut holidays = vec![
+      observed_next(date(year, 1, 1)),                         // New Year's Day
+      easter_sunday(year) - Duration::days(2),                 // Good Friday
+      weekday_on_or_before(date(year, 5, 24), Weekday::Mon),   // Victoria Day
+      observed_next(date(year, 7, 1)),                         // Canada Day
+      nth_weekday(year, 8, Weekday::Mon, 1),                   // Civic Holiday
+      nth_weekday(year, 9, Weekday::Mon, 1),                   // Labour Day
+      nth_weekday(year, 10, Weekday::Mon, 2),                  // Thanksgiving Day
+      christmas,
+      boxing_day
+   ];
+
+   if year >= 2008 { holidays.push(nth_weekday(year, 2, Weekday::Mon, 3)) }      // Family Day
+
+   holidays
+}
+
+fn uk_holidays(year: i32) -> Vec<NaiveDate> {
+   let easter = easter_sunday(year);
+   let (christmas, boxing_day) = christmas_and_boxing_day(year);
+
+   vec![
+      observed_next(date(year, 1, 1)),          // New Year's Day
+      easter - Duration::days(2),               // Good Friday
+      easter + Duration::days(1),               // Easter Monday
+      nth_weekday(year, 5, Weekday::Mon, 1),    // Early May bank holiday
+      last_weekday(year, 5, Weekday::Mon),      // Spring bank holiday
+      last_weekday(year, 8, Weekday::Mon),      // Summer bank holiday
+      christmas,
+      boxing_day
+   ]
+}
+
+fn german_holidays(year: i32) -> Vec<NaiveDate> {
+   let easter = easter_sunday(year);
+
+   vec![
+      date(year, 1, 1),                         // New Year's Day
+      easter - Duration::days(2),               // Good Friday
+      easter + Duration::days(1),               // Easter Monday
+      date(year, 5, 1),                         // Labour Day
+      date(year, 12, 24),                       // Christmas Eve
+      date(year, 12, 25),                       // Christmas Day
+      date(year, 12, 26),                       // Boxing Day
+      date(year, 12, 31)                        // New Year's Eve
+   ]
+}
\ No newline at end of file
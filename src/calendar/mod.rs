@@ -0,0 +1,280 @@
+//! Exchange trading calendars.
+//!
+//! Holidays and early closes are calculated by rule, so any year can be queried.  All timestamps are
+//! in milliseconds since the EPOCH, matching the rest of the crate.
+
+mod exchanges;
+pub use exchanges::Exchange;
+
+mod rules;
+
+mod sessions;
+pub use sessions::SessionClassifier;
+
+use chrono::{ Datelike, Duration, NaiveDate, NaiveTime, TimeZone };
+use chrono_tz::Tz;
+use crate::TradingSession;
+
+/// The maximum number of days searched when looking for the next open or close
+const SEARCH_DAYS: i64 = 366;
+
+/// The trading windows of a single trading day.
+///
+/// Each value is the number of minutes since midnight (local exchange time) of the trading day.  Values
+/// can be negative for sessions that start on the prior evening - e.g. CME Globex.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TradingHours {
+   /// The start of the pre-market session.  Equal to `open` when there is no pre-market
+   pub pre_open: i32,
+
+   /// The start of the regular session
+   pub open: i32,
+
+   /// The end of the regular session
+   pub close: i32,
+
+   /// The end of the after hours session.  Equal to `close` when there is no after hours
+   pub post_close: i32
+}
+impl TradingHours {
+   /// Creates new trading hours from the minutes since midnight of each boundary
+   pub fn new(pre_open: i32, open: i32, close: i32, post_close: i32) -> Self {
+      Self { pre_open, open, close, post_close }
+   }
+}
+
+/// The trading windows of a single trading day as timestamps
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SessionTimes {
+   pre_open: i64,
+   open: i64,
+   close: i64,
+   post_close: i64
+}
+impl SessionTimes {
+   fn session_at(&self, timestamp: i64) -> TradingSession {
+      if timestamp >= self.pre_open && timestamp < self.open { TradingSession::PreMarket }
+      else if timestamp >= self.open && timestamp < self.close { TradingSession::Regular }
+      else if timestamp >= self.close && timestamp < self.post_close { TradingSession::AfterHours }
+      else { TradingSession::Other }
+   }
+}
+
+/// The trading calendar for a single exchange
+#[derive(Clone, Copy, Debug)]
+pub struct ExchangeCalendar {
+   exchange: Exchange,
+   timezone: Tz
+}
+impl ExchangeCalendar {
+   /// Creates the trading calendar for an exchange
+   pub fn new(exchange: Exchange) -> Self {
+      Self { exchange, timezone: exchange.timezone() }
+   }
+
+   /// The exchange of the calendar
+   pub fn exchange(&self) -> Exchange { self.exchange }
+
+   /// The time zone of the exchange
+   pub fn timezone(&self) -> Tz { self.timezone }
+
+   /// Whether the date (local to the exchange) is a holiday
+   pub fn is_holiday(&self, date: NaiveDate) -> bool {
+      self.exchange.holidays(date.year()).contains(&date)
+   }
+
+   /// Whether the exchange trades on the date (local to the exchange)
+   pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+      !rules::is_weekend(date) && !self.is_holiday(date)
+   }
+
+   /// Gets the trading hours for a date (local to the exchange), taking early closes into account.
+   ///
+   /// Returns `None` when the exchange is closed for the day.
+   pub fn hours(&self, date: NaiveDate) -> Option<TradingHours> {
+      if !self.is_trading_day(date) { return None }
+
+      let early_close = self.exchange.early_closes(date.year()).into_iter().find(|(day, _)| *day == date);
+      Some(early_close.map_or_else(|| self.exchange.regular_hours(), |(_, hours)| hours))
+   }
+
+   /// Gets the trading session at a timestamp - `TradingSession::Other` when the exchange is closed
+   pub fn session_at(&self, timestamp: i64) -> TradingSession {
+      let date = self.local_date(timestamp);
+
+      // Sessions can start on the prior evening, so the next day's hours need to be checked as well
+      [ date, date + Duration::days(1) ].iter()
+         .filter_map(|day| self.session_times(*day))
+         .map(|times| times.session_at(timestamp))
+         .find(|session| *session != TradingSession::Other)
+         .unwrap_or(TradingSession::Other)
+   }
+
+   /// Whether the regular session is open at a timestamp
+   pub fn is_open(&self, timestamp: i64) -> bool {
+      self.session_at(timestamp) == TradingSession::Regular
+   }
+
+   /// Gets the timestamp of the next regular session open strictly after a timestamp
+   pub fn next_open(&self, timestamp: i64) -> Option<i64> {
+      self.next_boundary(timestamp, |hours| hours.open)
+   }
+
+   /// Gets the timestamp of the next regular session close strictly after a timestamp
+   pub fn next_close(&self, timestamp: i64) -> Option<i64> {
+      self.next_boundary(timestamp, |hours| hours.close)
+   }
+
+   /// Gets the trading days from `start` to `end` (local to the exchange), including both ends
+   pub fn trading_days(&self, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+      let mut days = vec![];
+      let mut day = start;
+      while day <= end {
+         if self.is_trading_day(day) { days.push(day) }
+         day += Duration::days(1);
+      }
+
+      days
+   }
+
+   /// Counts the trading days from `start` to `end` (local to the exchange), including both ends
+   pub fn trading_days_between(&self, start: NaiveDate, end: NaiveDate) -> usize {
+      self.trading_days(start, end).len()
+   }
+
+   /// Gets the date local to the exchange for a timestamp
+   pub fn local_date(&self, timestamp: i64) -> NaiveDate {
+      self.timezone.timestamp_millis_opt(timestamp).unwrap().date_naive()
+   }
+
+   /// Gets the timestamp for a number of minutes since midnight (local to the exchange) of a date.
+   ///
+   /// Local times repeated when daylight savings ends resolve to the earliest instant.  Local times
+   /// skipped when daylight savings starts are moved forward an hour, so 2:30am on a day the clocks go
+   /// from 2am to 3am is 3:30am.
+   pub fn timestamp_at(&self, date: NaiveDate, minutes: i32) -> i64 {
+      let local = date.and_time(NaiveTime::MIN) + Duration::minutes(i64::from(minutes));
+      let resolved = self.timezone.from_local_datetime(&local).earliest()
+         .or_else(|| self.timezone.from_local_datetime(&(local + Duration::hours(1))).earliest());
+
+      // a time skipped by more than an hour is treated as UTC rather than failing
+      resolved.map_or_else(|| local.and_utc().timestamp_millis(), |resolved| resolved.timestamp_millis())
+   }
+
+   /// Gets the session boundaries of a date (local to the exchange) as timestamps
+   pub(crate) fn session_times(&self, date: NaiveDate) -> Option<SessionTimes> {
+      self.hours(date).map(|hours| SessionTimes {
+         pre_open: self.timestamp_at(date, hours.pre_open),
+         open: self.timestamp_at(date, hours.open),
+         close: self.timestamp_at(date, hours.close),
+         post_close: self.timestamp_at(date, hours.post_close)
+      })
+   }
+
+   fn next_boundary(&self, timestamp: i64, boundary: impl Fn(&TradingHours) -> i32) -> Option<i64> {
+      let date = self.local_date(timestamp);
+      (0..=SEARCH_DAYS)
+         .map(|offset| date + Duration::days(offset))
+         .filter_map(|day| self.hours(day).map(|hours| self.timestamp_at(day, boundary(&hours))))
+         .find(|at| *at > timestamp)
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use super::rules::date;
+
+   fn local_time(calendar: &ExchangeCalendar, year: i32, month: u32, day: u32, hour: i32, minute: i32) -> i64 {
+      calendar.timestamp_at(date(year, month, day), hour * 60 + minute)
+   }
+
+   #[test]
+   fn verify_nyse_holidays() {
+      let holidays = Exchange::NYSE.holidays(2024);
+      assert_eq!(vec![
+         date(2024, 1, 1), date(2024, 1, 15), date(2024, 2, 19), date(2024, 3, 29), date(2024, 5, 27),
+         date(2024, 6, 19), date(2024, 7, 4), date(2024, 9, 2), date(2024, 11, 28), date(2024, 12, 25)
+      ], holidays);
+
+      // New Year's Day on a Saturday isn't observed, but Independence Day on a Saturday is
+      assert!(!Exchange::NYSE.holidays(2021).contains(&date(2021, 12, 31)));
+      assert!(Exchange::NYSE.holidays(2026).contains(&date(2026, 7, 3)));
+   }
+
+   #[test]
+   fn verify_nyse_early_closes() {
+      let days: Vec<NaiveDate> = Exchange::NYSE.early_closes(2024).into_iter().map(|(day, _)| day).collect();
+      assert_eq!(vec![ date(2024, 7, 3), date(2024, 11, 29), date(2024, 12, 24) ], days);
+
+      let calendar = ExchangeCalendar::new(Exchange::NYSE);
+      assert_eq!(13 * 60, calendar.hours(date(2024, 11, 29)).unwrap().close);
+      assert!(!calendar.is_open(local_time(&calendar, 2024, 11, 29, 13, 30)));
+   }
+
+   #[test]
+   fn verify_other_exchange_holidays() {
+      assert!(Exchange::TSX.holidays(2024).contains(&date(2024, 5, 20)));     // Victoria Day
+      assert!(Exchange::TSX.holidays(2024).contains(&date(2024, 8, 5)));      // Civic Holiday
+      assert!(Exchange::LSE.holidays(2024).contains(&date(2024, 4, 1)));      // Easter Monday
+      assert!(Exchange::LSE.holidays(2024).contains(&date(2024, 8, 26)));     // Summer bank holiday
+      assert!(Exchange::XETRA.holidays(2024).contains(&date(2024, 12, 24)));
+      assert!(!Exchange::XETRA.holidays(2024).contains(&date(2024, 5, 27)));
+   }
+
+   #[test]
+   fn verify_session_at() {
+      let calendar = ExchangeCalendar::new(Exchange::NYSE);
+
+      assert_eq!(TradingSession::Other, calendar.session_at(local_time(&calendar, 2024, 3, 12, 3, 59)));
+      assert_eq!(TradingSession::PreMarket, calendar.session_at(local_time(&calendar, 2024, 3, 12, 4, 0)));
+      assert_eq!(TradingSession::Regular, calendar.session_at(local_time(&calendar, 2024, 3, 12, 9, 30)));
+      assert_eq!(TradingSession::AfterHours, calendar.session_at(local_time(&calendar, 2024, 3, 12, 16, 0)));
+      assert_eq!(TradingSession::Other, calendar.session_at(local_time(&calendar, 2024, 3, 12, 20, 0)));
+      assert_eq!(TradingSession::Other, calendar.session_at(local_time(&calendar, 2024, 3, 29, 10, 0)));
+   }
+
+   #[test]
+   fn verify_session_at_overnight() {
+      let calendar = ExchangeCalendar::new(Exchange::CME);
+
+      // Sunday evening is the pre-market for Monday, Friday evening is closed
+      let sunday = calendar.timestamp_at(date(2024, 3, 10), 18 * 60);
+      let friday = calendar.timestamp_at(date(2024, 3, 15), 18 * 60);
+      assert_eq!(TradingSession::PreMarket, calendar.session_at(sunday));
+      assert_eq!(TradingSession::Other, calendar.session_at(friday));
+   }
+
+   #[test]
+   fn verify_next_open() {
+      let calendar = ExchangeCalendar::new(Exchange::NYSE);
+
+      // Thursday before Good Friday - next open is the following Monday
+      let thursday = local_time(&calendar, 2024, 3, 28, 12, 0);
+      assert_eq!(Some(local_time(&calendar, 2024, 4, 1, 9, 30)), calendar.next_open(thursday));
+      assert_eq!(Some(local_time(&calendar, 2024, 3, 28, 16, 0)), calendar.next_close(thursday));
+   }
+
+   #[test]
+   fn verify_timestamp_at_daylight_savings() {
+      let calendar = ExchangeCalendar::new(Exchange::NYSE);
+
+      // 2:30am on the 10th of March 2024 doesn't exist in New York, so it's 3:30am EDT - 7:30am UTC
+      let skipped = calendar.timestamp_at(date(2024, 3, 10), 2 * 60 + 30);
+      assert_eq!(date(2024, 3, 10).and_hms_opt(7, 30, 0).unwrap().and_utc().timestamp_millis(), skipped);
+      assert_eq!(calendar.timestamp_at(date(2024, 3, 10), 3 * 60 + 30), skipped);
+
+      // 1:30am on the 3rd of November 2024 happens twice - the first is 1:30am EDT, 5:30am UTC
+      let repeated = calendar.timestamp_at(date(2024, 11, 3), 60 + 30);
+      assert_eq!(date(2024, 11, 3).and_hms_opt(5, 30, 0).unwrap().and_utc().timestamp_millis(), repeated);
+   }
+
+   #[test]
+   fn verify_trading_days_between() {
+      let calendar = ExchangeCalendar::new(Exchange::NYSE);
+
+      assert_eq!(252, calendar.trading_days_between(date(2024, 1, 1), date(2024, 12, 31)));
+      assert_eq!(0, calendar.trading_days_between(date(2024, 12, 28), date(2024, 12, 29)));
+   }
+}
\ No newline at end of file
@@ -0,0 +1,108 @@
+use chrono::{ Datelike, Duration, NaiveDate, Weekday };
+
+/// Builds a date that is known to be valid
+pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+   NaiveDate::from_ymd_opt(year, month, day).expect("invalid calendar date")
+}
+
+/// Whether the date falls on a Saturday or Sunday
+pub fn is_weekend(date: NaiveDate) -> bool {
+   matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Calculates Easter Sunday for the given year (anonymous Gregorian algorithm)
+pub fn easter_sunday(year: i32) -> NaiveDate {
+   let a = year % 19;
+   let b = year / 100;
+   let c = year % 100;
+   let d = b / 4;
+   let e = b % 4;
+   let f = (b + 8) / 25;
+   let g = (b - f + 1) / 3;
+   let h = (19 * a + b - d - g + 15) % 30;
+   let i = c / 4;
+   let k = c % 4;
+   let l = (32 + 2 * e + 2 * i - h - k) % 7;
+   let m = (a + 11 * h + 22 * l) / 451;
+   let month = (h + l - 7 * m + 114) / 31;
+   let day = (h + l - 7 * m + 114) % 31 + 1;
+
+   date(year, month as u32, day as u32)
+}
+
+/// Gets the `n`th (starting at 1) occurrence of a weekday in a month
+pub fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u32) -> NaiveDate {
+   NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8).expect("invalid weekday of month")
+}
+
+/// Gets the last occurrence of a weekday in a month
+pub fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+   let next_month = if month == 12 { date(year + 1, 1, 1) } else { date(year, month + 1, 1) };
+   weekday_on_or_before(next_month - Duration::days(1), weekday)
+}
+
+/// Gets the given weekday on or before a date
+pub fn weekday_on_or_before(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+   let offset = (7 + date.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+   date - Duration::days(i64::from(offset))
+}
+
+/// US style observance - Saturday holidays move to Friday and Sunday holidays move to Monday
+pub fn observed_nearest(date: NaiveDate) -> NaiveDate {
+   match date.weekday() {
+      Weekday::Sat => date - Duration::days(1),
+      Weekday::Sun => date + Duration::days(1),
+      _ => date
+   }
+}
+
+/// Substitute day observance - weekend holidays move to the following Monday
+pub fn observed_next(date: NaiveDate) -> NaiveDate {
+   next_weekday_on_or_after(date)
+}
+
+/// Gets the first weekday on or after a date
+pub fn next_weekday_on_or_after(mut date: NaiveDate) -> NaiveDate {
+   while is_weekend(date) { date += Duration::days(1) }
+   date
+}
+
+/// Christmas and Boxing Day with substitute days, for the UK and Canada.
+///
+/// When either falls on a weekend the holiday moves to the next weekday that isn't already a holiday.
+pub fn christmas_and_boxing_day(year: i32) -> (NaiveDate, NaiveDate) {
+   let christmas = observed_next(date(year, 12, 25));
+   let mut boxing_day = observed_next(date(year, 12, 26));
+   if boxing_day <= christmas { boxing_day = next_weekday_on_or_after(christmas + Duration::days(1)) }
+
+   (christmas, boxing_day)
+}
+
+
+#[cfg(test)]
+mod tests {
+   use chrono::Weekday;
+   use super::*;
+
+   #[test]
+   fn verify_easter_sunday() {
+      assert_eq!(date(2019, 4, 21), easter_sunday(2019));
+      assert_eq!(date(2024, 3, 31), easter_sunday(2024));
+      assert_eq!(date(2025, 4, 20), easter_sunday(2025));
+      assert_eq!(date(2038, 4, 25), easter_sunday(2038));
+   }
+
+   #[test]
+   fn verify_nth_and_last_weekday() {
+      assert_eq!(date(2024, 11, 28), nth_weekday(2024, 11, Weekday::Thu, 4));
+      assert_eq!(date(2024, 5, 27), last_weekday(2024, 5, Weekday::Mon));
+      assert_eq!(date(2024, 12, 30), last_weekday(2024, 12, Weekday::Mon));
+   }
+
+   #[test]
+   fn verify_christmas_and_boxing_day() {
+      assert_eq!((date(2021, 12, 27), date(2021, 12, 28)), christmas_and_boxing_day(2021));
+      assert_eq!((date(2022, 12, 26), date(2022, 12, 27)), christmas_and_boxing_day(2022));
+      assert_eq!((date(2020, 12, 25), date(2020, 12, 28)), christmas_and_boxing_day(2020));
+   }
+}
\ No newline at end of file
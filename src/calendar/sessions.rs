@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use chrono::{ Duration, NaiveDate };
+use crate::{ Quote, TradingSession };
+use super::{ Exchange, ExchangeCalendar, SessionTimes };
+
+/// Assigns the trading session to quotes from feeds that don't provide one.
+///
+/// Session times are cached by date, so re-labelling long histories of quotes only works out each
+/// day's hours once.
+#[derive(Clone, Debug)]
+pub struct SessionClassifier {
+   calendar: ExchangeCalendar,
+
+   /// The session times for each date (local to the exchange) already seen
+   cache: HashMap<NaiveDate, Option<SessionTimes>>
+}
+impl SessionClassifier {
+   /// Creates a new classifier for an exchange
+   pub fn new(exchange: Exchange) -> Self {
+      Self { calendar: ExchangeCalendar::new(exchange), cache: HashMap::new() }
+   }
+
+   /// Gets the trading session at a timestamp - `TradingSession::Other` when the exchange is closed
+   pub fn classify(&mut self, timestamp: i64) -> TradingSession {
+      let date = self.calendar.local_date(timestamp);
+
+      // Sessions can start on the prior evening, so the next day's hours need to be checked as well
+      for day in [ date, date + Duration::days(1) ].iter() {
+         let session = self.session_times(*day).map_or(TradingSession::Other, |times| times.session_at(timestamp));
+         if session != TradingSession::Other { return session }
+      }
+
+      TradingSession::Other
+   }
+
+   /// Gets the trading session of a quote from its timestamp
+   pub fn classify_quote(&mut self, quote: &Quote) -> TradingSession { self.classify(quote.timestamp) }
+
+   /// Re-labels the session of every quote from its timestamp
+   pub fn label(&mut self, quotes: &mut [Quote]) {
+      for quote in quotes.iter_mut() { quote.session = self.classify(quote.timestamp) }
+   }
+
+   /// Labels the session of only the quotes marked as `TradingSession::Other`, keeping any
+   /// session the vendor did provide
+   pub fn label_missing(&mut self, quotes: &mut [Quote]) {
+      for quote in quotes.iter_mut().filter(|quote| quote.session == TradingSession::Other) {
+         quote.session = self.classify(quote.timestamp)
+      }
+   }
+
+   fn session_times(&mut self, date: NaiveDate) -> Option<SessionTimes> {
+      let calendar = &self.calendar;
+      *self.cache.entry(date).or_insert_with(|| calendar.session_times(date))
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use super::super::rules::date;
+
+   fn quote(timestamp: i64, session: TradingSession) -> Quote {
+      Quote { symbol: "AAPL".to_string(), timestamp, session, price: 100.0, volume: 10 }
+   }
+
+   #[test]
+   fn verify_classify() {
+      let calendar = ExchangeCalendar::new(Exchange::NASDAQ);
+      let mut classifier = SessionClassifier::new(Exchange::NASDAQ);
+
+      for minutes in (0..24 * 60).step_by(15) {
+         let timestamp = calendar.timestamp_at(date(2024, 6, 18), minutes);
+         assert_eq!(calendar.session_at(timestamp), classifier.classify(timestamp));
+      }
+   }
+
+   #[test]
+   fn verify_label() {
+      let calendar = ExchangeCalendar::new(Exchange::NYSE);
+      let mut classifier = SessionClassifier::new(Exchange::NYSE);
+      let mut quotes = vec![
+         quote(calendar.timestamp_at(date(2024, 6, 18), 8 * 60), TradingSession::Other),
+         quote(calendar.timestamp_at(date(2024, 6, 18), 10 * 60), TradingSession::Other),
+         quote(calendar.timestamp_at(date(2024, 6, 18), 17 * 60), TradingSession::Regular),
+         quote(calendar.timestamp_at(date(2024, 6, 19), 10 * 60), TradingSession::Regular)
+      ];
+
+      classifier.label_missing(&mut quotes);
+      let sessions: Vec<TradingSession> = quotes.iter().map(|quote| quote.session).collect();
+      assert_eq!(vec![ TradingSession::PreMarket, TradingSession::Regular, TradingSession::Regular, TradingSession::Regular ], sessions);
+
+      classifier.label(&mut quotes);
+      let sessions: Vec<TradingSession> = quotes.iter().map(|quote| quote.session).collect();
+      assert_eq!(vec![ TradingSession::PreMarket, TradingSession::Regular, TradingSession::AfterHours, TradingSession::Other ], sessions);
+   }
+}
\ No newline at end of file
@@ -0,0 +1,270 @@
+//! Time value of money and cash flow analytics.
+//!
+//! Rates are fractions - 5% is `0.05`.  Dated cash flows are discounted in years of 365 days from
+//! the first flow, the same as spreadsheet XNPV and XIRR, and positive amounts are money received.
+
+use crate::Timestamped;
+use crate::math::brent;
+use crate::metrics::EquityPoint;
+
+/// The number of milliseconds in a year of 365 days
+const MILLIS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0 * 1_000.0;
+
+/// The rates above 100% searched for a change in sign of the net present value, after every whole
+/// percentage from -99% to 100%
+const HIGH_RATES: [f64; 6] = [ 1.5, 2.0, 3.0, 5.0, 10.0, 100.0 ];
+
+/// An amount paid or received at a point in time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CashFlow {
+   /// The timestamp of the flow in millisecond accuracy
+   pub timestamp: i64,
+
+   /// The amount - positive when received and negative when paid
+   pub amount: f64
+}
+impl Timestamped for CashFlow {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// How interest is added to a balance
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compounding {
+   /// Interest is only earned on the principal
+   Simple,
+
+   /// Interest is added a number of times a year - e.g. 12 for monthly
+   Periodic(u32),
+
+   /// Interest is added continuously
+   Continuous
+}
+impl Compounding {
+   /// The growth of a unit at an annual rate over a number of years
+   pub fn growth(&self, rate: f64, years: f64) -> f64 {
+      match *self {
+         Self::Simple => 1.0 + rate * years,
+         Self::Periodic(per_year) => (1.0 + rate / per_year as f64).powf(per_year as f64 * years),
+         Self::Continuous => (rate * years).exp()
+      }
+   }
+}
+
+/// When the payments of an annuity are made in each period
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Timing {
+   /// At the end of each period - an ordinary annuity
+   End,
+
+   /// At the start of each period - an annuity due
+   Start
+}
+
+/// A payment of a loan, split between interest and repaying the principal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Installment {
+   /// The number of the payment, starting from one
+   pub period: u32,
+
+   /// The amount paid
+   pub payment: f64,
+
+   /// The interest paid
+   pub interest: f64,
+
+   /// The principal repaid
+   pub principal: f64,
+
+   /// The principal left after the payment
+   pub balance: f64
+}
+
+/// The value today of an amount received after a number of years
+pub fn present_value(amount: f64, rate: f64, years: f64, compounding: Compounding) -> f64 {
+   amount / compounding.growth(rate, years)
+}
+
+/// The value after a number of years of an amount invested today
+pub fn future_value(amount: f64, rate: f64, years: f64, compounding: Compounding) -> f64 {
+   amount * compounding.growth(rate, years)
+}
+
+/// The annual rate that gives the same growth in a year when compounded once a year
+pub fn effective_rate(rate: f64, compounding: Compounding) -> f64 { compounding.growth(rate, 1.0) - 1.0 }
+
+/// The net present value of flows a period apart, with the first flow today
+pub fn npv(rate: f64, flows: &[f64]) -> f64 {
+   flows.iter().enumerate().map(|(period, amount)| amount / (1.0 + rate).powi(period as i32)).sum()
+}
+
+/// The rate of return of flows a period apart - the rate giving a net present value of zero.
+///
+/// When there are several, the one closest to zero is found.  `None` if there isn't one from -99% to 10,000%.
+pub fn irr(flows: &[f64]) -> Option<f64> { solve_rate(|rate| npv(rate, flows)) }
+
+/// The net present value of dated flows at the time of the first
+pub fn xnpv(rate: f64, flows: &[CashFlow]) -> f64 {
+   let first = match flows.iter().map(|flow| flow.timestamp).min() {
+      Some(first) => first,
+      None => return 0.0
+   };
+   flows.iter().map(|flow| flow.amount / (1.0 + rate).powf((flow.timestamp - first) as f64 / MILLIS_PER_YEAR)).sum()
+}
+
+/// The annual rate of return of dated flows - the rate giving a net present value of zero.
+///
+/// When there are several, the one closest to zero is found.  `None` if there isn't one from -99% to 10,000%.
+pub fn xirr(flows: &[CashFlow]) -> Option<f64> { solve_rate(|rate| xnpv(rate, flows)) }
+
+/// The value today of a number of equal payments
+pub fn annuity_present_value(payment: f64, rate: f64, periods: u32, timing: Timing) -> f64 {
+   if rate == 0.0 { return payment * periods as f64 }
+   let value = payment * (1.0 - (1.0 + rate).powi(-(periods as i32))) / rate;
+   if timing == Timing::Start { value * (1.0 + rate) } else { value }
+}
+
+/// The value after the last period of a number of equal payments
+pub fn annuity_future_value(payment: f64, rate: f64, periods: u32, timing: Timing) -> f64 {
+   annuity_present_value(payment, rate, periods, timing) * (1.0 + rate).powi(periods as i32)
+}
+
+/// The payment at the end of each period that repays a loan over a number of periods
+pub fn payment(principal: f64, rate: f64, periods: u32) -> f64 {
+   if periods == 0 { return principal }
+   principal / annuity_present_value(1.0, rate, periods, Timing::End)
+}
+
+/// The payments that repay a loan over a number of periods, at a rate for each period
+pub fn amortization(principal: f64, rate: f64, periods: u32) -> Vec<Installment> {
+   let payment = payment(principal, rate, periods);
+   let mut balance = principal;
+   (1..=periods).map(|period| {
+      let interest = balance * rate;
+      balance -= payment - interest;
+      Installment { period, payment, interest, principal: payment - interest, balance }
+   }).collect()
+}
+
+/// The compounded return of a portfolio, removing the effect of deposits and withdrawals.
+///
+/// The values are sorted by timestamp and include the flows at the same timestamp.  Flows are
+/// deposits when positive and withdrawals when negative, and are treated as made at the end of the
+/// period between values they fall in.
+pub fn time_weighted_return(values: &[EquityPoint], flows: &[CashFlow]) -> f64 {
+   values.windows(2).fold(1.0, |total, period| {
+      let flow: f64 = flows.iter()
+         .filter(|flow| period[0].timestamp < flow.timestamp && flow.timestamp <= period[1].timestamp)
+         .map(|flow| flow.amount)
+         .sum();
+      total * (period[1].value - flow) / period[0].value
+   }) - 1.0
+}
+
+/// The annual rate of return of a portfolio, weighting returns by the money invested at the time.
+///
+/// This is the rate of return of investing the first value, making the deposits and withdrawals and
+/// receiving the last value - the values and flows are as for [`time_weighted_return`].
+pub fn money_weighted_return(values: &[EquityPoint], flows: &[CashFlow]) -> Option<f64> {
+   let (first, last) = (values.first()?, values.last()?);
+   let mut investor = vec![ CashFlow { timestamp: first.timestamp, amount: -first.value } ];
+   investor.extend(flows.iter()
+      .filter(|flow| first.timestamp < flow.timestamp && flow.timestamp <= last.timestamp)
+      .map(|flow| CashFlow { timestamp: flow.timestamp, amount: -flow.amount }));
+   investor.push(CashFlow { timestamp: last.timestamp, amount: last.value });
+   xirr(&investor)
+}
+
+/// Finds the rate closest to zero where the net present value changes sign
+fn solve_rate(npv: impl Fn(f64) -> f64) -> Option<f64> {
+   let rates: Vec<f64> = (-99..=100).map(|percent| percent as f64 / 100.0).chain(HIGH_RATES.iter().copied()).collect();
+   let values: Vec<f64> = rates.iter().map(|rate| npv(*rate)).collect();
+   let mut brackets: Vec<usize> = (1..rates.len()).filter(|index| values[index - 1] * values[*index] <= 0.0).collect();
+   brackets.sort_by(|a, b| (rates[a - 1] + rates[*a]).abs().total_cmp(&(rates[b - 1] + rates[*b]).abs()));
+   brackets.into_iter().find_map(|index| brent(&npv, rates[index - 1], rates[index], 1e-12, 200))
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1_000;
+
+   fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+      assert!((expected - actual).abs() < tolerance, "expected {} but was {}", expected, actual);
+   }
+
+   fn flow(day: i64, amount: f64) -> CashFlow { CashFlow { timestamp: day * MILLIS_PER_DAY, amount } }
+
+   fn point(day: i64, value: f64) -> EquityPoint { EquityPoint { timestamp: day * MILLIS_PER_DAY, value } }
+
+   #[test]
+   fn verify_compounding() {
+      assert_close(110.0, future_value(100.0, 0.1, 1.0, Compounding::Simple), 1e-10);
+      assert_close(100.0 * 1.05_f64.powi(4), future_value(100.0, 0.1, 2.0, Compounding::Periodic(2)), 1e-10);
+      assert_close(100.0, present_value(100.0 * 0.1_f64.exp(), 0.1, 1.0, Compounding::Continuous), 1e-10);
+
+      assert_close(0.1268250301319698, effective_rate(0.12, Compounding::Periodic(12)), 1e-12);
+      assert_close(0.12_f64.exp() - 1.0, effective_rate(0.12, Compounding::Continuous), 1e-12);
+   }
+
+   #[test]
+   fn verify_npv_and_irr() {
+      // spreadsheet examples - NPV discounts the first flow a period, so it's given a zero flow today
+      assert_close(1188.44, npv(0.1, &[ 0.0, -10_000.0, 3_000.0, 4_200.0, 6_800.0 ]), 0.005);
+      assert_close(0.086630948, irr(&[ -70_000.0, 12_000.0, 15_000.0, 18_000.0, 21_000.0, 26_000.0 ]).unwrap(), 1e-9);
+      assert_close(-0.021244848, irr(&[ -70_000.0, 12_000.0, 15_000.0, 18_000.0, 21_000.0 ]).unwrap(), 1e-9);
+
+      // no change in sign, and two rates of return of 10% and 20%
+      assert_eq!(None, irr(&[ 100.0, 50.0 ]));
+      assert_close(0.1, irr(&[ -100.0, 230.0, -132.0 ]).unwrap(), 1e-10);
+   }
+
+   #[test]
+   fn verify_xnpv_and_xirr() {
+      // spreadsheet examples from the 1st of January 2008 to the 1st of April 2009
+      let flows = [ flow(13_879, -10_000.0), flow(13_939, 2_750.0), flow(14_182, 4_250.0), flow(14_290, 3_250.0), flow(14_335, 2_750.0) ];
+      assert_close(2_086.647602, xnpv(0.09, &flows), 1e-6);
+      assert_close(0.373362535, xirr(&flows).unwrap(), 1e-8);
+
+      // the order of the flows doesn't matter
+      let mut reversed = flows;
+      reversed.reverse();
+      assert_close(0.373362535, xirr(&reversed).unwrap(), 1e-8);
+      assert_eq!(0.0, xnpv(0.1, &[]));
+   }
+
+   #[test]
+   fn verify_annuities() {
+      assert_close(1_000.0 * (1.0 - 1.05_f64.powi(-10)) / 0.05, annuity_present_value(1_000.0, 0.05, 10, Timing::End), 1e-9);
+      assert_close(12_577.892535548839, annuity_future_value(1_000.0, 0.05, 10, Timing::End), 1e-9);
+      assert_close(12_577.892535548839 * 1.05, annuity_future_value(1_000.0, 0.05, 10, Timing::Start), 1e-9);
+      assert_eq!(500.0, annuity_present_value(100.0, 0.0, 5, Timing::Start));
+
+      // a 30 year mortgage of 200,000 at 6% a year paid monthly
+      let schedule = amortization(200_000.0, 0.005, 360);
+      assert_eq!(360, schedule.len());
+      assert_close(1_199.10, schedule[0].payment, 0.005);
+      assert_close(1_000.0, schedule[0].interest, 1e-9);
+      assert_close(199.10, schedule[0].principal, 0.005);
+      assert_close(0.0, schedule[359].balance, 1e-6);
+      assert_close(200_000.0, schedule.iter().map(|installment| installment.principal).sum(), 1e-6);
+      assert_eq!(vec![ 50.0; 4 ], amortization(200.0, 0.0, 4).iter().map(|installment| installment.payment).collect::<Vec<f64>>());
+   }
+
+   #[test]
+   fn verify_portfolio_returns() {
+      // up 10% in the first year, a deposit doubling the money invested, then down 10% in the second
+      let values = [ point(0, 1_000.0), point(365, 2_100.0), point(730, 1_890.0) ];
+      let flows = [ flow(365, 1_000.0) ];
+
+      assert_close(1.1 * 0.9 - 1.0, time_weighted_return(&values, &flows), 1e-12);
+      assert_close(0.1, time_weighted_return(&values[..2], &flows), 1e-12);
+
+      // 1,000 (1 + r)^2 + 1,000 (1 + r) = 1,890
+      let growth = ((1.0 + 4.0 * 1.89_f64).sqrt() - 1.0) / 2.0;
+      assert_close(growth - 1.0, money_weighted_return(&values, &flows).unwrap(), 1e-10);
+      assert_eq!(None, money_weighted_return(&[], &flows));
+   }
+}
\ No newline at end of file
@@ -0,0 +1,308 @@
+//! Currencies, amounts of money and foreign exchange rates.
+//!
+//! Amounts carry their currency, and combining amounts of different currencies is an error rather
+//! than a silent mistake - they have to be converted with a rate first.  Rates are kept by time so
+//! that historical prices convert at the rate of their day.
+
+use std::collections::{ BTreeMap, BTreeSet };
+use std::fmt;
+use std::ops::{ Div, Mul, Neg };
+use std::str::FromStr;
+use crate::{ Bar, Quote };
+
+/// The active ISO 4217 currency codes
+const CODES: [&str; 155] = [
+   "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT", "BGN", "BHD", "BIF",
+   "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD", "CDF", "CHF", "CLP", "CNY", "COP", "CRC",
+   "CUP", "CVE", "CZK", "DJF", "DKK", "DOP", "DZD", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS",
+   "GIP", "GMD", "GNF", "GTQ", "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD",
+   "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP", "LKR", "LRD", "LSL",
+   "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR", "MVR", "MWK", "MXN", "MYR", "MZN", "NAD",
+   "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD",
+   "RUB", "RWF", "SAR", "SBD", "SCR", "SDG", "SEK", "SGD", "SHP", "SLE", "SOS", "SRD", "SSP", "STN", "SVC", "SYP",
+   "SZL", "THB", "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU", "UZS", "VES",
+   "VND", "VUV", "WST", "XAF", "XCD", "XOF", "XPF", "YER", "ZAR", "ZMW", "ZWL"
+];
+
+/// The currencies without minor units
+const WHOLE: [&str; 16] = [ "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "VND", "VUV", "XAF", "XOF", "XPF" ];
+
+/// The currencies with thousandths as minor units
+const THOUSANDTHS: [&str; 7] = [ "BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND" ];
+
+/// Problems with currencies and their conversion
+#[derive(Clone, Debug, PartialEq)]
+pub enum CurrencyError {
+   /// The code isn't an active ISO 4217 currency
+   UnknownCurrency(String),
+
+   /// The symbol isn't a currency pair
+   InvalidPair(String),
+
+   /// Amounts of different currencies were combined
+   Mismatch { expected: Currency, found: Currency },
+
+   /// There is no rate between the currencies, directly or through another currency
+   MissingRate { from: Currency, to: Currency }
+}
+impl fmt::Display for CurrencyError {
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+         Self::UnknownCurrency(code) => write!(f, "'{}' isn't an ISO 4217 currency", code),
+         Self::InvalidPair(symbol) => write!(f, "'{}' isn't a currency pair", symbol),
+         Self::Mismatch { expected, found } => write!(f, "expected an amount in {} but found {}", expected, found),
+         Self::MissingRate { from, to } => write!(f, "there is no rate from {} to {}", from, to)
+      }
+   }
+}
+impl std::error::Error for CurrencyError {}
+
+/// An ISO 4217 currency
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Currency([u8; 3]);
+impl Currency {
+   pub const AUD: Self = Self(*b"AUD");
+   pub const CAD: Self = Self(*b"CAD");
+   pub const CHF: Self = Self(*b"CHF");
+   pub const CNY: Self = Self(*b"CNY");
+   pub const EUR: Self = Self(*b"EUR");
+   pub const GBP: Self = Self(*b"GBP");
+   pub const HKD: Self = Self(*b"HKD");
+   pub const JPY: Self = Self(*b"JPY");
+   pub const USD: Self = Self(*b"USD");
+
+   /// The three letter code
+   pub fn code(&self) -> &str { std::str::from_utf8(&self.0).unwrap_or("???") }
+
+   /// The number of decimal places of the minor unit - e.g. 2 for cents
+   pub fn minor_units(&self) -> u32 {
+      if WHOLE.contains(&self.code()) { 0 } else if THOUSANDTHS.contains(&self.code()) { 3 } else { 2 }
+   }
+}
+impl FromStr for Currency {
+   type Err = CurrencyError;
+
+   fn from_str(code: &str) -> Result<Self, Self::Err> {
+      let code = code.trim();
+      match CODES.binary_search(&code) {
+         Ok(_) => Ok(Self([ code.as_bytes()[0], code.as_bytes()[1], code.as_bytes()[2] ])),
+         Err(_) => Err(CurrencyError::UnknownCurrency(code.to_string()))
+      }
+   }
+}
+impl fmt::Display for Currency {
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str(self.code()) }
+}
+
+/// An amount of a currency
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Money {
+   pub amount: f64,
+   pub currency: Currency
+}
+impl Money {
+   pub fn new(amount: f64, currency: Currency) -> Self { Self { amount, currency } }
+
+   /// Adds an amount of the same currency
+   pub fn checked_add(&self, other: Money) -> Result<Money, CurrencyError> {
+      self.check(&other)?;
+      Ok(Self::new(self.amount + other.amount, self.currency))
+   }
+
+   /// Subtracts an amount of the same currency
+   pub fn checked_sub(&self, other: Money) -> Result<Money, CurrencyError> {
+      self.check(&other)?;
+      Ok(Self::new(self.amount - other.amount, self.currency))
+   }
+
+   /// Sums amounts that are all of one currency
+   pub fn sum(currency: Currency, amounts: &[Money]) -> Result<Money, CurrencyError> {
+      amounts.iter().try_fold(Self::new(0.0, currency), |total, amount| total.checked_add(*amount))
+   }
+
+   /// Rounds to the minor unit of the currency
+   pub fn round(&self) -> Self {
+      let scale = 10f64.powi(self.currency.minor_units() as i32);
+      Self::new((self.amount * scale).round() / scale, self.currency)
+   }
+
+   fn check(&self, other: &Money) -> Result<(), CurrencyError> {
+      if self.currency == other.currency { Ok(()) } else { Err(CurrencyError::Mismatch { expected: self.currency, found: other.currency }) }
+   }
+}
+impl Mul<f64> for Money {
+   type Output = Self;
+
+   fn mul(self, factor: f64) -> Self { Self::new(self.amount * factor, self.currency) }
+}
+impl Div<f64> for Money {
+   type Output = Self;
+
+   fn div(self, divisor: f64) -> Self { Self::new(self.amount / divisor, self.currency) }
+}
+impl Neg for Money {
+   type Output = Self;
+
+   fn neg(self) -> Self { Self::new(-self.amount, self.currency) }
+}
+impl fmt::Display for Money {
+   /// Formats to the minor unit of the currency - e.g. `12.50 USD`
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      write!(f, "{:.*} {}", self.currency.minor_units() as usize, self.amount, self.currency)
+   }
+}
+
+/// Exchange rates between currencies over time.
+///
+/// A rate for a pair is the amount of the quote currency one unit of the base currency buys - the
+/// rate of EUR/USD is the number of dollars in a euro.  Lookups use the latest rate at or before a
+/// timestamp, the inverse of the opposite pair, or a cross rate through a third currency.
+#[derive(Clone, Debug, Default)]
+pub struct FxRates {
+   rates: BTreeMap<(Currency, Currency), BTreeMap<i64, f64>>
+}
+impl FxRates {
+   pub fn new() -> Self { Self::default() }
+
+   /// Sets the rate of a pair from a timestamp
+   pub fn insert(&mut self, base: Currency, quote: Currency, timestamp: i64, rate: f64) {
+      self.rates.entry((base, quote)).or_default().insert(timestamp, rate);
+   }
+
+   /// Sets a rate from the quote of a currency pair - `EURUSD=X`, `EURUSD` or `EUR/USD`, with a
+   /// single currency such as `JPY=X` taken as priced in dollars
+   pub fn update(&mut self, quote: &Quote) -> Result<(), CurrencyError> {
+      let (base, counter) = parse_pair(&quote.symbol)?;
+      self.insert(base, counter, quote.timestamp, quote.price);
+      Ok(())
+   }
+
+   /// The rate to convert from one currency to another at a timestamp
+   pub fn rate(&self, from: Currency, to: Currency, timestamp: i64) -> Option<f64> {
+      if from == to { return Some(1.0) }
+      if let Some(rate) = self.direct(from, to, timestamp) { return Some(rate) }
+
+      // triangulate through dollars or euros first, then any other currency
+      let preferred = [ Currency::USD, Currency::EUR ];
+      preferred.iter().copied()
+         .chain(self.currencies().into_iter().filter(|currency| !preferred.contains(currency)))
+         .filter(|currency| *currency != from && *currency != to)
+         .find_map(|currency| Some(self.direct(from, currency, timestamp)? * self.direct(currency, to, timestamp)?))
+   }
+
+   /// The latest rate to convert from one currency to another
+   pub fn latest(&self, from: Currency, to: Currency) -> Option<f64> { self.rate(from, to, i64::MAX) }
+
+   /// Converts an amount to another currency at the rate of a timestamp
+   pub fn convert(&self, money: Money, to: Currency, timestamp: i64) -> Result<Money, CurrencyError> {
+      let rate = self.rate(money.currency, to, timestamp).ok_or(CurrencyError::MissingRate { from: money.currency, to })?;
+      Ok(Money::new(money.amount * rate, to))
+   }
+
+   /// Converts the prices of bars to another currency at the rate of each bar's timestamp
+   pub fn convert_bars(&self, bars: &[Bar], from: Currency, to: Currency) -> Result<Vec<Bar>, CurrencyError> {
+      bars.iter().map(|bar| {
+         let rate = self.rate(from, to, bar.timestamp).ok_or(CurrencyError::MissingRate { from, to })?;
+         Ok(Bar { open: bar.open * rate, high: bar.high * rate, low: bar.low * rate, close: bar.close * rate, ..*bar })
+      }).collect()
+   }
+
+   /// The currencies with rates
+   pub fn currencies(&self) -> BTreeSet<Currency> {
+      self.rates.keys().flat_map(|(base, quote)| vec![ *base, *quote ]).collect()
+   }
+
+   /// The rate of the pair or the inverse of the opposite pair
+   fn direct(&self, from: Currency, to: Currency, timestamp: i64) -> Option<f64> {
+      let at = |pair| self.rates.get(&pair).and_then(|rates: &BTreeMap<i64, f64>| rates.range(..=timestamp).next_back()).map(|(_, rate)| *rate);
+      at((from, to)).or_else(|| at((to, from)).map(|rate| 1.0 / rate))
+   }
+}
+
+/// The base and quote currencies of a currency pair symbol
+fn parse_pair(symbol: &str) -> Result<(Currency, Currency), CurrencyError> {
+   let invalid = || CurrencyError::InvalidPair(symbol.to_string());
+   let codes = symbol.trim().trim_end_matches("=X").replace('/', "");
+   if !codes.is_ascii() { return Err(invalid()) }
+
+   match codes.len() {
+      3 => Ok((Currency::USD, codes.parse()?)),
+      6 => Ok((codes[..3].parse()?, codes[3..].parse()?)),
+      _ => Err(invalid())
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::TradingSession;
+
+   fn assert_close(expected: f64, actual: f64) {
+      assert!((expected - actual).abs() < 1e-9, "expected {} but was {}", expected, actual);
+   }
+
+   fn quote(symbol: &str, timestamp: i64, price: f64) -> Quote {
+      Quote { symbol: symbol.to_string(), timestamp, session: TradingSession::Regular, price, volume: 0 }
+   }
+
+   #[test]
+   fn verify_currencies() {
+      assert_eq!(Currency::EUR, "EUR".parse().unwrap());
+      assert_eq!(Err(CurrencyError::UnknownCurrency("ABC".to_string())), "ABC".parse::<Currency>());
+      assert_eq!((0, 2, 3), (Currency::JPY.minor_units(), Currency::USD.minor_units(), "KWD".parse::<Currency>().unwrap().minor_units()));
+      assert!(CODES.windows(2).all(|pair| pair[0] < pair[1]));
+   }
+
+   #[test]
+   fn verify_money() {
+      let (dollars, euros) = (Money::new(10.25, Currency::USD), Money::new(5.0, Currency::EUR));
+      assert_eq!(Money::new(20.5, Currency::USD), dollars.checked_add(dollars).unwrap());
+      assert_eq!(Err(CurrencyError::Mismatch { expected: Currency::USD, found: Currency::EUR }), dollars.checked_sub(euros));
+      assert!(Money::sum(Currency::USD, &[ dollars, euros ]).is_err());
+
+      assert_eq!("20.50 USD", (dollars * 2.0).to_string());
+      assert_eq!("1235 JPY", Money::new(1234.6, Currency::JPY).to_string());
+      assert_eq!(Money::new(3.42, Currency::USD), (dollars / 3.0).round());
+      assert_eq!(Money::new(-5.0, Currency::EUR), -euros);
+   }
+
+   #[test]
+   fn verify_rates() {
+      let mut rates = FxRates::new();
+      rates.update(&quote("EURUSD=X", 1_000, 1.1)).unwrap();
+      rates.update(&quote("EURUSD=X", 3_000, 1.2)).unwrap();
+      rates.update(&quote("JPY=X", 1_000, 150.0)).unwrap();
+      assert!(rates.update(&quote("EURUSDX", 1_000, 1.0)).is_err());
+
+      // the rate at or before the timestamp, inverted when only the opposite pair is known
+      assert_eq!(None, rates.rate(Currency::EUR, Currency::USD, 999));
+      assert_close(1.1, rates.rate(Currency::EUR, Currency::USD, 2_999).unwrap());
+      assert_close(1.0 / 1.2, rates.latest(Currency::USD, Currency::EUR).unwrap());
+
+      // euros to yen through dollars
+      assert_close(1.2 * 150.0, rates.latest(Currency::EUR, Currency::JPY).unwrap());
+      assert_eq!(None, rates.latest(Currency::EUR, Currency::GBP));
+
+      let converted = rates.convert(Money::new(100.0, Currency::EUR), Currency::USD, 2_000).unwrap();
+      assert_close(110.0, converted.amount);
+      assert_eq!(Err(CurrencyError::MissingRate { from: Currency::GBP, to: Currency::USD }), rates.convert(Money::new(1.0, Currency::GBP), Currency::USD, 2_000));
+   }
+
+   #[test]
+   fn verify_bars() {
+      let mut rates = FxRates::new();
+      rates.insert(Currency::GBP, Currency::USD, 0, 1.25);
+      rates.insert(Currency::GBP, Currency::USD, 2_000, 1.5);
+
+      let bars = vec![
+         Bar { timestamp: 1_000, open: 10.0, high: 12.0, low: 8.0, close: 11.0, volume: Some(5) },
+         Bar { timestamp: 2_000, open: 11.0, high: 11.0, low: 10.0, close: 10.0, volume: Some(6) }
+      ];
+      let converted = rates.convert_bars(&bars, Currency::USD, Currency::GBP).unwrap();
+      assert_close(8.0, converted[0].open);
+      assert_close(9.6, converted[0].high);
+      assert_close(10.0 / 1.5, converted[1].close);
+      assert_eq!(Some(6), converted[1].volume);
+   }
+}
\ No newline at end of file
@@ -0,0 +1,453 @@
+//! Exact fixed-point prices.
+//!
+//! A [`Price`] holds a whole number of hundred-millionths, so sums, differences and tick
+//! comparisons never drift the way `f64` does.  [`DecimalBar`] and [`DecimalQuote`] are the decimal
+//! variants of [`Bar`] and [`Quote`] - indicators still work in `f64`, and are fed through the
+//! conversions at the boundary.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{ Add, AddAssign, Div, Mul, Neg, Sub, SubAssign };
+use std::str::FromStr;
+use crate::{ Bar, Quote, Timestamped, TradingSession };
+use crate::instruments::Rounding;
+
+/// The number of decimal places a price holds
+const DECIMALS: usize = 8;
+
+/// The raw value of one whole unit
+const SCALE: i64 = 100_000_000;
+
+/// Why a string couldn't be parsed as a price
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParsePriceError {
+   /// The string isn't a decimal number
+   Invalid(String),
+
+   /// The number has more significant decimal places than a price holds
+   TooPrecise(String),
+
+   /// The number is too large for a price
+   Overflow(String)
+}
+impl fmt::Display for ParsePriceError {
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+         Self::Invalid(value) => write!(f, "'{}' isn't a decimal number", value),
+         Self::TooPrecise(value) => write!(f, "'{}' has more than {} decimal places", value, DECIMALS),
+         Self::Overflow(value) => write!(f, "'{}' is too large for a price", value)
+      }
+   }
+}
+impl std::error::Error for ParsePriceError {}
+
+/// A fixed-point price with 8 decimal places, from about -92 billion to 92 billion.
+///
+/// The operators panic when a result is out of that range or when dividing by zero - the `checked_`
+/// methods return `None` instead.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Price(i64);
+impl Price {
+   /// A price of zero
+   pub const ZERO: Self = Self(0);
+
+   /// Creates a price from a whole number of hundred-millionths
+   pub const fn from_raw(raw: i64) -> Self { Self(raw) }
+
+   /// The whole number of hundred-millionths in the price
+   pub const fn raw(&self) -> i64 { self.0 }
+
+   /// Converts from the nearest price to a float - `None` if it isn't finite or is too large.
+   ///
+   /// Converting a price to `f64` and back gives the same price while there are at most 15
+   /// significant digits, which covers any price below 10 million.
+   pub fn from_f64(value: f64) -> Option<Self> {
+      let raw = (value * SCALE as f64).round();
+      if !raw.is_finite() || raw.abs() >= i64::MAX as f64 { return None }
+      Some(Self(raw as i64))
+   }
+
+   /// Converts to the nearest float
+   pub fn to_f64(&self) -> f64 { self.0 as f64 / SCALE as f64 }
+
+   /// The absolute value of the price
+   pub fn abs(&self) -> Self { Self(self.0.abs()) }
+
+   /// Whether the price is below zero
+   pub fn is_negative(&self) -> bool { self.0 < 0 }
+
+   /// Moves the price onto a multiple of a tick - halves round away from zero when rounding to the nearest,
+   /// and the price is kept when the tick it would move to is out of range
+   pub fn round_to_tick(&self, tick: Price, rounding: Rounding) -> Self {
+      if tick.0 <= 0 { return *self }
+
+      let (ticks, remainder) = (self.0.div_euclid(tick.0), self.0.rem_euclid(tick.0));
+      let up = match rounding {
+         Rounding::Down => false,
+         Rounding::Up => remainder > 0,
+         Rounding::Nearest => remainder > tick.0 - remainder || (remainder == tick.0 - remainder && !self.is_negative())
+      };
+      let ticks = if up { ticks.checked_add(1) } else { Some(ticks) };
+      ticks.and_then(|ticks| ticks.checked_mul(tick.0)).map_or(*self, Self)
+   }
+
+   /// Rounds to the nearest number of decimal places - halves round away from zero
+   pub fn round_dp(&self, decimals: u32) -> Self {
+      if decimals as usize >= DECIMALS { return *self }
+      self.round_to_tick(Self(10i64.pow(DECIMALS as u32 - decimals)), Rounding::Nearest)
+   }
+
+   /// Whether the price is a whole number of ticks
+   pub fn is_multiple_of(&self, tick: Price) -> bool { tick.0 != 0 && self.0 % tick.0 == 0 }
+
+   /// Adds another price - `None` if the sum is out of range
+   pub fn checked_add(&self, other: Price) -> Option<Self> { self.0.checked_add(other.0).map(Self) }
+
+   /// Subtracts another price - `None` if the difference is out of range
+   pub fn checked_sub(&self, other: Price) -> Option<Self> { self.0.checked_sub(other.0).map(Self) }
+
+   /// Multiplies by a whole quantity exactly - `None` if the product is out of range
+   pub fn checked_mul_int(&self, quantity: i64) -> Option<Self> { self.0.checked_mul(quantity).map(Self) }
+
+   /// Multiplies by another price, rounding to the nearest hundred-millionth - `None` if the product is out of range
+   pub fn checked_mul(&self, other: Price) -> Option<Self> { divide_rounded(self.0 as i128 * other.0 as i128, scaled(1)).map(Self) }
+
+   /// Divides by a whole number, rounding to the nearest hundred-millionth - `None` if the divisor is zero
+   pub fn checked_div_int(&self, divisor: i64) -> Option<Self> { divide_rounded(self.0 as i128, divisor as i128).map(Self) }
+
+   /// Divides by another price, rounding to the nearest hundred-millionth - `None` if the divisor is zero or the
+   /// quotient is out of range
+   pub fn checked_div(&self, other: Price) -> Option<Self> { divide_rounded(scaled(self.0), other.0 as i128).map(Self) }
+}
+impl Add for Price {
+   type Output = Self;
+
+   fn add(self, other: Self) -> Self { self.checked_add(other).expect("price overflow") }
+}
+impl AddAssign for Price {
+   fn add_assign(&mut self, other: Self) { *self = *self + other }
+}
+impl Sub for Price {
+   type Output = Self;
+
+   fn sub(self, other: Self) -> Self { self.checked_sub(other).expect("price overflow") }
+}
+impl SubAssign for Price {
+   fn sub_assign(&mut self, other: Self) { *self = *self - other }
+}
+impl Neg for Price {
+   type Output = Self;
+
+   fn neg(self) -> Self { Self(self.0.checked_neg().expect("price overflow")) }
+}
+impl Mul<i64> for Price {
+   type Output = Self;
+
+   /// Multiplies by a whole quantity exactly
+   fn mul(self, quantity: i64) -> Self { self.checked_mul_int(quantity).expect("price overflow") }
+}
+impl Mul for Price {
+   type Output = Self;
+
+   /// Multiplies by another price, rounding to the nearest hundred-millionth
+   fn mul(self, other: Self) -> Self { self.checked_mul(other).expect("price overflow") }
+}
+impl Div<i64> for Price {
+   type Output = Self;
+
+   /// Divides by a whole number, rounding to the nearest hundred-millionth
+   fn div(self, divisor: i64) -> Self { self.checked_div_int(divisor).expect("price division by zero") }
+}
+impl Div for Price {
+   type Output = Self;
+
+   /// Divides by another price, rounding to the nearest hundred-millionth
+   fn div(self, other: Self) -> Self { self.checked_div(other).expect("price division by zero or overflow") }
+}
+impl Sum for Price {
+   fn sum<I: Iterator<Item = Self>>(prices: I) -> Self { prices.fold(Self::ZERO, Add::add) }
+}
+impl FromStr for Price {
+   type Err = ParsePriceError;
+
+   /// Parses a decimal number such as `-12.345` - extra decimal places are only allowed when zero
+   fn from_str(value: &str) -> Result<Self, Self::Err> {
+      let text = value.trim();
+      let (negative, digits) = match text.strip_prefix('-') {
+         Some(digits) => (true, digits),
+         None => (false, text.strip_prefix('+').unwrap_or(text))
+      };
+      let (whole, fraction) = match digits.find('.') {
+         Some(index) => (&digits[..index], &digits[index + 1..]),
+         None => (digits, "")
+      };
+
+      let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+      if whole.len() + fraction.len() == 0 || !is_digits(whole) || !is_digits(fraction) {
+         return Err(ParsePriceError::Invalid(value.to_string()))
+      }
+      if fraction.len() > DECIMALS && fraction[DECIMALS..].chars().any(|c| c != '0') {
+         return Err(ParsePriceError::TooPrecise(value.to_string()))
+      }
+
+      let fraction = format!("{:0<width$}", &fraction[..fraction.len().min(DECIMALS)], width = DECIMALS);
+      let overflow = || ParsePriceError::Overflow(value.to_string());
+      let whole: i64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| overflow())? };
+      let raw = whole.checked_mul(SCALE).and_then(|raw| raw.checked_add(fraction.parse::<i64>().unwrap_or(0))).ok_or_else(overflow)?;
+      Ok(Self(if negative { -raw } else { raw }))
+   }
+}
+impl fmt::Display for Price {
+   /// Formats without trailing zeros, or rounded to the precision when one is given - e.g. `{:.2}` - and
+   /// honours the width, fill, alignment and sign flags
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      let decimals = f.precision().map(|precision| precision.min(DECIMALS));
+      let price = decimals.map_or(*self, |decimals| self.round_dp(decimals as u32));
+
+      let (whole, fraction) = (price.0.unsigned_abs() / SCALE as u64, price.0.unsigned_abs() % SCALE as u64);
+      let fraction = format!("{:0width$}", fraction, width = DECIMALS);
+      let fraction = match decimals {
+         Some(decimals) => format!("{:0<width$}", &fraction[..decimals], width = f.precision().unwrap_or(0)),
+         None => fraction.trim_end_matches('0').to_string()
+      };
+
+      // padded as a number, as the precision is the decimal places rather than a maximum width
+      let digits = if fraction.is_empty() { whole.to_string() } else { format!("{}.{}", whole, fraction) };
+      f.pad_integral(!price.is_negative(), "", &digits)
+   }
+}
+
+/// The decimal variant of a [`Bar`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecimalBar {
+   /// A time during the unit of time - usually the time at open.
+   pub timestamp: i64,
+
+   /// The price at the start of the unit of time
+   pub open: Price,
+
+   /// The highest price seen during the unit of time
+   pub high: Price,
+
+   /// The lowest price seen during the unit of time
+   pub low: Price,
+
+   /// The price at the end of the unit of time
+   pub close: Price,
+
+   /// The optional volume traded during the unit of time
+   pub volume: Option<u64>
+}
+impl DecimalBar {
+   /// Converts from a bar with the nearest prices - `None` if a price can't be represented
+   pub fn from_bar(bar: &Bar) -> Option<Self> {
+      Some(Self {
+         timestamp: bar.timestamp,
+         open: Price::from_f64(bar.open)?,
+         high: Price::from_f64(bar.high)?,
+         low: Price::from_f64(bar.low)?,
+         close: Price::from_f64(bar.close)?,
+         volume: bar.volume
+      })
+   }
+
+   /// Converts to a bar with the nearest float prices
+   pub fn to_bar(&self) -> Bar {
+      Bar {
+         timestamp: self.timestamp,
+         open: self.open.to_f64(),
+         high: self.high.to_f64(),
+         low: self.low.to_f64(),
+         close: self.close.to_f64(),
+         volume: self.volume
+      }
+   }
+}
+impl Timestamped for DecimalBar {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// The decimal variant of a [`Quote`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecimalQuote {
+   /// The symbol for the quote
+   pub symbol: String,
+
+   /// The timestamp of the quote in millisecond accuracy
+   pub timestamp: i64,
+
+   /// The trading session of the quote - pre market / regular hours / after hours
+   pub session: TradingSession,
+
+   /// The price of the quote
+   pub price: Price,
+
+   /// The volume traded
+   pub volume: u64
+}
+impl DecimalQuote {
+   /// Converts from a quote with the nearest price - `None` if the price can't be represented
+   pub fn from_quote(quote: &Quote) -> Option<Self> {
+      Some(Self { symbol: quote.symbol.clone(), timestamp: quote.timestamp, session: quote.session, price: Price::from_f64(quote.price)?, volume: quote.volume })
+   }
+
+   /// Converts to a quote with the nearest float price
+   pub fn to_quote(&self) -> Quote {
+      Quote { symbol: self.symbol.clone(), timestamp: self.timestamp, session: self.session, price: self.price.to_f64(), volume: self.volume }
+   }
+}
+impl Timestamped for DecimalQuote {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+#[cfg(feature = "indicators")]
+mod indicators {
+   use crate::Bar;
+   use crate::indicators::UpdatableIndicator;
+   use super::{ DecimalBar, Price };
+
+   /// Indicators of bars take decimal bars through their float prices
+   impl<T: UpdatableIndicator<Bar>> UpdatableIndicator<DecimalBar> for T {
+      type Output = T::Output;
+
+      fn commit(&mut self, current: &DecimalBar) -> Self::Output { UpdatableIndicator::<Bar>::commit(self, &current.to_bar()) }
+
+      fn update(&mut self, current: &DecimalBar) -> Self::Output { UpdatableIndicator::<Bar>::update(self, &current.to_bar()) }
+   }
+
+   /// Indicators of values take prices through their float values
+   impl<T: UpdatableIndicator<f64>> UpdatableIndicator<Price> for T {
+      type Output = T::Output;
+
+      fn commit(&mut self, current: &Price) -> Self::Output { UpdatableIndicator::<f64>::commit(self, &current.to_f64()) }
+
+      fn update(&mut self, current: &Price) -> Self::Output { UpdatableIndicator::<f64>::update(self, &current.to_f64()) }
+   }
+}
+
+/// A raw value multiplied by the scale, without overflowing
+fn scaled(raw: i64) -> i128 { raw as i128 * SCALE as i128 }
+
+/// Divides rounding halves away from zero - `None` if the denominator is zero or the quotient doesn't fit
+fn divide_rounded(numerator: i128, denominator: i128) -> Option<i64> {
+   if denominator == 0 { return None }
+
+   let (quotient, remainder) = (numerator / denominator, numerator % denominator);
+   let away = 2 * remainder.abs() >= denominator.abs();
+   let sign = if (numerator < 0) == (denominator < 0) { 1 } else { -1 };
+   i64::try_from(quotient + if away { sign } else { 0 }).ok()
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn price(value: &str) -> Price { value.parse().unwrap() }
+
+   #[test]
+   fn verify_parsing() {
+      assert_eq!(Price::from_raw(1_234_500_000), price("12.345"));
+      assert_eq!(Price::from_raw(-50_000_000), price("-.5"));
+      assert_eq!(Price::from_raw(100_000_000), price("+1.000000000"));
+      assert_eq!(Err(ParsePriceError::TooPrecise("0.000000001".to_string())), "0.000000001".parse::<Price>());
+      assert_eq!(Err(ParsePriceError::Invalid("1.2.3".to_string())), "1.2.3".parse::<Price>());
+      assert_eq!(Err(ParsePriceError::Invalid("-".to_string())), "-".parse::<Price>());
+      assert!(matches!("100000000000000".parse::<Price>(), Err(ParsePriceError::Overflow(_))));
+   }
+
+   #[test]
+   fn verify_formatting() {
+      assert_eq!("12.345", price("12.345").to_string());
+      assert_eq!("-0.5", price("-0.5").to_string());
+      assert_eq!("7", price("7.00").to_string());
+      assert_eq!("12.35", format!("{:.2}", price("12.345")));
+      assert_eq!("-1.000", format!("{:.3}", price("-0.9999")));
+      assert_eq!("0.1000000000", format!("{:.10}", price("0.1")));
+      assert_eq!("    -12.35|12.345    |+7", format!("{:>10.2}|{:<10}|{:+}", price("-12.345"), price("12.345"), price("7")));
+      assert_eq!("92233720368.54", format!("{:.2}", Price::from_raw(i64::MAX)));
+   }
+
+   #[test]
+   fn verify_arithmetic() {
+      // the classic float drift doesn't happen
+      assert_eq!(price("0.3"), price("0.1") + price("0.2"));
+      assert_ne!(0.3, 0.1 + 0.2);
+
+      let total: Price = vec![ price("0.1"); 10 ].into_iter().sum();
+      assert_eq!(price("1"), total);
+      assert_eq!(price("-250.5"), -(price("2.505") * 100));
+      assert_eq!(price("3.7"), price("1.85") * price("2"));
+      assert_eq!(price("0.33333333"), price("1") / 3);
+      assert_eq!(price("0.66666667"), price("2") / price("3"));
+      assert_eq!(price("-0.66666667"), price("-2") / 3);
+   }
+
+   #[test]
+   fn verify_checked_arithmetic() {
+      let large = price("1000");
+      assert_eq!(None, large.checked_mul_int(100_000_000));
+      assert_eq!(Some(price("100000")), large.checked_mul_int(100));
+      assert_eq!(None, large.checked_mul(large * 100_000));
+      assert_eq!(None, Price::from_raw(i64::MAX).checked_add(Price::from_raw(1)));
+      assert_eq!(None, Price::from_raw(i64::MIN).checked_sub(Price::from_raw(1)));
+      assert_eq!(None, large.checked_div(Price::ZERO));
+      assert_eq!(None, large.checked_div_int(0));
+      assert_eq!(None, large.checked_div(Price::from_raw(1)));
+      assert_eq!(Some(price("2.5")), price("5").checked_div(price("2")));
+   }
+
+   #[test]
+   #[should_panic(expected = "price overflow")]
+   fn verify_overflow_panics() { let _ = price("1000") * 100_000_000; }
+
+   #[test]
+   fn verify_ticks() {
+      let tick = price("0.25");
+      assert_eq!(price("10.25"), price("10.3").round_to_tick(tick, Rounding::Nearest));
+      assert_eq!(price("10.5"), price("10.375").round_to_tick(tick, Rounding::Nearest));
+      assert_eq!(price("10.5"), price("10.26").round_to_tick(tick, Rounding::Up));
+      assert_eq!(price("-10.5"), price("-10.26").round_to_tick(tick, Rounding::Down));
+      assert!(price("10.75").is_multiple_of(tick) && !price("10.7").is_multiple_of(tick));
+      assert_eq!(price("1.24"), price("1.235").round_dp(2));
+
+      // halves round away from zero, the same as division
+      assert_eq!(price("-10.5"), price("-10.375").round_to_tick(tick, Rounding::Nearest));
+      assert_eq!(price("-1.24"), price("-1.235").round_dp(2));
+      assert_eq!(price("-0.00000001"), Price::from_raw(-1) / 2);
+   }
+
+   #[test]
+   fn verify_conversions() {
+      for value in [ 0.1, 12.345, -99.99, 1234567.89012345, 0.00000001 ].iter() {
+         let converted = Price::from_f64(*value).unwrap();
+         assert_eq!(*value, converted.to_f64());
+         assert_eq!(converted, Price::from_f64(converted.to_f64()).unwrap());
+      }
+      assert_eq!(None, Price::from_f64(f64::NAN));
+      assert_eq!(None, Price::from_f64(1e20));
+
+      let bar = Bar { timestamp: 5, open: 10.1, high: 10.2, low: 9.9, close: 10.0, volume: Some(100) };
+      let decimal = DecimalBar::from_bar(&bar).unwrap();
+      assert_eq!(price("10.2"), decimal.high);
+      assert_eq!((10.1, 10.2, 9.9, 10.0), { let bar = decimal.to_bar(); (bar.open, bar.high, bar.low, bar.close) });
+
+      let quote = Quote { symbol: "ABC".to_string(), timestamp: 5, session: TradingSession::Regular, price: 10.05, volume: 7 };
+      assert_eq!(quote.price, DecimalQuote::from_quote(&quote).unwrap().to_quote().price);
+   }
+
+   #[cfg(feature = "indicators")]
+   #[test]
+   fn verify_indicators() {
+      use crate::indicators::{ ExponentialMovingAverage, UpdatableIndicator };
+
+      let (mut decimal, mut float) = (ExponentialMovingAverage::new(3), ExponentialMovingAverage::new(3));
+      for close in [ "10.1", "10.3", "10.2" ].iter() {
+         let value: f64 = close.parse().unwrap();
+         assert_eq!(float.commit(&value), decimal.commit(&price(close)));
+      }
+   }
+}
\ No newline at end of file
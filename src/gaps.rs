@@ -0,0 +1,197 @@
+//! Detection and filling of missing bars.
+//!
+//! The expected bars come from an exchange calendar, so weekends, holidays and closed hours are never
+//! reported as gaps.  Intraday intervals expect a bar at every interval from the regular session open
+//! up to the close, and daily intervals expect a bar on every trading day.  Intervals longer than a
+//! day have no fixed schedule and are never reported as having gaps.
+
+use std::collections::HashSet;
+use crate::{ Bar, Interval };
+use crate::calendar::ExchangeCalendar;
+
+/// How missing bars are dealt with when filling a series
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FillStrategy {
+   /// Inserts flat, zero volume bars at the previous close
+   ForwardFill,
+
+   /// Inserts flat, zero volume bars linearly interpolated between the surrounding closes
+   Interpolate,
+
+   /// Leaves the series as is, only flagging the bars that follow a gap
+   Flag
+}
+
+/// A bar from a filled series
+#[derive(Clone, Copy, Debug)]
+pub struct FilledBar {
+   /// The bar - either from the original series or created to fill a gap
+   pub bar: Bar,
+
+   /// Whether the bar was created to fill a gap
+   pub filled: bool,
+
+   /// Whether the bar is the first of the original bars after a gap
+   pub after_gap: bool
+}
+
+/// Finds the timestamps of the bars missing from a series sorted by timestamp.
+///
+/// Only the span from the first to the last bar is checked.  Intraday bars are matched to the interval
+/// since the session open that they fall in, so bars stamped slightly off the interval still count.
+/// Daily bars are matched to trading days by their date local to the exchange, and missing daily bars
+/// are reported at the session open - a daily bar stamped at midnight UTC is the evening before in New
+/// York, so it counts as the previous day's bar for US exchanges.
+pub fn find_gaps(bars: &[Bar], interval: Interval, calendar: &ExchangeCalendar) -> Vec<i64> {
+   let present: HashSet<i64> = bars.iter().map(|bar| slot(bar.timestamp, interval, calendar)).collect();
+   expected_slots(bars, interval, calendar).into_iter().filter(|timestamp| !present.contains(timestamp)).collect()
+}
+
+/// Fills the missing bars of a series sorted by timestamp
+pub fn fill_gaps(bars: &[Bar], interval: Interval, calendar: &ExchangeCalendar, strategy: FillStrategy) -> Vec<FilledBar> {
+   let gaps = find_gaps(bars, interval, calendar);
+   let mut filled = Vec::with_capacity(bars.len() + if strategy == FillStrategy::Flag { 0 } else { gaps.len() });
+   let mut gaps = gaps.into_iter().peekable();
+
+   for (index, bar) in bars.iter().enumerate() {
+      let current_slot = slot(bar.timestamp, interval, calendar);
+
+      let mut missing = vec![];
+      while let Some(timestamp) = gaps.next_if(|timestamp| *timestamp < current_slot) { missing.push(timestamp) }
+
+      if index > 0 && strategy != FillStrategy::Flag {
+         let previous = bars[index - 1].close;
+         let steps = missing.len() as f64 + 1.0;
+         for (step, timestamp) in missing.iter().enumerate() {
+            let price = match strategy {
+               FillStrategy::Interpolate => previous + (bar.close - previous) * (step as f64 + 1.0) / steps,
+               _ => previous
+            };
+            let bar = Bar { timestamp: *timestamp, open: price, high: price, low: price, close: price, volume: Some(0) };
+            filled.push(FilledBar { bar, filled: true, after_gap: false });
+         }
+      }
+
+      filled.push(FilledBar { bar: *bar, filled: false, after_gap: !missing.is_empty() });
+   }
+
+   filled
+}
+
+/// Gets the expected timestamp that a bar's timestamp fills - the start of its interval from the session
+/// open, or the open itself for daily bars
+fn slot(timestamp: i64, interval: Interval, calendar: &ExchangeCalendar) -> i64 {
+   let date = calendar.local_date(timestamp);
+   let open = match calendar.hours(date) {
+      Some(hours) => calendar.timestamp_at(date, hours.open),
+      None => return timestamp
+   };
+
+   match (interval, interval.minutes()) {
+      (_, Some(minutes)) if timestamp >= open => {
+         let length = i64::from(minutes) * 60_000;
+         open + (timestamp - open) / length * length
+      },
+      (Interval::_1d, None) => open,
+      _ => timestamp
+   }
+}
+
+/// Gets all of the expected timestamps from the first to the last bar
+fn expected_slots(bars: &[Bar], interval: Interval, calendar: &ExchangeCalendar) -> Vec<i64> {
+   let (first, last) = match (bars.first(), bars.last()) {
+      (Some(first), Some(last)) => (first.timestamp, last.timestamp),
+      _ => return vec![]
+   };
+
+   let start = calendar.local_date(first);
+   let end = calendar.local_date(last);
+   let mut expected = vec![];
+
+   for date in calendar.trading_days(start, end) {
+      let hours = calendar.hours(date).expect("trading days have hours");
+      match (interval, interval.minutes()) {
+         (_, Some(minutes)) => {
+            let mut at = hours.open;
+            while at < hours.close {
+               expected.push(calendar.timestamp_at(date, at));
+               at += minutes as i32;
+            }
+         },
+         (Interval::_1d, None) => expected.push(calendar.timestamp_at(date, hours.open)),
+         _ => return vec![]
+      }
+   }
+
+   let start = slot(first, interval, calendar);
+   let end = slot(last, interval, calendar);
+   expected.retain(|timestamp| *timestamp >= start && *timestamp <= end);
+   expected
+}
+
+
+#[cfg(test)]
+mod tests {
+   use chrono::NaiveDate;
+   use crate::calendar::Exchange;
+   use super::*;
+
+   fn bar(timestamp: i64, close: f64) -> Bar {
+      Bar { timestamp, open: close, high: close, low: close, close, volume: Some(100) }
+   }
+
+   fn at(calendar: &ExchangeCalendar, day: u32, minutes: i32) -> i64 {
+      calendar.timestamp_at(NaiveDate::from_ymd_opt(2024, 3, day).unwrap(), minutes)
+   }
+
+   #[test]
+   fn verify_find_gaps_intraday() {
+      let calendar = ExchangeCalendar::new(Exchange::NYSE);
+      let bars = vec![
+         bar(at(&calendar, 12, 15 * 60), 1.0),
+         bar(at(&calendar, 12, 15 * 60 + 30), 2.0),
+         bar(at(&calendar, 13, 9 * 60 + 30), 3.0),
+         bar(at(&calendar, 13, 10 * 60 + 30), 4.0)
+      ];
+
+      // The overnight close isn't a gap
+      let gaps = find_gaps(&bars, Interval::_30m, &calendar);
+      assert_eq!(vec![ at(&calendar, 13, 10 * 60) ], gaps);
+
+      // bars stamped a little after each interval starts are still on the grid
+      let late: Vec<Bar> = bars.iter().map(|bar| Bar { timestamp: bar.timestamp + 1, ..*bar }).collect();
+      assert_eq!(gaps, find_gaps(&late, Interval::_30m, &calendar));
+      assert_eq!(5, fill_gaps(&late, Interval::_30m, &calendar, FillStrategy::ForwardFill).len());
+   }
+
+   #[test]
+   fn verify_find_gaps_daily() {
+      let calendar = ExchangeCalendar::new(Exchange::NYSE);
+      let april = |day| calendar.timestamp_at(NaiveDate::from_ymd_opt(2024, 4, day).unwrap(), 9 * 60 + 30);
+      let bars = vec![ bar(at(&calendar, 27, 0), 1.0), bar(at(&calendar, 28, 0), 2.0), bar(april(2), 3.0) ];
+
+      // Good Friday and the weekend aren't gaps - the Monday is
+      let gaps = find_gaps(&bars, Interval::_1d, &calendar);
+      assert_eq!(vec![ april(1) ], gaps);
+   }
+
+   #[test]
+   fn verify_fill_gaps() {
+      let calendar = ExchangeCalendar::new(Exchange::NYSE);
+      let bars = vec![ bar(at(&calendar, 12, 10 * 60), 10.0), bar(at(&calendar, 12, 11 * 60 + 30), 40.0) ];
+
+      let forward = fill_gaps(&bars, Interval::_30m, &calendar, FillStrategy::ForwardFill);
+      let closes: Vec<f64> = forward.iter().map(|filled| filled.bar.close).collect();
+      assert_eq!(vec![ 10.0, 10.0, 10.0, 40.0 ], closes);
+      assert_eq!(Some(0), forward[1].bar.volume);
+      assert!(forward[2].filled && forward[3].after_gap && !forward[3].filled);
+
+      let interpolated = fill_gaps(&bars, Interval::_30m, &calendar, FillStrategy::Interpolate);
+      let closes: Vec<f64> = interpolated.iter().map(|filled| filled.bar.close).collect();
+      assert_eq!(vec![ 10.0, 20.0, 30.0, 40.0 ], closes);
+
+      let flagged = fill_gaps(&bars, Interval::_30m, &calendar, FillStrategy::Flag);
+      assert_eq!(2, flagged.len());
+      assert!(!flagged[0].after_gap && flagged[1].after_gap);
+   }
+}
\ No newline at end of file
@@ -117,7 +117,7 @@ mod test {
       let mut macd = MovingAverageCD::new(7, 15, 9);
 
       // WHEN - we get the values
-      let result = macd.update(&100.0);
+      macd.update(&100.0);
 
       // THEN - are what we expect
       let current = macd.current();
@@ -133,7 +133,7 @@ mod test {
 
       // WHEN - we get the values
       macd.commit(&100.0);
-      let result = macd.update(&50.0);
+      macd.update(&50.0);
 
       // THEN - are what we expect
       let current = macd.current();
@@ -141,4 +141,16 @@ mod test {
       assert_eq!(6.0625, current.signal);
       assert_eq!(14.0625, current.value);
    }
+   #[test]
+   fn update_returns_current() {
+      // GIVEN - a MACD with a committed value
+      let mut macd = MovingAverageCD::new(7, 15, 9);
+      macd.commit(&100.0);
+
+      // WHEN - we update with the next value
+      let result = macd.update(&50.0);
+
+      // THEN - the result is the current value
+      assert_eq!(result, macd.current());
+   }
 }
\ No newline at end of file
@@ -1,10 +1,17 @@
 mod traits;
+pub use traits::{ TechnicalIndicator, UpdatableIndicator };
 
 mod averages;
 pub use averages::ExponentialMovingAverage;
 
 mod macd;
-pub use macd::MACD;
+pub use macd::{ MACD, MovingAverageCD };
 
 mod rsi;
-pub use rsi::RelativeStrengthIndex;
\ No newline at end of file
+pub use rsi::RelativeStrengthIndex;
+
+mod rolling;
+pub use rolling::{ RollingBeta, RollingCorrelation, RollingCovariance, RollingMax, RollingMean, RollingMin, RollingQuantile, RollingVariance, RollingZScore };
+
+mod regression;
+pub use regression::{ LinearRegression, RegressionChannel };
\ No newline at end of file
@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use crate::Bar;
+use crate::statistics::linear_regression;
+use super::traits::{ TechnicalIndicator, UpdatableIndicator };
+
+type Precision = f64;
+
+/// The values of a linear regression channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegressionChannel {
+   /// The fitted value at the latest time period
+   pub value: Precision,
+
+   /// The change in the fitted value per time period
+   pub slope: Precision,
+
+   /// The fitted value plus the channel width
+   pub upper: Precision,
+
+   /// The fitted value less the channel width
+   pub lower: Precision,
+
+   /// The fraction of the variance explained by the fit
+   pub r_squared: Precision
+}
+
+/// Linear regression channel over a number of time periods.
+///
+/// Fits a straight line through the closes of the window, with the channel a number of standard
+/// deviations of the residuals either side of it.
+#[derive(Clone, Debug)]
+pub struct LinearRegression {
+   period: usize,
+
+   /// The number of residual standard deviations either side of the fit
+   width: Precision,
+
+   values: VecDeque<Precision>,
+
+   /// The current channel values
+   current: RegressionChannel
+}
+impl LinearRegression {
+   /// Creates a new linear regression channel for the given time periods.
+   ///
+   /// # Arguments
+   ///
+   /// * `periods` the number of time periods for the fit - often 20 or 100
+   /// * `width` the number of standard deviations of the residuals either side of the fit - often 2
+   pub fn new(periods: u16, width: Precision) -> Self {
+      Self {
+         period: usize::from(periods.max(2)),
+         width,
+         values: VecDeque::new(),
+         current: RegressionChannel { value: 0.0, slope: 0.0, upper: 0.0, lower: 0.0, r_squared: 0.0 }
+      }
+   }
+
+   fn calculate(&mut self, values: &[Precision]) -> RegressionChannel {
+      let x: Vec<Precision> = (0..values.len()).map(|index| index as Precision).collect();
+      let last = values.last().copied().unwrap_or(0.0);
+
+      self.current = match linear_regression(&x, values) {
+         Some(fit) => {
+            let value = fit.predict((values.len() - 1) as Precision);
+            let width = self.width * fit.residual_stdev;
+            RegressionChannel { value, slope: fit.slope, upper: value + width, lower: value - width, r_squared: fit.r_squared }
+         },
+         None => RegressionChannel { value: last, slope: 0.0, upper: last, lower: last, r_squared: 0.0 }
+      };
+
+      self.current
+   }
+
+   fn window_with(&self, current: Precision) -> Vec<Precision> {
+      let skip = usize::from(self.values.len() == self.period);
+      self.values.iter().skip(skip).copied().chain(std::iter::once(current)).collect()
+   }
+
+   fn commit_value(&mut self, current: Precision) -> RegressionChannel {
+      let window = self.window_with(current);
+      if self.values.len() == self.period { self.values.pop_front(); }
+      self.values.push_back(current);
+      self.calculate(&window)
+   }
+
+   fn update_value(&mut self, current: Precision) -> RegressionChannel {
+      let window = self.window_with(current);
+      self.calculate(&window)
+   }
+}
+impl TechnicalIndicator for LinearRegression {
+   type Output = RegressionChannel;
+
+   fn current(self) -> Self::Output { self.current }
+}
+impl UpdatableIndicator<Bar> for LinearRegression {
+   type Output = RegressionChannel;
+
+   fn commit(&mut self, current: &Bar) -> Self::Output { self.commit_value(current.close) }
+
+   fn update(&mut self, current: &Bar) -> Self::Output { self.update_value(current.close) }
+}
+impl UpdatableIndicator<f64> for LinearRegression {
+   type Output = RegressionChannel;
+
+   fn commit(&mut self, current: &f64) -> Self::Output { self.commit_value(*current) }
+
+   fn update(&mut self, current: &f64) -> Self::Output { self.update_value(*current) }
+}
+
+#[cfg(test)]
+mod test {
+   use super::*;
+
+   #[test]
+   fn new() {
+      // GIVEN - a new linear regression channel
+      let regression = LinearRegression::new(5, 2.0);
+
+      // WHEN - we get the values
+      // THEN - are what we expect
+      let current = regression.current();
+      assert_eq!(0.0, current.value);
+      assert_eq!(0.0, current.slope);
+   }
+
+   #[test]
+   fn commit_f64_trend() {
+      // GIVEN - a linear regression channel over 3 periods
+      let mut regression = LinearRegression::new(3, 2.0);
+
+      // WHEN - we commit a straight line after an unrelated value
+      for value in [ 50.0, 10.0, 12.0, 14.0 ].iter() { regression.commit(value); }
+
+      // THEN - the fit is exact and only uses the window
+      let current = regression.current();
+      assert_eq!(2.0, current.slope);
+      assert_eq!(14.0, current.value);
+      assert_eq!(current.upper, current.lower);
+      assert_eq!(1.0, current.r_squared);
+   }
+
+   #[test]
+   fn update_f64_next() {
+      // GIVEN - a linear regression channel with committed values
+      let mut regression = LinearRegression::new(3, 1.0);
+      regression.commit(&10.0);
+      regression.commit(&12.0);
+
+      // WHEN - we update
+      regression.update(&100.0);
+      let result = regression.update(&16.0);
+
+      // THEN - only the last update is used
+      let current = regression.current();
+      assert_eq!(result, current);
+      assert_eq!(3.0, current.slope);
+      assert!(current.upper > current.value && current.lower < current.value);
+   }
+}
\ No newline at end of file
@@ -0,0 +1,790 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use super::traits::{ TechnicalIndicator, UpdatableIndicator };
+
+type Precision = f64;
+
+/// Running means and co-moments of a window of pairs - Welford's algorithm extended to removals
+#[derive(Clone, Copy, Debug, Default)]
+struct Moments {
+   count: usize,
+   mean_x: Precision,
+   mean_y: Precision,
+
+   /// The sum of the products of the deviations from the means of x and y
+   co_moment: Precision,
+
+   /// The sums of the squared deviations from the means of x and y
+   m2_x: Precision,
+   m2_y: Precision
+}
+impl Moments {
+   fn add(&mut self, x: Precision, y: Precision) {
+      self.count += 1;
+      let count = self.count as Precision;
+      let dx = x - self.mean_x;
+      let dy = y - self.mean_y;
+      self.mean_x += dx / count;
+      self.mean_y += dy / count;
+      self.co_moment += dx * (y - self.mean_y);
+      self.m2_x += dx * (x - self.mean_x);
+      self.m2_y += dy * (y - self.mean_y);
+   }
+
+   fn remove(&mut self, x: Precision, y: Precision) {
+      if self.count <= 1 {
+         *self = Self::default();
+         return
+      }
+
+      let remaining = (self.count - 1) as Precision;
+      let mean_x = self.mean_x - (x - self.mean_x) / remaining;
+      let mean_y = self.mean_y - (y - self.mean_y) / remaining;
+      self.co_moment -= (x - mean_x) * (y - self.mean_y);
+      self.m2_x -= (x - mean_x) * (x - self.mean_x);
+      self.m2_y -= (y - mean_y) * (y - self.mean_y);
+      self.mean_x = mean_x;
+      self.mean_y = mean_y;
+      self.count -= 1;
+   }
+
+   fn variance_x(&self) -> Precision { self.sample(self.m2_x).max(0.0) }
+
+   fn covariance(&self) -> Precision { self.sample(self.co_moment) }
+
+   fn beta(&self) -> Precision { if self.m2_y == 0.0 { 0.0 } else { self.co_moment / self.m2_y } }
+
+   fn correlation(&self) -> Precision {
+      if self.m2_x <= 0.0 || self.m2_y <= 0.0 { 0.0 } else { self.co_moment / (self.m2_x * self.m2_y).sqrt() }
+   }
+
+   fn sample(&self, value: Precision) -> Precision {
+      if self.count < 2 { 0.0 } else { value / (self.count - 1) as Precision }
+   }
+}
+
+/// A window of moments over the most recent pairs of values
+#[derive(Clone, Debug)]
+struct MomentWindow {
+   period: usize,
+   values: VecDeque<(Precision, Precision)>,
+   moments: Moments
+}
+impl MomentWindow {
+   fn new(period: u16) -> Self {
+      Self { period: usize::from(period.max(1)), values: VecDeque::new(), moments: Moments::default() }
+   }
+
+   /// Gets the moments with the given pair added, without changing the window
+   fn with(&self, x: Precision, y: Precision) -> Moments {
+      let mut moments = self.moments;
+      if self.values.len() == self.period {
+         let (old_x, old_y) = self.values[0];
+         moments.remove(old_x, old_y);
+      }
+      moments.add(x, y);
+      moments
+   }
+
+   fn push(&mut self, x: Precision, y: Precision) -> Moments {
+      self.moments = self.with(x, y);
+      if self.values.len() == self.period { self.values.pop_front(); }
+      self.values.push_back((x, y));
+      self.moments
+   }
+}
+
+/// Rolling mean over a fixed number of periods
+#[derive(Clone, Debug)]
+pub struct RollingMean {
+   window: MomentWindow,
+   current_value: Precision
+}
+impl RollingMean {
+   /// Creates a new rolling mean for a given number of time periods
+   pub fn new(period_count: u16) -> Self { Self { window: MomentWindow::new(period_count), current_value: 0.0 } }
+}
+impl TechnicalIndicator for RollingMean {
+   type Output = Precision;
+
+   fn current(self) -> Self::Output { self.current_value }
+}
+impl UpdatableIndicator<f64> for RollingMean {
+   type Output = Precision;
+
+   fn commit(&mut self, current: &f64) -> Self::Output {
+      self.current_value = self.window.push(*current, *current).mean_x;
+      self.current_value
+   }
+
+   fn update(&mut self, current: &f64) -> Self::Output {
+      self.current_value = self.window.with(*current, *current).mean_x;
+      self.current_value
+   }
+}
+
+/// Rolling sample variance over a fixed number of periods - using Welford's algorithm
+#[derive(Clone, Debug)]
+pub struct RollingVariance {
+   window: MomentWindow,
+   current_value: Precision
+}
+impl RollingVariance {
+   /// Creates a new rolling variance for a given number of time periods
+   pub fn new(period_count: u16) -> Self { Self { window: MomentWindow::new(period_count), current_value: 0.0 } }
+
+   /// The standard deviation matching the current variance
+   pub fn stdev(&self) -> Precision { self.current_value.sqrt() }
+}
+impl TechnicalIndicator for RollingVariance {
+   type Output = Precision;
+
+   fn current(self) -> Self::Output { self.current_value }
+}
+impl UpdatableIndicator<f64> for RollingVariance {
+   type Output = Precision;
+
+   fn commit(&mut self, current: &f64) -> Self::Output {
+      self.current_value = self.window.push(*current, *current).variance_x();
+      self.current_value
+   }
+
+   fn update(&mut self, current: &f64) -> Self::Output {
+      self.current_value = self.window.with(*current, *current).variance_x();
+      self.current_value
+   }
+}
+
+/// Rolling z-score - how many standard deviations the latest value is from the mean of the window
+#[derive(Clone, Debug)]
+pub struct RollingZScore {
+   window: MomentWindow,
+   current_value: Precision
+}
+impl RollingZScore {
+   /// Creates a new rolling z-score for a given number of time periods
+   pub fn new(period_count: u16) -> Self { Self { window: MomentWindow::new(period_count), current_value: 0.0 } }
+
+   fn calculate(&mut self, value: Precision, moments: Moments) -> Precision {
+      let stdev = moments.variance_x().sqrt();
+      self.current_value = if stdev == 0.0 { 0.0 } else { (value - moments.mean_x) / stdev };
+      self.current_value
+   }
+}
+impl TechnicalIndicator for RollingZScore {
+   type Output = Precision;
+
+   fn current(self) -> Self::Output { self.current_value }
+}
+impl UpdatableIndicator<f64> for RollingZScore {
+   type Output = Precision;
+
+   fn commit(&mut self, current: &f64) -> Self::Output {
+      let moments = self.window.push(*current, *current);
+      self.calculate(*current, moments)
+   }
+
+   fn update(&mut self, current: &f64) -> Self::Output {
+      let moments = self.window.with(*current, *current);
+      self.calculate(*current, moments)
+   }
+}
+
+/// Rolling sample covariance between two series
+#[derive(Clone, Debug)]
+pub struct RollingCovariance {
+   window: MomentWindow,
+   current_value: Precision
+}
+impl RollingCovariance {
+   /// Creates a new rolling covariance for a given number of time periods
+   pub fn new(period_count: u16) -> Self { Self { window: MomentWindow::new(period_count), current_value: 0.0 } }
+}
+impl TechnicalIndicator for RollingCovariance {
+   type Output = Precision;
+
+   fn current(self) -> Self::Output { self.current_value }
+}
+impl UpdatableIndicator<(f64, f64)> for RollingCovariance {
+   type Output = Precision;
+
+   fn commit(&mut self, current: &(f64, f64)) -> Self::Output {
+      self.current_value = self.window.push(current.0, current.1).covariance();
+      self.current_value
+   }
+
+   fn update(&mut self, current: &(f64, f64)) -> Self::Output {
+      self.current_value = self.window.with(current.0, current.1).covariance();
+      self.current_value
+   }
+}
+
+/// Rolling Pearson correlation between two series - 0 while either series doesn't vary in the window
+#[derive(Clone, Debug)]
+pub struct RollingCorrelation {
+   window: MomentWindow,
+   current_value: Precision
+}
+impl RollingCorrelation {
+   /// Creates a new rolling correlation for a given number of time periods
+   pub fn new(period_count: u16) -> Self { Self { window: MomentWindow::new(period_count), current_value: 0.0 } }
+}
+impl TechnicalIndicator for RollingCorrelation {
+   type Output = Precision;
+
+   fn current(self) -> Self::Output { self.current_value }
+}
+impl UpdatableIndicator<(f64, f64)> for RollingCorrelation {
+   type Output = Precision;
+
+   fn commit(&mut self, current: &(f64, f64)) -> Self::Output {
+      self.current_value = self.window.push(current.0, current.1).correlation();
+      self.current_value
+   }
+
+   fn update(&mut self, current: &(f64, f64)) -> Self::Output {
+      self.current_value = self.window.with(current.0, current.1).correlation();
+      self.current_value
+   }
+}
+
+/// Rolling beta of an asset against a benchmark - the input pairs are `(asset, benchmark)` returns
+#[derive(Clone, Debug)]
+pub struct RollingBeta {
+   window: MomentWindow,
+   current_value: Precision
+}
+impl RollingBeta {
+   /// Creates a new rolling beta for a given number of time periods
+   pub fn new(period_count: u16) -> Self { Self { window: MomentWindow::new(period_count), current_value: 0.0 } }
+}
+impl TechnicalIndicator for RollingBeta {
+   type Output = Precision;
+
+   fn current(self) -> Self::Output { self.current_value }
+}
+impl UpdatableIndicator<(f64, f64)> for RollingBeta {
+   type Output = Precision;
+
+   fn commit(&mut self, current: &(f64, f64)) -> Self::Output {
+      self.current_value = self.window.push(current.0, current.1).beta();
+      self.current_value
+   }
+
+   fn update(&mut self, current: &(f64, f64)) -> Self::Output {
+      self.current_value = self.window.with(current.0, current.1).beta();
+      self.current_value
+   }
+}
+
+/// A monotonic deque over a window - the front is always the extreme of the window
+#[derive(Clone, Debug)]
+struct MonotonicWindow {
+   period: u64,
+
+   /// The number of values committed so far - used as the index of the next value
+   count: u64,
+
+   /// The candidates for the extreme, with their index
+   values: VecDeque<(u64, Precision)>,
+
+   /// Whether the first value is kept in preference to the second
+   keep: fn(Precision, Precision) -> bool
+}
+impl MonotonicWindow {
+   fn new(period: u16, keep: fn(Precision, Precision) -> bool) -> Self {
+      Self { period: u64::from(period.max(1)), count: 0, values: VecDeque::new(), keep }
+   }
+
+   /// Gets the extreme with the given value added, without changing the window
+   fn with(&self, value: Precision) -> Precision {
+      let oldest = (self.count + 1).saturating_sub(self.period);
+      self.values.iter()
+         .find(|(index, _)| *index >= oldest)
+         .map_or(value, |(_, extreme)| if (self.keep)(*extreme, value) { *extreme } else { value })
+   }
+
+   fn push(&mut self, value: Precision) -> Precision {
+      while let Some((_, last)) = self.values.back() {
+         if (self.keep)(*last, value) { break }
+         self.values.pop_back();
+      }
+      self.values.push_back((self.count, value));
+      self.count += 1;
+
+      let oldest = self.count.saturating_sub(self.period);
+      while self.values.front().is_some_and(|(index, _)| *index < oldest) { self.values.pop_front(); }
+
+      self.values.front().map_or(value, |(_, extreme)| *extreme)
+   }
+}
+
+/// Rolling minimum over a fixed number of periods - using a monotonic deque
+#[derive(Clone, Debug)]
+pub struct RollingMin {
+   window: MonotonicWindow,
+   current_value: Precision
+}
+impl RollingMin {
+   /// Creates a new rolling minimum for a given number of time periods
+   pub fn new(period_count: u16) -> Self {
+      Self { window: MonotonicWindow::new(period_count, |kept, value| kept < value), current_value: 0.0 }
+   }
+}
+impl TechnicalIndicator for RollingMin {
+   type Output = Precision;
+
+   fn current(self) -> Self::Output { self.current_value }
+}
+impl UpdatableIndicator<f64> for RollingMin {
+   type Output = Precision;
+
+   fn commit(&mut self, current: &f64) -> Self::Output {
+      self.current_value = self.window.push(*current);
+      self.current_value
+   }
+
+   fn update(&mut self, current: &f64) -> Self::Output {
+      self.current_value = self.window.with(*current);
+      self.current_value
+   }
+}
+
+/// Rolling maximum over a fixed number of periods - using a monotonic deque
+#[derive(Clone, Debug)]
+pub struct RollingMax {
+   window: MonotonicWindow,
+   current_value: Precision
+}
+impl RollingMax {
+   /// Creates a new rolling maximum for a given number of time periods
+   pub fn new(period_count: u16) -> Self {
+      Self { window: MonotonicWindow::new(period_count, |kept, value| kept > value), current_value: 0.0 }
+   }
+}
+impl TechnicalIndicator for RollingMax {
+   type Output = Precision;
+
+   fn current(self) -> Self::Output { self.current_value }
+}
+impl UpdatableIndicator<f64> for RollingMax {
+   type Output = Precision;
+
+   fn commit(&mut self, current: &f64) -> Self::Output {
+      self.current_value = self.window.push(*current);
+      self.current_value
+   }
+
+   fn update(&mut self, current: &f64) -> Self::Output {
+      self.current_value = self.window.with(*current);
+      self.current_value
+   }
+}
+
+/// A multiset of values in order that can be looked up by rank - a treap with the size of each
+/// subtree, so inserts, removals and lookups are O(log n).  Values are ordered with `total_cmp`.
+#[derive(Clone, Debug, Default)]
+struct OrderedValues {
+   nodes: Vec<TreapNode>,
+
+   /// The indices of removed nodes, reused by later inserts
+   free: Vec<usize>,
+   root: Option<usize>,
+
+   /// The number of values ever inserted, mixed into the priority of each new node
+   inserted: u64
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TreapNode {
+   value: Precision,
+   priority: u64,
+   size: usize,
+   left: Option<usize>,
+   right: Option<usize>
+}
+
+impl OrderedValues {
+   fn len(&self) -> usize { self.size(self.root) }
+
+   fn insert(&mut self, value: Precision) {
+      self.inserted += 1;
+      let node = TreapNode { value, priority: mix(self.inserted), size: 1, left: None, right: None };
+      let index = match self.free.pop() {
+         Some(index) => { self.nodes[index] = node; index },
+         None => { self.nodes.push(node); self.nodes.len() - 1 }
+      };
+
+      let (below, rest) = self.split_below(self.root, value);
+      let below = self.merge(below, Some(index));
+      self.root = self.merge(below, rest);
+   }
+
+   /// Removes one of the values equal to a value, if there is one
+   fn remove(&mut self, value: Precision) {
+      let (below, rest) = self.split_below(self.root, value);
+      let (first, rest) = self.split_first(rest);
+      let rest = match first {
+         Some(first) if self.nodes[first].value.total_cmp(&value) == Ordering::Equal => { self.free.push(first); rest },
+         _ => self.merge(first, rest)
+      };
+      self.root = self.merge(below, rest);
+   }
+
+   /// The number of values below a value
+   fn rank(&self, value: Precision) -> usize {
+      let (mut node, mut rank) = (self.root, 0);
+      while let Some(index) = node {
+         let TreapNode { value: existing, left, right, .. } = self.nodes[index];
+         if existing.total_cmp(&value) == Ordering::Less {
+            rank += self.size(left) + 1;
+            node = right;
+         } else {
+            node = left;
+         }
+      }
+      rank
+   }
+
+   /// The value with a rank from zero, which must be less than the number of values
+   fn select(&self, mut rank: usize) -> Precision {
+      let mut node = self.root;
+      while let Some(index) = node {
+         let TreapNode { value, left, right, .. } = self.nodes[index];
+         let below = self.size(left);
+         match rank.cmp(&below) {
+            Ordering::Less => node = left,
+            Ordering::Equal => return value,
+            Ordering::Greater => { rank -= below + 1; node = right }
+         }
+      }
+      Precision::NAN
+   }
+
+   fn size(&self, node: Option<usize>) -> usize { node.map_or(0, |index| self.nodes[index].size) }
+
+   fn resize(&mut self, index: usize) {
+      let TreapNode { left, right, .. } = self.nodes[index];
+      self.nodes[index].size = 1 + self.size(left) + self.size(right);
+   }
+
+   /// Splits a tree into the values below a value and the rest
+   fn split_below(&mut self, node: Option<usize>, value: Precision) -> (Option<usize>, Option<usize>) {
+      let index = match node {
+         Some(index) => index,
+         None => return (None, None)
+      };
+
+      if self.nodes[index].value.total_cmp(&value) == Ordering::Less {
+         let (below, rest) = self.split_below(self.nodes[index].right, value);
+         self.nodes[index].right = below;
+         self.resize(index);
+         (Some(index), rest)
+      } else {
+         let (below, rest) = self.split_below(self.nodes[index].left, value);
+         self.nodes[index].left = rest;
+         self.resize(index);
+         (below, Some(index))
+      }
+   }
+
+   /// Splits the smallest value from the rest of a tree
+   fn split_first(&mut self, node: Option<usize>) -> (Option<usize>, Option<usize>) {
+      let index = match node {
+         Some(index) => index,
+         None => return (None, None)
+      };
+
+      match self.nodes[index].left {
+         None => {
+            let rest = self.nodes[index].right.take();
+            self.resize(index);
+            (Some(index), rest)
+         },
+         Some(left) => {
+            let (first, rest) = self.split_first(Some(left));
+            self.nodes[index].left = rest;
+            self.resize(index);
+            (first, Some(index))
+         }
+      }
+   }
+
+   /// Joins two trees where every value of the first is no greater than those of the second
+   fn merge(&mut self, first: Option<usize>, second: Option<usize>) -> Option<usize> {
+      match (first, second) {
+         (None, tree) | (tree, None) => tree,
+         (Some(first), Some(second)) => {
+            if self.nodes[first].priority > self.nodes[second].priority {
+               let right = self.merge(self.nodes[first].right, Some(second));
+               self.nodes[first].right = right;
+               self.resize(first);
+               Some(first)
+            } else {
+               let left = self.merge(Some(first), self.nodes[second].left);
+               self.nodes[second].left = left;
+               self.resize(second);
+               Some(second)
+            }
+         }
+      }
+   }
+}
+
+/// Scrambles a counter into a priority - the SplitMix64 finalizer
+fn mix(counter: u64) -> u64 {
+   let mut value = counter.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+   value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+   value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+   value ^ (value >> 31)
+}
+
+/// Rolling quantile over a fixed number of periods.
+///
+/// The window is also kept in order in a balanced tree, so committing a value and finding the
+/// quantile are O(log n) in the number of periods.  Quantiles are linearly interpolated between
+/// values.
+#[derive(Clone, Debug)]
+pub struct RollingQuantile {
+   period: usize,
+   quantile: Precision,
+   values: VecDeque<Precision>,
+   sorted: OrderedValues,
+   current_value: Precision
+}
+impl RollingQuantile {
+   /// Creates a new rolling quantile (between 0 and 1) for a given number of time periods
+   pub fn new(period_count: u16, quantile: Precision) -> Self {
+      Self {
+         period: usize::from(period_count.max(1)),
+         quantile: quantile.clamp(0.0, 1.0),
+         values: VecDeque::new(),
+         sorted: OrderedValues::default(),
+         current_value: 0.0
+      }
+   }
+
+   /// Creates a new rolling median for a given number of time periods
+   pub fn median(period_count: u16) -> Self { Self::new(period_count, 0.5) }
+
+   /// Interpolates the quantile from a count of sorted values and a way of getting a value by rank
+   fn interpolate(&self, count: usize, at: impl Fn(usize) -> Precision) -> Precision {
+      let target = self.quantile * (count - 1) as Precision;
+      let lower = target.floor() as usize;
+      let fraction = target - lower as Precision;
+
+      let low = at(lower);
+      if fraction == 0.0 { low } else { low + (at(lower + 1) - low) * fraction }
+   }
+
+   /// Gets the quantile with `added` included and `removed` excluded, without changing the window
+   fn calculate(&self, added: Precision, removed: Option<Precision>) -> Precision {
+      let removed_at = removed.map(|value| self.sorted.rank(value));
+      let inserted_at = self.sorted.rank(added);
+      let added_at = inserted_at - removed_at.map_or(0, |removed_at| usize::from(removed_at < inserted_at));
+
+      // the sorted values with the removed value taken out
+      let remaining = |rank: usize| match removed_at {
+         Some(removed_at) if rank >= removed_at => self.sorted.select(rank + 1),
+         _ => self.sorted.select(rank)
+      };
+
+      let count = self.sorted.len() + 1 - usize::from(removed.is_some());
+      self.interpolate(count, |rank| match rank.cmp(&added_at) {
+         Ordering::Equal => added,
+         Ordering::Less => remaining(rank),
+         Ordering::Greater => remaining(rank - 1)
+      })
+   }
+}
+impl TechnicalIndicator for RollingQuantile {
+   type Output = Precision;
+
+   fn current(self) -> Self::Output { self.current_value }
+}
+impl UpdatableIndicator<f64> for RollingQuantile {
+   type Output = Precision;
+
+   fn commit(&mut self, current: &f64) -> Self::Output {
+      if self.values.len() == self.period {
+         if let Some(removed) = self.values.pop_front() { self.sorted.remove(removed) }
+      }
+
+      self.sorted.insert(*current);
+      self.values.push_back(*current);
+
+      self.current_value = self.interpolate(self.sorted.len(), |rank| self.sorted.select(rank));
+      self.current_value
+   }
+
+   fn update(&mut self, current: &f64) -> Self::Output {
+      let removed = if self.values.len() == self.period { self.values.front().copied() } else { None };
+      self.current_value = self.calculate(*current, removed);
+      self.current_value
+   }
+}
+
+#[cfg(test)]
+mod test {
+   use super::*;
+
+   fn assert_close(expected: f64, actual: f64) {
+      assert!((expected - actual).abs() < 1e-10, "expected {} but was {}", expected, actual);
+   }
+
+   #[test]
+   fn mean_and_variance() {
+      // GIVEN - rolling statistics over 3 periods
+      let mut mean = RollingMean::new(3);
+      let mut variance = RollingVariance::new(3);
+
+      // WHEN - we commit more values than the window holds
+      for value in [ 1.0, 2.0, 3.0, 4.0, 8.0 ].iter() {
+         mean.commit(value);
+         variance.commit(value);
+      }
+
+      // THEN - only the last 3 values are used
+      assert_close(5.0, mean.clone().current());
+      assert_close(7.0, variance.clone().current());
+      assert_close(7.0_f64.sqrt(), variance.stdev());
+   }
+
+   #[test]
+   fn update_does_not_commit() {
+      // GIVEN - a rolling mean with committed values
+      let mut mean = RollingMean::new(2);
+      mean.commit(&2.0);
+      mean.commit(&4.0);
+
+      // WHEN - we update a few times
+      mean.update(&100.0);
+      let result = mean.update(&6.0);
+
+      // THEN - only the last update is used, on top of the committed values
+      assert_close(5.0, result);
+      assert_close(7.0, mean.commit(&10.0));
+   }
+
+   #[test]
+   fn z_score() {
+      // GIVEN - a rolling z-score
+      let mut z_score = RollingZScore::new(4);
+      for value in [ 1.0, 2.0, 3.0 ].iter() { z_score.commit(value); }
+
+      // WHEN - we update with a new value
+      let result = z_score.update(&4.0);
+
+      // THEN - the value is compared to the window including it
+      assert_close(1.5 / (5.0_f64 / 3.0).sqrt(), result);
+   }
+
+   #[test]
+   fn min_and_max() {
+      // GIVEN - rolling extremes over 3 periods
+      let mut min = RollingMin::new(3);
+      let mut max = RollingMax::new(3);
+      let values = [ 5.0, 1.0, 3.0, 4.0, 2.0, 6.0 ];
+      let expected_min = [ 5.0, 1.0, 1.0, 1.0, 2.0, 2.0 ];
+      let expected_max = [ 5.0, 5.0, 5.0, 4.0, 4.0, 6.0 ];
+
+      // WHEN - we commit values
+      // THEN - the extremes of the window are kept
+      for (index, value) in values.iter().enumerate() {
+         assert_eq!(expected_min[index], min.update(value));
+         assert_eq!(expected_max[index], max.update(value));
+         assert_eq!(expected_min[index], min.commit(value));
+         assert_eq!(expected_max[index], max.commit(value));
+      }
+   }
+
+   #[test]
+   fn quantiles() {
+      // GIVEN - a rolling median and upper quartile over 4 periods
+      let mut median = RollingQuantile::median(4);
+      let mut quartile = RollingQuantile::new(4, 0.75);
+      let values = [ 5.0, 1.0, 3.0, 4.0, 2.0, 9.0, 0.0 ];
+
+      // WHEN - we update and commit values
+      // THEN - updates match commits, and commits match a sorted window
+      for (index, value) in values.iter().enumerate() {
+         let updated = (median.update(value), quartile.update(value));
+         let committed = (median.commit(value), quartile.commit(value));
+         assert_eq!(committed, updated);
+
+         let mut window: Vec<f64> = values[index.saturating_sub(3)..=index].to_vec();
+         window.sort_by(|a, b| a.partial_cmp(b).unwrap());
+         let middle = (window.len() - 1) as f64 / 2.0;
+         assert_close((window[middle.floor() as usize] + window[middle.ceil() as usize]) / 2.0, committed.0);
+      }
+   }
+
+   #[test]
+   fn quantiles_over_long_windows() {
+      // GIVEN - a rolling 10th percentile over 50 periods and values with many repeats
+      let mut quantile = RollingQuantile::new(50, 0.1);
+      let values: Vec<f64> = (0..1_000_u64).map(|index| (mix(index) % 40) as f64).collect();
+
+      // WHEN - we update and commit values well past the window
+      // THEN - each matches the quantile of a sorted copy of the window
+      for (index, value) in values.iter().enumerate() {
+         let updated = quantile.update(value);
+         let committed = quantile.commit(value);
+
+         let mut window: Vec<f64> = values[index.saturating_sub(49)..=index].to_vec();
+         window.sort_by(|a, b| a.partial_cmp(b).unwrap());
+         let target = 0.1 * (window.len() - 1) as f64;
+         let (lower, upper) = (window[target.floor() as usize], window[target.ceil() as usize]);
+         assert_close(lower + (upper - lower) * target.fract(), committed);
+         assert_eq!(committed, updated);
+      }
+      assert_eq!(50, quantile.sorted.len());
+   }
+
+   #[test]
+   fn covariance_and_correlation() {
+      // GIVEN - rolling covariance and correlation over 3 periods
+      let mut covariance = RollingCovariance::new(3);
+      let mut correlation = RollingCorrelation::new(3);
+
+      // WHEN - we commit pairs that move together, then opposite
+      for pair in [ (10.0, -1.0), (1.0, 2.0), (2.0, 4.0), (3.0, 6.0) ].iter() {
+         covariance.commit(pair);
+         correlation.commit(pair);
+      }
+      let together = correlation.clone().current();
+      let opposite = correlation.update(&(4.0, 0.0));
+
+      // THEN - the statistics only use the window
+      assert_close(2.0, covariance.current());
+      assert_close(1.0, together);
+      assert_close(-4.0 / (2.0_f64 * 56.0 / 3.0).sqrt(), opposite);
+   }
+
+   #[test]
+   fn correlation_without_variation() {
+      // GIVEN - a rolling correlation over 3 periods
+      let mut correlation = RollingCorrelation::new(3);
+
+      // WHEN - we have a single pair, then a constant series
+      let single = correlation.commit(&(1.0, 2.0));
+      for value in [ 2.0, 3.0 ].iter() { correlation.commit(&(*value, 2.0)); }
+
+      // THEN - there is no correlation rather than NaN
+      assert_eq!(0.0, single);
+      assert_eq!(0.0, correlation.current());
+   }
+
+   #[test]
+   fn beta() {
+      // GIVEN - a rolling beta over 3 periods
+      let mut beta = RollingBeta::new(3);
+
+      // WHEN - we commit returns that move 2x the benchmark after an unrelated one
+      beta.commit(&(0.5, -0.1));
+      for benchmark in [ 0.01, -0.02, 0.03 ].iter() { beta.commit(&(2.0 * benchmark + 0.001, *benchmark)); }
+
+      // THEN - the beta only uses the window
+      assert_close(2.0, beta.current());
+   }
+}
\ No newline at end of file
@@ -0,0 +1,272 @@
+//! Instrument metadata and the rules for valid prices and quantities.
+//!
+//! Prices snap to the tick size of an instrument, which can change with the price - e.g. US equities
+//! trade in hundredths of a cent below a dollar.  Quantities snap down to whole lots, and values and
+//! P&L are scaled by the contract multiplier.
+
+use crate::{ Bar, Quote };
+use crate::orders::{ Order, OrderType, Position, Side };
+
+/// The kind of market an instrument trades in
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum AssetClass {
+   Equity,
+   Option,
+   Future,
+   Forex,
+   Crypto,
+   Bond,
+   Index
+}
+
+/// How a price is moved onto a tick
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rounding {
+   Nearest,
+   Down,
+   Up
+}
+
+/// The minimum price increment of an instrument
+#[derive(Clone, Debug, PartialEq)]
+pub enum TickSize {
+   /// The same tick at every price
+   Fixed(f64),
+
+   /// `(price, tick)` pairs sorted by price - each tick applies at and above its price, with the
+   /// first tick also used below the first price
+   Tiered(Vec<(f64, f64)>)
+}
+impl TickSize {
+   /// US equities under Reg NMS rule 612 - $0.0001 below a dollar and $0.01 from a dollar
+   pub fn us_equity() -> Self { Self::Tiered(vec![ (0.0, 0.0001), (1.0, 0.01) ]) }
+
+   /// The tick at a price
+   pub fn at(&self, price: f64) -> f64 {
+      match self {
+         Self::Fixed(tick) => *tick,
+         Self::Tiered(tiers) => tiers.iter()
+            .take_while(|(from, _)| *from <= price)
+            .last()
+            .or_else(|| tiers.first())
+            .map_or(0.0, |(_, tick)| *tick)
+      }
+   }
+}
+
+/// A tradable instrument
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instrument {
+   /// The symbol of the instrument
+   pub symbol: String,
+
+   /// The kind of market the instrument trades in
+   pub asset_class: AssetClass,
+
+   /// The currency prices are quoted in
+   pub currency: String,
+
+   /// The code of the exchange listing the instrument
+   pub exchange: String,
+
+   /// The minimum price increment
+   pub tick_size: TickSize,
+
+   /// The minimum quantity increment
+   pub lot_size: f64,
+
+   /// The value of a unit of price for a single contract - e.g. 100 for US equity options
+   pub multiplier: f64,
+
+   /// The number of decimal places prices are shown to
+   pub precision: u32
+}
+impl Instrument {
+   /// Creates an instrument with a tick of a cent, a lot size and multiplier of one and a precision
+   /// of two decimal places
+   pub fn new(symbol: &str, asset_class: AssetClass, currency: &str) -> Self {
+      Self {
+         symbol: symbol.to_string(),
+         asset_class,
+         currency: currency.to_string(),
+         exchange: String::new(),
+         tick_size: TickSize::Fixed(0.01),
+         lot_size: 1.0,
+         multiplier: 1.0,
+         precision: 2
+      }
+   }
+
+   /// The tick at a price
+   pub fn tick(&self, price: f64) -> f64 { self.tick_size.at(price) }
+
+   /// Moves a price onto a tick
+   pub fn round_price(&self, price: f64, rounding: Rounding) -> f64 {
+      let tick = self.tick(price);
+      if tick <= 0.0 || !price.is_finite() { return price }
+
+      // allow for prices that are a tick apart from floating point error
+      let ticks = price / tick;
+      let ticks = match rounding {
+         Rounding::Nearest => ticks.round(),
+         Rounding::Down => (ticks + 1e-9).floor(),
+         Rounding::Up => (ticks - 1e-9).ceil()
+      };
+      self.round_precision(ticks * tick, tick)
+   }
+
+   /// Whether a price is on a tick
+   pub fn is_valid_price(&self, price: f64) -> bool {
+      (self.round_price(price, Rounding::Nearest) - price).abs() < self.tick(price) * 1e-6
+   }
+
+   /// Moves a quantity down to a whole number of lots, keeping its sign
+   pub fn round_quantity(&self, quantity: f64) -> f64 {
+      if self.lot_size <= 0.0 { return quantity }
+      ((quantity.abs() / self.lot_size + 1e-9).floor() * self.lot_size).copysign(quantity)
+   }
+
+   /// Whether a quantity is a whole number of lots
+   pub fn is_valid_quantity(&self, quantity: f64) -> bool {
+      (self.round_quantity(quantity) - quantity).abs() < self.lot_size * 1e-6
+   }
+
+   /// The value of a quantity at a price
+   pub fn notional(&self, quantity: f64, price: f64) -> f64 { quantity * price * self.multiplier }
+
+   /// The profit or loss of a quantity bought at one price and sold at another - negative quantities are short
+   pub fn pnl(&self, quantity: f64, entry: f64, exit: f64) -> f64 { quantity * (exit - entry) * self.multiplier }
+
+   /// The value of a position at a price
+   pub fn market_value(&self, position: &Position, price: f64) -> f64 { position.market_value(price) * self.multiplier }
+
+   /// The unrealized profit or loss of a position at a price
+   pub fn unrealized_pnl(&self, position: &Position, price: f64) -> f64 { position.unrealized_pnl(price) * self.multiplier }
+
+   /// The realized and unrealized profit or loss of a position at a price, less commissions
+   pub fn total_pnl(&self, position: &Position, price: f64) -> f64 {
+      (position.realized_pnl + position.unrealized_pnl(price)) * self.multiplier - position.commission
+   }
+
+   /// Moves the prices of a bar to the nearest ticks
+   pub fn round_bar(&self, bar: &Bar) -> Bar {
+      let round = |price| self.round_price(price, Rounding::Nearest);
+      Bar { open: round(bar.open), high: round(bar.high), low: round(bar.low), close: round(bar.close), ..*bar }
+   }
+
+   /// Moves the price of a quote to the nearest tick
+   pub fn round_quote(&self, quote: &Quote) -> Quote {
+      Quote { price: self.round_price(quote.price, Rounding::Nearest), ..quote.clone() }
+   }
+
+   /// Moves the prices of an order onto ticks and its quantity down to whole lots.
+   ///
+   /// Prices are rounded to be less aggressive - limits away from the market and stops further from
+   /// triggering - so the order never trades at a worse price than was asked for.
+   pub fn round_order(&self, order: &Order) -> Order {
+      let (limit, stop) = match order.side {
+         Side::Buy => (Rounding::Down, Rounding::Up),
+         Side::Sell => (Rounding::Up, Rounding::Down)
+      };
+
+      let order_type = match order.order_type {
+         OrderType::Limit(price) => OrderType::Limit(self.round_price(price, limit)),
+         OrderType::Stop(price) => OrderType::Stop(self.round_price(price, stop)),
+         OrderType::StopLimit { stop: trigger, limit: price } => OrderType::StopLimit {
+            stop: self.round_price(trigger, stop),
+            limit: self.round_price(price, limit)
+         },
+         other => other
+      };
+      Order { order_type, quantity: self.round_quantity(order.quantity), ..order.clone() }
+   }
+
+   /// Rounds to the precision of the instrument, or the decimal places of the tick if finer, to clear
+   /// floating point error
+   fn round_precision(&self, price: f64, tick: f64) -> f64 {
+      let scale = 10f64.powi(self.precision.max(decimal_places(tick)) as i32);
+      (price * scale).round() / scale
+   }
+}
+
+/// The decimal places needed to show a tick - at most 15 for ticks that don't end
+fn decimal_places(tick: f64) -> u32 {
+   (0..15).find(|places| {
+      let scaled = tick * 10f64.powi(*places as i32);
+      (scaled - scaled.round()).abs() < 1e-6
+   }).unwrap_or(15)
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn equity() -> Instrument {
+      Instrument { exchange: "XNAS".to_string(), tick_size: TickSize::us_equity(), precision: 4, ..Instrument::new("ABC", AssetClass::Equity, "USD") }
+   }
+
+   #[test]
+   fn verify_ticks() {
+      let equity = equity();
+      assert_eq!(0.0001, equity.tick(0.5));
+      assert_eq!(0.01, equity.tick(1.0));
+      assert_eq!(0.5123, equity.round_price(0.51234, Rounding::Nearest));
+      assert_eq!(10.13, equity.round_price(10.125_1, Rounding::Nearest));
+      assert_eq!(10.12, equity.round_price(10.129, Rounding::Down));
+      assert_eq!(10.13, equity.round_price(10.121, Rounding::Up));
+      assert_eq!(10.12, equity.round_price(10.12, Rounding::Up));
+      assert!(equity.is_valid_price(0.5123) && equity.is_valid_price(10.12));
+      assert!(!equity.is_valid_price(10.123));
+
+      let future = Instrument { tick_size: TickSize::Fixed(0.25), ..Instrument::new("ESZ6", AssetClass::Future, "USD") };
+      assert_eq!(5000.25, future.round_price(5000.3, Rounding::Nearest));
+      assert_eq!(5000.5, future.round_price(5000.3, Rounding::Up));
+   }
+
+   #[test]
+   fn verify_ticks_finer_than_precision() {
+      let bond = Instrument { tick_size: TickSize::Fixed(0.03125), ..Instrument::new("ZNZ6", AssetClass::Future, "USD") };
+      assert_eq!(110.03125, bond.round_price(110.03125, Rounding::Nearest));
+      assert_eq!(110.0625, bond.round_price(110.05, Rounding::Nearest));
+      assert!(bond.is_valid_price(110.03125) && !bond.is_valid_price(110.03));
+   }
+
+   #[test]
+   fn verify_quantities() {
+      let lots = Instrument { lot_size: 100.0, ..equity() };
+      assert_eq!(200.0, lots.round_quantity(250.0));
+      assert_eq!(-200.0, lots.round_quantity(-299.0));
+      assert!(lots.is_valid_quantity(300.0) && !lots.is_valid_quantity(301.0));
+   }
+
+   #[test]
+   fn verify_orders() {
+      let equity = Instrument { lot_size: 10.0, ..equity() };
+
+      let buy = equity.round_order(&Order::stop_limit(Side::Buy, 25.0, 10.121, 10.129));
+      assert_eq!(OrderType::StopLimit { stop: 10.13, limit: 10.12 }, buy.order_type);
+      assert_eq!(20.0, buy.quantity);
+
+      let sell = equity.round_order(&Order::limit(Side::Sell, 10.0, 0.51234));
+      assert_eq!(OrderType::Limit(0.5124), sell.order_type);
+      assert_eq!(OrderType::Stop(10.12), equity.round_order(&Order::stop(Side::Sell, 10.0, 10.129)).order_type);
+
+      let quote = Quote { symbol: "ABC".to_string(), timestamp: 0, session: crate::TradingSession::Regular, price: 10.1234, volume: 5 };
+      assert_eq!(10.12, equity.round_quote(&quote).price);
+      let bar = equity.round_bar(&Bar { timestamp: 0, open: 10.004, high: 10.256, low: 9.994, close: 10.1, volume: None });
+      assert_eq!((10.0, 10.26, 9.99, 10.1), (bar.open, bar.high, bar.low, bar.close));
+   }
+
+   #[test]
+   fn verify_multiplier() {
+      let option = Instrument { multiplier: 100.0, ..Instrument::new("ABC   261218C00100000", AssetClass::Option, "USD") };
+      assert_eq!(25_000.0, option.notional(10.0, 25.0));
+      assert_eq!(-1_500.0, option.pnl(-5.0, 2.0, 5.0));
+
+      let position = Position { quantity: 2.0, average_price: 3.0, realized_pnl: 1.0, commission: 4.0, ..Position::default() };
+      assert_eq!(700.0, option.market_value(&position, 3.5));
+      assert_eq!(100.0, option.unrealized_pnl(&position, 3.5));
+      assert_eq!(196.0, option.total_pnl(&position, 3.5));
+   }
+}
\ No newline at end of file
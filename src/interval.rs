@@ -11,9 +11,20 @@ use std::fmt;
 pub enum Interval { _1m, _2m, _5m, _15m, _30m, _60m, _90m, _1d, _5d, _1mo, _3mo, _6mo, _1y, _2y, _5y, _10y, _ytd, _max }
 impl Interval {
    pub fn is_intraday(&self) -> bool {
+      matches!(self, Self::_1m | Self::_2m | Self::_5m | Self::_15m | Self::_30m | Self::_60m | Self::_90m)
+   }
+
+   /// The number of minutes in an intraday interval - `None` for intervals of a day or longer
+   pub fn minutes(&self) -> Option<u32> {
       match self {
-         Self::_1m | Self::_2m | Self::_5m | Self::_15m | Self::_30m | Self::_60m | Self::_90m => true,
-         _ => false
+         Self::_1m => Some(1),
+         Self::_2m => Some(2),
+         Self::_5m => Some(5),
+         Self::_15m => Some(15),
+         Self::_30m => Some(30),
+         Self::_60m => Some(60),
+         Self::_90m => Some(90),
+         _ => None
       }
    }
 }
@@ -34,6 +45,7 @@ mod tests {
    fn test_interval(interval: Interval, value: &str, is_intraday: bool) {
       assert_eq!(format!("{}", interval), value);
       assert_eq!(interval.is_intraday(), is_intraday);
+      assert_eq!(interval.minutes().is_some(), is_intraday);
    }
 
    #[test] fn interval_1m() { test_interval(Interval::_1m, "1m", true); }
@@ -54,4 +66,11 @@ mod tests {
    #[test] fn interval_10y() { test_interval(Interval::_10y, "10y", false); }
    #[test] fn interval_ytd() { test_interval(Interval::_ytd, "ytd", false); }
    #[test] fn interval_max() { test_interval(Interval::_max, "max", false); }
+
+   #[test]
+   fn interval_minutes() {
+      assert_eq!(Some(15), Interval::_15m.minutes());
+      assert_eq!(Some(90), Interval::_90m.minutes());
+      assert_eq!(None, Interval::_1d.minutes());
+   }
 }
\ No newline at end of file
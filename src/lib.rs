@@ -7,9 +7,17 @@
 mod traits;
 pub use traits::{ Timestamped };
 
+mod math;
+
 #[cfg(feature = "indicators")]
 pub mod indicators;
 
+#[cfg(feature = "calendar")]
+pub mod calendar;
+
+#[cfg(feature = "calendar")]
+pub mod gaps;
+
 mod interval;
 pub use interval::Interval;
 
@@ -17,4 +25,43 @@ mod bar;
 pub use bar::Bar;
 
 mod quote;
-pub use quote::{ Quote, TradingSession };
\ No newline at end of file
+pub use quote::{ Quote, TradingSession };
+
+mod tick;
+pub use tick::{ BookQuote, LeeReady, Tick };
+
+pub mod adjustments;
+
+pub mod returns;
+
+pub mod metrics;
+
+pub mod statistics;
+
+pub mod validation;
+
+pub mod orders;
+
+pub mod backtest;
+
+pub mod portfolio;
+
+pub mod book;
+
+pub mod options;
+
+pub mod symbols;
+
+pub mod instruments;
+
+pub mod currency;
+
+pub mod cash_flows;
+
+pub mod patterns;
+
+#[cfg(feature = "bonds")]
+pub mod bonds;
+
+#[cfg(feature = "decimal")]
+pub mod decimal;
\ No newline at end of file
@@ -0,0 +1,305 @@
+//! Shared numerical helpers - statistics, the normal distribution, root finding and interpolation.
+
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+
+/// The arithmetic mean - 0 for an empty series
+pub fn mean(values: &[f64]) -> f64 {
+   if values.is_empty() { 0.0 } else { values.iter().sum::<f64>() / values.len() as f64 }
+}
+
+/// The sample variance (n - 1) - 0 when there are fewer than two values
+pub fn variance(values: &[f64]) -> f64 {
+   if values.len() < 2 { return 0.0 }
+
+   let center = mean(values);
+   values.iter().map(|value| (value - center).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+}
+
+/// The sample standard deviation (n - 1)
+pub fn stdev(values: &[f64]) -> f64 { variance(values).sqrt() }
+
+/// Sorts a copy of the values, ordering NaNs as equal
+pub fn sorted(values: &[f64]) -> Vec<f64> {
+   let mut sorted = values.to_vec();
+   sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+   sorted
+}
+
+/// The quantile (0 to 1) of values that are already sorted, linearly interpolating between values
+pub fn quantile_sorted(sorted: &[f64], quantile: f64) -> f64 {
+   if sorted.is_empty() { return f64::NAN }
+
+   let position = quantile.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+   let lower = position.floor() as usize;
+   let upper = position.ceil() as usize;
+   sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// The median of the values
+pub fn median(values: &[f64]) -> f64 { quantile_sorted(&sorted(values), 0.5) }
+
+/// The standard normal probability density function
+pub fn norm_pdf(x: f64) -> f64 {
+   (-0.5 * x * x).exp() / (2.0 * PI).sqrt()
+}
+
+/// The standard normal cumulative distribution function - Hart's double precision algorithm
+pub fn norm_cdf(x: f64) -> f64 {
+   let xabs = x.abs();
+   let tail = if xabs > 37.0 {
+      0.0
+   } else {
+      let exponential = (-xabs * xabs / 2.0).exp();
+      if xabs < 7.071_067_811_865_47 {
+         let mut numerator = 3.526_249_659_989_11e-2 * xabs + 0.700_383_064_443_688;
+         numerator = numerator * xabs + 6.373_962_203_531_65;
+         numerator = numerator * xabs + 33.912_866_078_383;
+         numerator = numerator * xabs + 112.079_291_497_871;
+         numerator = numerator * xabs + 221.213_596_169_931;
+         numerator = numerator * xabs + 220.206_867_912_376;
+
+         let mut denominator = 8.838_834_764_831_84e-2 * xabs + 1.755_667_163_182_64;
+         denominator = denominator * xabs + 16.064_177_579_207;
+         denominator = denominator * xabs + 86.780_732_202_946_1;
+         denominator = denominator * xabs + 296.564_248_779_674;
+         denominator = denominator * xabs + 637.333_633_378_831;
+         denominator = denominator * xabs + 793.826_512_519_948;
+         denominator = denominator * xabs + 440.413_735_824_752;
+
+         exponential * numerator / denominator
+      } else {
+         let mut fraction = xabs + 0.65;
+         fraction = xabs + 4.0 / fraction;
+         fraction = xabs + 3.0 / fraction;
+         fraction = xabs + 2.0 / fraction;
+         fraction = xabs + 1.0 / fraction;
+         exponential / fraction / 2.506_628_274_631
+      }
+   };
+
+   if x > 0.0 { 1.0 - tail } else { tail }
+}
+
+/// The inverse of the standard normal cumulative distribution function.
+///
+/// Uses Acklam's rational approximation refined with a step of Halley's method.
+pub fn norm_inv(p: f64) -> f64 {
+   const A: [f64; 6] = [ -3.969_683_028_665_376e1, 2.209_460_984_245_205e2, -2.759_285_104_469_687e2, 1.383_577_518_672_69e2, -3.066_479_806_614_716e1, 2.506_628_277_459_239 ];
+   const B: [f64; 5] = [ -5.447_609_879_822_406e1, 1.615_858_368_580_409e2, -1.556_989_798_598_866e2, 6.680_131_188_771_972e1, -1.328_068_155_288_572e1 ];
+   const C: [f64; 6] = [ -7.784_894_002_430_293e-3, -3.223_964_580_411_365e-1, -2.400_758_277_161_838, -2.549_732_539_343_734, 4.374_664_141_464_968, 2.938_163_982_698_783 ];
+   const D: [f64; 4] = [ 7.784_695_709_041_462e-3, 3.224_671_290_700_398e-1, 2.445_134_137_142_996, 3.754_408_661_907_416 ];
+   const LOW: f64 = 0.02425;
+
+   if p <= 0.0 { return f64::NEG_INFINITY }
+   if p >= 1.0 { return f64::INFINITY }
+
+   let tail = |q: f64| {
+      (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5]) / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+   };
+
+   let x = if p < LOW {
+      tail((-2.0 * p.ln()).sqrt())
+   } else if p > 1.0 - LOW {
+      -tail((-2.0 * (1.0 - p).ln()).sqrt())
+   } else {
+      let q = p - 0.5;
+      let r = q * q;
+      (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+         / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+   };
+
+   let error = norm_cdf(x) - p;
+   let step = error * (2.0 * PI).sqrt() * (x * x / 2.0).exp();
+   x - step / (1.0 + x * step / 2.0)
+}
+
+
+/// Finds a root of a function between two points where it has opposite signs - Brent's method.
+///
+/// `None` if the function has the same sign at both points or it doesn't converge.
+pub fn brent(f: impl Fn(f64) -> f64, lower: f64, upper: f64, tolerance: f64, max_iterations: usize) -> Option<f64> {
+   let (mut a, mut b) = (lower, upper);
+   let (mut fa, mut fb) = (f(a), f(b));
+   if fa * fb > 0.0 { return None }
+
+   let (mut c, mut fc) = (b, fb);
+   let (mut step, mut previous) = (b - a, b - a);
+   for _ in 0..max_iterations {
+      if fb * fc > 0.0 {
+         c = a;
+         fc = fa;
+         step = b - a;
+         previous = step;
+      }
+      if fc.abs() < fb.abs() {
+         a = b;
+         b = c;
+         c = a;
+         fa = fb;
+         fb = fc;
+         fc = fa;
+      }
+
+      let accuracy = 2.0 * f64::EPSILON * b.abs() + tolerance / 2.0;
+      let middle = (c - b) / 2.0;
+      if middle.abs() <= accuracy || fb == 0.0 { return Some(b) }
+
+      if previous.abs() >= accuracy && fa.abs() > fb.abs() {
+         // inverse quadratic interpolation, or the secant method when there are only two points
+         let s = fb / fa;
+         let (mut p, mut q) = if a == c {
+            (2.0 * middle * s, 1.0 - s)
+         } else {
+            let (qa, r) = (fa / fc, fb / fc);
+            (s * (2.0 * middle * qa * (qa - r) - (b - a) * (r - 1.0)), (qa - 1.0) * (r - 1.0) * (s - 1.0))
+         };
+         if p > 0.0 { q = -q } else { p = -p }
+
+         if 2.0 * p < (3.0 * middle * q - (accuracy * q).abs()).min((previous * q).abs()) {
+            previous = step;
+            step = p / q;
+         } else {
+            step = middle;
+            previous = middle;
+         }
+      } else {
+         step = middle;
+         previous = middle;
+      }
+
+      a = b;
+      fa = fb;
+      b += if step.abs() > accuracy { step } else { accuracy.copysign(middle) };
+      fb = f(b);
+   }
+
+   None
+}
+
+/// A natural cubic spline through points sorted by x, flat beyond the first and last points
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubicSpline {
+   x: Vec<f64>,
+   y: Vec<f64>,
+
+   /// The second derivative at each point
+   second: Vec<f64>
+}
+impl CubicSpline {
+   /// Fits a spline through points with distinct x values, sorted by x
+   pub fn new(x: &[f64], y: &[f64]) -> Self {
+      let count = x.len().min(y.len());
+      let (x, y) = (x[..count].to_vec(), y[..count].to_vec());
+      let mut second = vec![ 0.0; count ];
+      if count < 3 { return Self { x, y, second } }
+
+      // the tridiagonal system for the second derivatives, solved with the Thomas algorithm
+      let mut upper = vec![ 0.0; count ];
+      for index in 1..count - 1 {
+         let sigma = (x[index] - x[index - 1]) / (x[index + 1] - x[index - 1]);
+         let pivot = sigma * second[index - 1] + 2.0;
+         second[index] = (sigma - 1.0) / pivot;
+         let slopes = (y[index + 1] - y[index]) / (x[index + 1] - x[index]) - (y[index] - y[index - 1]) / (x[index] - x[index - 1]);
+         upper[index] = (6.0 * slopes / (x[index + 1] - x[index - 1]) - sigma * upper[index - 1]) / pivot;
+      }
+
+      second[count - 1] = 0.0;
+      for index in (0..count - 1).rev() {
+         second[index] = second[index] * second[index + 1] + upper[index];
+      }
+
+      Self { x, y, second }
+   }
+
+   /// The value of the spline at a point
+   pub fn value(&self, at: f64) -> f64 {
+      match self.segment(at) {
+         Ok(index) => {
+            let (low, high) = (index, index + 1);
+            let width = self.x[high] - self.x[low];
+            let a = (self.x[high] - at) / width;
+            let b = (at - self.x[low]) / width;
+            a * self.y[low] + b * self.y[high]
+               + ((a.powi(3) - a) * self.second[low] + (b.powi(3) - b) * self.second[high]) * width * width / 6.0
+         },
+         Err(value) => value
+      }
+   }
+
+   /// The slope of the spline at a point
+   pub fn derivative(&self, at: f64) -> f64 {
+      match self.segment(at) {
+         Ok(index) => {
+            let (low, high) = (index, index + 1);
+            let width = self.x[high] - self.x[low];
+            let a = (self.x[high] - at) / width;
+            let b = (at - self.x[low]) / width;
+            (self.y[high] - self.y[low]) / width
+               - (3.0 * a * a - 1.0) * width * self.second[low] / 6.0
+               + (3.0 * b * b - 1.0) * width * self.second[high] / 6.0
+         },
+         Err(_) => 0.0
+      }
+   }
+
+   /// The index of the point starting the segment containing a point, or the flat value outside of the points
+   fn segment(&self, at: f64) -> Result<usize, f64> {
+      let count = self.x.len();
+      if count == 0 { return Err(f64::NAN) }
+      if at <= self.x[0] { return Err(self.y[0]) }
+      if at >= self.x[count - 1] { return Err(self.y[count - 1]) }
+
+      Ok(self.x.partition_point(|x| *x <= at) - 1)
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+      assert!((expected - actual).abs() < tolerance, "expected {} but was {}", expected, actual);
+   }
+
+   #[test]
+   fn verify_statistics() {
+      let values = [ 2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0 ];
+      assert_eq!(5.0, mean(&values));
+      assert_close(32.0 / 7.0, variance(&values), 1e-12);
+      assert_eq!(4.5, median(&values));
+      assert_eq!(2.0, quantile_sorted(&sorted(&values), 0.0));
+      assert_eq!(9.0, quantile_sorted(&sorted(&values), 1.0));
+   }
+
+   #[test]
+   fn verify_normal_distribution() {
+      assert_close(0.5, norm_cdf(0.0), 1e-15);
+      assert_close(0.975_002_104_851_780, norm_cdf(1.96), 1e-14);
+      assert_close(0.022_750_131_948_179, norm_cdf(-2.0), 1e-14);
+      assert_close(0.398_942_280_401_433, norm_pdf(0.0), 1e-14);
+      assert_close(1.644_853_626_951_472, norm_inv(0.95), 1e-12);
+      assert_close(-2.326_347_874_040_841, norm_inv(0.01), 1e-12);
+   }
+
+   #[test]
+   fn verify_brent() {
+      let root = brent(|x| x * x - 2.0, 0.0, 2.0, 1e-12, 100).unwrap();
+      assert_close(2.0_f64.sqrt(), root, 1e-10);
+      assert_eq!(None, brent(|x| x * x + 1.0, -1.0, 1.0, 1e-12, 100));
+   }
+
+   #[test]
+   fn verify_cubic_spline() {
+      let x = [ 0.0, 1.0, 2.0, 3.0 ];
+      let spline = CubicSpline::new(&x, &[ 0.0, 1.0, 0.0, 1.0 ]);
+      assert_close(1.0, spline.value(1.0), 1e-12);
+      assert_close(0.0, spline.value(-1.0), 1e-12);
+      assert_close(1.0, spline.value(4.0), 1e-12);
+
+      // a straight line is fitted exactly
+      let line = CubicSpline::new(&x, &[ 1.0, 3.0, 5.0, 7.0 ]);
+      assert_close(4.0, line.value(1.5), 1e-12);
+      assert_close(2.0, line.derivative(2.5), 1e-12);
+   }
+}
\ No newline at end of file
@@ -0,0 +1,265 @@
+//! Performance and risk metrics for equity curves.
+//!
+//! Ratios are annualized using the number of periods per year of the curve - see
+//! [`periods_per_year`](crate::returns::periods_per_year).  Risk free rates are annual rates.
+
+use crate::Timestamped;
+use crate::math::{ mean, norm_inv, norm_pdf, quantile_sorted, sorted, stdev };
+use crate::returns::{ annualize_volatility, deannualize_rate, simple_returns };
+
+/// The number of milliseconds in an average year
+const MILLIS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0 * 1_000.0;
+
+/// The value of an investment at a point in time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EquityPoint {
+   /// The timestamp of the value in millisecond accuracy
+   pub timestamp: i64,
+
+   /// The value of the investment
+   pub value: f64
+}
+impl Timestamped for EquityPoint {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// A fall from a peak in value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Drawdown {
+   /// The fall from the peak as a fraction of the peak - always zero or negative
+   pub depth: f64,
+
+   /// The timestamp of the peak value
+   pub peak: i64,
+
+   /// The timestamp of the lowest value
+   pub trough: i64,
+
+   /// The timestamp the value first got back to the peak - `None` if it hasn't recovered
+   pub recovery: Option<i64>
+}
+impl Drawdown {
+   /// The time from the peak to the recovery, or to the given timestamp if it hasn't recovered
+   pub fn duration(&self, end: i64) -> i64 { self.recovery.unwrap_or(end) - self.peak }
+}
+
+/// Performance and risk metrics for an equity curve sorted by timestamp
+#[derive(Clone, Debug)]
+pub struct Performance {
+   points: Vec<EquityPoint>,
+   returns: Vec<f64>,
+   periods_per_year: f64
+}
+impl Performance {
+   /// Creates the metrics for an equity curve with the given number of periods per year
+   pub fn new(points: &[EquityPoint], periods_per_year: f64) -> Self {
+      let values: Vec<f64> = points.iter().map(|point| point.value).collect();
+      Self { points: points.to_vec(), returns: simple_returns(&values), periods_per_year }
+   }
+
+   /// The returns between each point of the curve
+   pub fn returns(&self) -> &[f64] { &self.returns }
+
+   /// The compound annual growth rate, based on the time between the first and last points
+   pub fn cagr(&self) -> f64 {
+      match (self.points.first(), self.points.last()) {
+         (Some(first), Some(last)) if last.timestamp > first.timestamp => {
+            let years = (last.timestamp - first.timestamp) as f64 / MILLIS_PER_YEAR;
+            (last.value / first.value).powf(1.0 / years) - 1.0
+         },
+         _ => 0.0
+      }
+   }
+
+   /// The annualized standard deviation of returns
+   pub fn volatility(&self) -> f64 { annualize_volatility(stdev(&self.returns), self.periods_per_year) }
+
+   /// The annualized Sharpe ratio
+   pub fn sharpe(&self, risk_free: f64) -> f64 {
+      let excess = self.excess_returns(risk_free);
+      mean(&excess) / stdev(&excess) * self.periods_per_year.sqrt()
+   }
+
+   /// The annualized Sortino ratio, using the risk free rate as the target return
+   pub fn sortino(&self, risk_free: f64) -> f64 {
+      let excess = self.excess_returns(risk_free);
+      let downside: Vec<f64> = excess.iter().map(|value| value.min(0.0).powi(2)).collect();
+      mean(&excess) / mean(&downside).sqrt() * self.periods_per_year.sqrt()
+   }
+
+   /// The Calmar ratio - the compound annual growth rate over the maximum drawdown
+   pub fn calmar(&self) -> f64 {
+      self.max_drawdown().map_or(f64::INFINITY, |drawdown| self.cagr() / drawdown.depth.abs())
+   }
+
+   /// The Omega ratio - the gains above a threshold return over the losses below it
+   pub fn omega(&self, threshold: f64) -> f64 {
+      let gains: f64 = self.returns.iter().map(|value| (value - threshold).max(0.0)).sum();
+      let losses: f64 = self.returns.iter().map(|value| (threshold - value).max(0.0)).sum();
+      gains / losses
+   }
+
+   /// All of the drawdowns in the order they happened
+   pub fn drawdowns(&self) -> Vec<Drawdown> {
+      let mut drawdowns = vec![];
+      let mut current: Option<Drawdown> = None;
+      let mut peak = match self.points.first() {
+         Some(first) => *first,
+         None => return drawdowns
+      };
+
+      for point in self.points.iter().skip(1) {
+         if point.value >= peak.value {
+            if let Some(mut drawdown) = current.take() {
+               drawdown.recovery = Some(point.timestamp);
+               drawdowns.push(drawdown);
+            }
+            peak = *point;
+            continue
+         }
+
+         let depth = point.value / peak.value - 1.0;
+         let drawdown = current.get_or_insert(Drawdown { depth, peak: peak.timestamp, trough: point.timestamp, recovery: None });
+         if depth < drawdown.depth {
+            drawdown.depth = depth;
+            drawdown.trough = point.timestamp;
+         }
+      }
+
+      drawdowns.extend(current);
+      drawdowns
+   }
+
+   /// The deepest drawdown - `None` if the value never fell
+   pub fn max_drawdown(&self) -> Option<Drawdown> {
+      self.drawdowns().into_iter().fold(None, |deepest: Option<Drawdown>, drawdown| match deepest {
+         Some(deepest) if deepest.depth <= drawdown.depth => Some(deepest),
+         _ => Some(drawdown)
+      })
+   }
+
+   /// The longest time spent in a drawdown, in milliseconds
+   pub fn max_drawdown_duration(&self) -> i64 {
+      let end = self.points.last().map_or(0, |point| point.timestamp);
+      self.drawdowns().iter().map(|drawdown| drawdown.duration(end)).max().unwrap_or(0)
+   }
+
+   /// The historical value at risk - the loss (as a positive fraction) not exceeded with the given confidence
+   pub fn var(&self, confidence: f64) -> f64 {
+      -quantile_sorted(&sorted(&self.returns), 1.0 - confidence)
+   }
+
+   /// The historical conditional value at risk - the average loss beyond the value at risk
+   pub fn cvar(&self, confidence: f64) -> f64 {
+      let cutoff = -self.var(confidence);
+      let tail: Vec<f64> = self.returns.iter().copied().filter(|value| *value <= cutoff).collect();
+      -mean(&tail)
+   }
+
+   /// The value at risk assuming normally distributed returns
+   pub fn parametric_var(&self, confidence: f64) -> f64 {
+      -(mean(&self.returns) + norm_inv(1.0 - confidence) * stdev(&self.returns))
+   }
+
+   /// The conditional value at risk assuming normally distributed returns
+   pub fn parametric_cvar(&self, confidence: f64) -> f64 {
+      let z = norm_inv(1.0 - confidence);
+      -(mean(&self.returns) - stdev(&self.returns) * norm_pdf(z) / (1.0 - confidence))
+   }
+
+   /// The skewness of returns
+   pub fn skewness(&self) -> f64 { self.standardized_moment(3) }
+
+   /// The excess kurtosis of returns - 0 for a normal distribution
+   pub fn kurtosis(&self) -> f64 { self.standardized_moment(4) - 3.0 }
+
+   /// The fraction of returns that were gains
+   pub fn hit_rate(&self) -> f64 {
+      if self.returns.is_empty() { return 0.0 }
+      self.returns.iter().filter(|value| **value > 0.0).count() as f64 / self.returns.len() as f64
+   }
+
+   fn excess_returns(&self, risk_free: f64) -> Vec<f64> {
+      let rate = deannualize_rate(risk_free, self.periods_per_year);
+      self.returns.iter().map(|value| value - rate).collect()
+   }
+
+   fn standardized_moment(&self, power: i32) -> f64 {
+      let center = mean(&self.returns);
+      let deviations: Vec<f64> = self.returns.iter().map(|value| value - center).collect();
+      let variance = mean(&deviations.iter().map(|value| value.powi(2)).collect::<Vec<f64>>());
+      mean(&deviations.iter().map(|value| value.powi(power)).collect::<Vec<f64>>()) / variance.powf(f64::from(power) / 2.0)
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   const DAY: i64 = 24 * 60 * 60 * 1_000;
+
+   fn assert_close(expected: f64, actual: f64) {
+      assert!((expected - actual).abs() < 1e-9, "expected {} but was {}", expected, actual);
+   }
+
+   fn curve(values: &[f64]) -> Vec<EquityPoint> {
+      values.iter().enumerate().map(|(index, value)| EquityPoint { timestamp: index as i64 * DAY, value: *value }).collect()
+   }
+
+   #[test]
+   fn verify_drawdowns() {
+      let performance = Performance::new(&curve(&[ 100.0, 120.0, 90.0, 100.0, 130.0, 117.0 ]), 252.0);
+
+      let drawdowns = performance.drawdowns();
+      assert_eq!(2, drawdowns.len());
+      assert_eq!(Drawdown { depth: -0.25, peak: DAY, trough: 2 * DAY, recovery: Some(4 * DAY) }, drawdowns[0]);
+      assert_eq!(None, drawdowns[1].recovery);
+      assert_eq!(-0.25, performance.max_drawdown().unwrap().depth);
+      assert_eq!(3 * DAY, performance.max_drawdown_duration());
+   }
+
+   #[test]
+   fn verify_ratios() {
+      let performance = Performance::new(&curve(&[ 100.0, 110.0, 99.0, 108.9, 119.79 ]), 4.0);
+      let returns = performance.returns();
+      assert_close(0.1, returns[0]);
+      assert_close(-0.1, returns[1]);
+
+      // mean 0.05 and sample standard deviation 0.1 per period
+      assert_close(0.05 / 0.1 * 2.0, performance.sharpe(0.0));
+      assert_close(0.05 / (0.01_f64 / 4.0).sqrt() * 2.0, performance.sortino(0.0));
+      assert_close(0.3 / 0.1, performance.omega(0.0));
+      assert_close(0.75, performance.hit_rate());
+      assert_close(0.2, performance.volatility());
+   }
+
+   #[test]
+   fn verify_cagr() {
+      let points = vec![
+         EquityPoint { timestamp: 0, value: 100.0 },
+         EquityPoint { timestamp: (2.0 * MILLIS_PER_YEAR) as i64, value: 121.0 }
+      ];
+      let performance = Performance::new(&points, 1.0);
+
+      assert_close(0.1, performance.cagr());
+      assert_eq!(f64::INFINITY, performance.calmar());
+   }
+
+   #[test]
+   fn verify_value_at_risk() {
+      let values: Vec<f64> = (0..=100).scan(100.0, |value, index| {
+         *value *= 1.0 + if index % 10 == 0 { -0.05 } else { 0.01 };
+         Some(*value)
+      }).collect();
+      let performance = Performance::new(&curve(&values), 252.0);
+
+      assert_close(0.05, performance.var(0.95));
+      assert_close(0.05, performance.cvar(0.95));
+      assert!(performance.parametric_var(0.99) > performance.parametric_var(0.95));
+      assert!(performance.parametric_cvar(0.95) > performance.parametric_var(0.95));
+      assert!(performance.skewness() < 0.0);
+      assert!(performance.kurtosis() > 0.0);
+   }
+}
\ No newline at end of file
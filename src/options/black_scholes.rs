@@ -0,0 +1,240 @@
+use crate::Quote;
+use crate::math::{ norm_cdf, norm_pdf };
+use super::{ Greeks, OptionKind };
+
+/// A European option on an underlying paying a continuous dividend yield (Black-Scholes-Merton)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlackScholes {
+   /// The price of the underlying
+   pub spot: f64,
+
+   /// The price the option can be exercised at
+   pub strike: f64,
+
+   /// The time to expiry in years
+   pub time: f64,
+
+   /// The risk free interest rate
+   pub rate: f64,
+
+   /// The continuous dividend yield of the underlying
+   pub dividend_yield: f64,
+
+   /// The volatility of the underlying
+   pub volatility: f64
+}
+impl BlackScholes {
+   /// Creates an option on an underlying without a dividend yield
+   pub fn new(spot: f64, strike: f64, time: f64, rate: f64, volatility: f64) -> Self {
+      Self { spot, strike, time, rate, dividend_yield: 0.0, volatility }
+   }
+
+   /// Creates an option on the underlying of a quote, at the quote's price
+   pub fn from_quote(quote: &Quote, strike: f64, time: f64, rate: f64, volatility: f64) -> Self {
+      Self::new(quote.price, strike, time, rate, volatility)
+   }
+
+   /// The forward price of the underlying at expiry
+   pub fn forward(&self) -> f64 { self.spot * ((self.rate - self.dividend_yield) * self.time).exp() }
+
+   /// The standardized distances of the forward from the strike, `(d1, d2)`
+   pub fn d1_d2(&self) -> (f64, f64) {
+      let deviation = self.volatility * self.time.sqrt();
+      let d1 = ((self.spot / self.strike).ln() + (self.rate - self.dividend_yield + self.volatility.powi(2) / 2.0) * self.time) / deviation;
+      (d1, d1 - deviation)
+   }
+
+   /// The price of the option
+   pub fn price(&self, kind: OptionKind) -> f64 {
+      let (spot, strike) = (self.discounted_spot(), self.discounted_strike());
+      if self.is_degenerate() { return kind.intrinsic(spot, strike) }
+
+      let (d1, d2) = self.d1_d2();
+      match kind {
+         OptionKind::Call => spot * norm_cdf(d1) - strike * norm_cdf(d2),
+         OptionKind::Put => strike * norm_cdf(-d2) - spot * norm_cdf(-d1)
+      }
+   }
+
+   /// The sensitivities of the price of the option
+   pub fn greeks(&self, kind: OptionKind) -> Greeks {
+      let (carry, discount) = ((-self.dividend_yield * self.time).exp(), (-self.rate * self.time).exp());
+      if self.is_degenerate() {
+         let delta = match kind {
+            OptionKind::Call if self.discounted_spot() > self.discounted_strike() => carry,
+            OptionKind::Put if self.discounted_spot() < self.discounted_strike() => -carry,
+            _ => 0.0
+         };
+         return Greeks { delta, ..Greeks::default() }
+      }
+
+      let (d1, d2) = self.d1_d2();
+      let root_time = self.time.sqrt();
+      let density = norm_pdf(d1);
+      let sign = match kind { OptionKind::Call => 1.0, OptionKind::Put => -1.0 };
+
+      let vega = self.spot * carry * density * root_time;
+      let decay = -self.spot * carry * density * self.volatility / (2.0 * root_time);
+      let drift = (2.0 * (self.rate - self.dividend_yield) * self.time - d2 * self.volatility * root_time)
+         / (2.0 * self.time * self.volatility * root_time);
+
+      Greeks {
+         delta: sign * carry * norm_cdf(sign * d1),
+         gamma: carry * density / (self.spot * self.volatility * root_time),
+         vega,
+         theta: decay - sign * self.rate * self.strike * discount * norm_cdf(sign * d2)
+            + sign * self.dividend_yield * self.spot * carry * norm_cdf(sign * d1),
+         rho: sign * self.strike * self.time * discount * norm_cdf(sign * d2),
+         vanna: -carry * density * d2 / self.volatility,
+         volga: vega * d1 * d2 / self.volatility,
+         charm: sign * self.dividend_yield * carry * norm_cdf(sign * d1) - carry * density * drift
+      }
+   }
+
+   fn discounted_spot(&self) -> f64 { self.spot * (-self.dividend_yield * self.time).exp() }
+
+   fn discounted_strike(&self) -> f64 { self.strike * (-self.rate * self.time).exp() }
+
+   /// Whether there is no uncertainty left, so the option is worth its discounted intrinsic value
+   fn is_degenerate(&self) -> bool { self.time <= 0.0 || self.volatility <= 0.0 }
+}
+
+/// A European option on a futures or forward contract (Black-76)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Black76 {
+   /// The price of the futures contract
+   pub forward: f64,
+
+   /// The price the option can be exercised at
+   pub strike: f64,
+
+   /// The time to expiry in years
+   pub time: f64,
+
+   /// The risk free interest rate
+   pub rate: f64,
+
+   /// The volatility of the futures price
+   pub volatility: f64
+}
+impl Black76 {
+   /// Creates an option on a futures contract
+   pub fn new(forward: f64, strike: f64, time: f64, rate: f64, volatility: f64) -> Self {
+      Self { forward, strike, time, rate, volatility }
+   }
+
+   /// The price of the option
+   pub fn price(&self, kind: OptionKind) -> f64 { self.as_black_scholes().price(kind) }
+
+   /// The sensitivities of the price of the option, with delta and gamma against the futures price
+   pub fn greeks(&self, kind: OptionKind) -> Greeks {
+      // the rate only discounts the payoff, as the futures price doesn't drift
+      Greeks { rho: -self.time * self.price(kind), ..self.as_black_scholes().greeks(kind) }
+   }
+
+   /// A futures contract is an underlying with a yield equal to the interest rate
+   fn as_black_scholes(&self) -> BlackScholes {
+      BlackScholes { spot: self.forward, strike: self.strike, time: self.time, rate: self.rate, dividend_yield: self.rate, volatility: self.volatility }
+   }
+}
+
+/// The price of a European call implied by the price of the put with the same strike and expiry
+pub fn call_from_put(put: f64, spot: f64, strike: f64, time: f64, rate: f64, dividend_yield: f64) -> f64 {
+   put + spot * (-dividend_yield * time).exp() - strike * (-rate * time).exp()
+}
+
+/// The price of a European put implied by the price of the call with the same strike and expiry
+pub fn put_from_call(call: f64, spot: f64, strike: f64, time: f64, rate: f64, dividend_yield: f64) -> f64 {
+   call - spot * (-dividend_yield * time).exp() + strike * (-rate * time).exp()
+}
+
+/// How far the prices of a call and put break put-call parity - positive when the call is rich
+pub fn parity_gap(call: f64, put: f64, spot: f64, strike: f64, time: f64, rate: f64, dividend_yield: f64) -> f64 {
+   call - call_from_put(put, spot, strike, time, rate, dividend_yield)
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+      assert!((expected - actual).abs() < tolerance, "expected {} but was {}", expected, actual);
+   }
+
+   #[test]
+   fn verify_prices() {
+      // Hull - Options, Futures and Other Derivatives, example 15.6
+      let option = BlackScholes::new(42.0, 40.0, 0.5, 0.1, 0.2);
+      assert_close(4.76, option.price(OptionKind::Call), 0.005);
+      assert_close(0.81, option.price(OptionKind::Put), 0.005);
+
+      // Haug - The Complete Guide to Option Pricing Formulas, generalized Black-Scholes-Merton
+      let option = BlackScholes { dividend_yield: 0.05, ..BlackScholes::new(100.0, 95.0, 0.5, 0.1, 0.2) };
+      assert_close(2.4648, option.price(OptionKind::Put), 0.00005);
+
+      // Haug - Black-76
+      let futures = Black76::new(19.0, 19.0, 0.75, 0.1, 0.28);
+      assert_close(1.7011, futures.price(OptionKind::Call), 0.00005);
+      assert_close(1.7011, futures.price(OptionKind::Put), 0.00005);
+   }
+
+   #[test]
+   fn verify_greeks() {
+      // Hull - Options, Futures and Other Derivatives, chapter 19
+      let option = BlackScholes::new(49.0, 50.0, 0.3846, 0.05, 0.2);
+      let greeks = option.greeks(OptionKind::Call);
+      assert_close(0.522, greeks.delta, 0.0005);
+      assert_close(0.066, greeks.gamma, 0.0005);
+      assert_close(12.1, greeks.vega, 0.05);
+      assert_close(-4.31, greeks.theta, 0.005);
+      assert_close(8.91, greeks.rho, 0.005);
+   }
+
+   #[test]
+   fn verify_greeks_against_differences() {
+      let option = BlackScholes { dividend_yield: 0.03, ..BlackScholes::new(100.0, 105.0, 0.75, 0.04, 0.25) };
+      let bump = 1e-4;
+
+      for kind in [ OptionKind::Call, OptionKind::Put ].iter().copied() {
+         let greeks = option.greeks(kind);
+         let with = |change: &dyn Fn(&mut BlackScholes)| { let mut bumped = option; change(&mut bumped); bumped };
+         let price = |change: &dyn Fn(&mut BlackScholes)| with(change).price(kind);
+         let delta = |change: &dyn Fn(&mut BlackScholes)| with(change).greeks(kind).delta;
+
+         assert_close((price(&|o| o.spot += bump) - price(&|o| o.spot -= bump)) / (2.0 * bump), greeks.delta, 1e-6);
+         assert_close((delta(&|o| o.spot += bump) - delta(&|o| o.spot -= bump)) / (2.0 * bump), greeks.gamma, 1e-6);
+         assert_close((price(&|o| o.volatility += bump) - price(&|o| o.volatility -= bump)) / (2.0 * bump), greeks.vega, 1e-5);
+         assert_close((price(&|o| o.time -= bump) - price(&|o| o.time += bump)) / (2.0 * bump), greeks.theta, 1e-5);
+         assert_close((price(&|o| o.rate += bump) - price(&|o| o.rate -= bump)) / (2.0 * bump), greeks.rho, 1e-5);
+         assert_close((delta(&|o| o.volatility += bump) - delta(&|o| o.volatility -= bump)) / (2.0 * bump), greeks.vanna, 1e-6);
+         assert_close((delta(&|o| o.time -= bump) - delta(&|o| o.time += bump)) / (2.0 * bump), greeks.charm, 1e-6);
+
+         let vega = |change: &dyn Fn(&mut BlackScholes)| with(change).greeks(kind).vega;
+         assert_close((vega(&|o| o.volatility += bump) - vega(&|o| o.volatility -= bump)) / (2.0 * bump), greeks.volga, 1e-4);
+      }
+
+      let futures = Black76::new(100.0, 95.0, 0.5, 0.05, 0.3);
+      let bumped = Black76 { rate: 0.05 + bump, ..futures }.price(OptionKind::Put) - Black76 { rate: 0.05 - bump, ..futures }.price(OptionKind::Put);
+      assert_close(bumped / (2.0 * bump), futures.greeks(OptionKind::Put).rho, 1e-6);
+   }
+
+   #[test]
+   fn verify_parity() {
+      let option = BlackScholes { dividend_yield: 0.02, ..BlackScholes::new(100.0, 110.0, 1.0, 0.05, 0.3) };
+      let (call, put) = (option.price(OptionKind::Call), option.price(OptionKind::Put));
+
+      assert_close(call, call_from_put(put, 100.0, 110.0, 1.0, 0.05, 0.02), 1e-10);
+      assert_close(put, put_from_call(call, 100.0, 110.0, 1.0, 0.05, 0.02), 1e-10);
+      assert_close(0.5, parity_gap(call + 0.5, put, 100.0, 110.0, 1.0, 0.05, 0.02), 1e-10);
+   }
+
+   #[test]
+   fn verify_expired() {
+      let option = BlackScholes::new(105.0, 100.0, 0.0, 0.05, 0.2);
+      assert_eq!(5.0, option.price(OptionKind::Call));
+      assert_eq!(0.0, option.price(OptionKind::Put));
+      assert_eq!(1.0, option.greeks(OptionKind::Call).delta);
+      assert_eq!(0.0, option.greeks(OptionKind::Put).gamma);
+   }
+}
\ No newline at end of file
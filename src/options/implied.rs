@@ -0,0 +1,132 @@
+use std::fmt;
+use crate::math::brent;
+use super::{ Black76, BlackScholes, OptionKind };
+
+/// The accuracy implied volatilities are solved to
+const TOLERANCE: f64 = 1e-10;
+
+/// The highest volatility searched for
+const MAX_VOLATILITY: f64 = 20.0;
+
+/// Why a volatility couldn't be implied from a price
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImpliedVolatilityError {
+   /// The price is below the discounted intrinsic value - no volatility gives this price
+   BelowIntrinsic { price: f64, minimum: f64 },
+
+   /// The price is at or above what the option is worth with infinite volatility
+   AboveMaximum { price: f64, maximum: f64 },
+
+   /// The solver didn't converge
+   NoConvergence
+}
+impl fmt::Display for ImpliedVolatilityError {
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+         Self::BelowIntrinsic { price, minimum } => write!(f, "a price of {} is below the minimum of {}", price, minimum),
+         Self::AboveMaximum { price, maximum } => write!(f, "a price of {} is at or above the maximum of {}", price, maximum),
+         Self::NoConvergence => write!(f, "the implied volatility didn't converge")
+      }
+   }
+}
+impl std::error::Error for ImpliedVolatilityError {}
+
+impl BlackScholes {
+   /// The volatility that gives an option a price - the option's own volatility is used as the first guess
+   pub fn implied_volatility(&self, kind: OptionKind, price: f64) -> Result<f64, ImpliedVolatilityError> {
+      let (spot, strike) = (self.spot * (-self.dividend_yield * self.time).exp(), self.strike * (-self.rate * self.time).exp());
+      let maximum = match kind {
+         OptionKind::Call => spot,
+         OptionKind::Put => strike
+      };
+      check_bounds(price, kind.intrinsic(spot, strike), maximum)?;
+
+      let with = |volatility: f64| Self { volatility, ..*self };
+      solve(
+         |volatility| with(volatility).price(kind) - price,
+         |volatility| with(volatility).greeks(kind).vega,
+         self.volatility)
+   }
+}
+
+impl Black76 {
+   /// The volatility that gives an option a price - the option's own volatility is used as the first guess
+   pub fn implied_volatility(&self, kind: OptionKind, price: f64) -> Result<f64, ImpliedVolatilityError> {
+      let discount = (-self.rate * self.time).exp();
+      check_bounds(price, discount * kind.intrinsic(self.forward, self.strike), discount * match kind {
+         OptionKind::Call => self.forward,
+         OptionKind::Put => self.strike
+      })?;
+
+      let with = |volatility: f64| Self { volatility, ..*self };
+      solve(
+         |volatility| with(volatility).price(kind) - price,
+         |volatility| with(volatility).greeks(kind).vega,
+         self.volatility)
+   }
+}
+
+fn check_bounds(price: f64, minimum: f64, maximum: f64) -> Result<(), ImpliedVolatilityError> {
+   if price < minimum - TOLERANCE { return Err(ImpliedVolatilityError::BelowIntrinsic { price, minimum }) }
+   if price >= maximum { return Err(ImpliedVolatilityError::AboveMaximum { price, maximum }) }
+   Ok(())
+}
+
+/// Newton's method from the guess, falling back to Brent's method when a step leaves the bracket of
+/// volatilities known to hold the answer or the vega is too small to step with
+fn solve(error: impl Fn(f64) -> f64, vega: impl Fn(f64) -> f64, guess: f64) -> Result<f64, ImpliedVolatilityError> {
+   let (mut lower, mut upper) = (0.0, MAX_VOLATILITY);
+   let mut volatility = if guess > 0.0 && guess < MAX_VOLATILITY { guess } else { 0.2 };
+
+   for _ in 0..50 {
+      let difference = error(volatility);
+      if difference.abs() < TOLERANCE { return Ok(volatility) }
+      if difference > 0.0 { upper = volatility } else { lower = volatility }
+
+      let slope = vega(volatility);
+      let next = volatility - difference / slope;
+      if !(slope > TOLERANCE && next > lower && next < upper) { break }
+      volatility = next;
+   }
+
+   brent(error, lower, upper, TOLERANCE, 200).ok_or(ImpliedVolatilityError::NoConvergence)
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn assert_close(expected: f64, actual: f64) {
+      assert!((expected - actual).abs() < 1e-8, "expected {} but was {}", expected, actual);
+   }
+
+   #[test]
+   fn verify_round_trip() {
+      for volatility in [ 0.05, 0.2, 0.6, 1.5 ].iter() {
+         for strike in [ 50.0, 90.0, 100.0, 120.0, 200.0 ].iter() {
+            let option = BlackScholes { dividend_yield: 0.01, ..BlackScholes::new(100.0, *strike, 0.5, 0.03, *volatility) };
+            for kind in [ OptionKind::Call, OptionKind::Put ].iter().copied() {
+               // deep in or out of the money the price barely depends on the volatility
+               if option.greeks(kind).vega < 1e-2 { continue }
+               let price = option.price(kind);
+
+               // start a long way from the answer to exercise the fallback
+               let guess = BlackScholes { volatility: 3.0, ..option };
+               assert_close(*volatility, guess.implied_volatility(kind, price).unwrap());
+            }
+         }
+      }
+
+      let futures = Black76::new(19.0, 19.0, 0.75, 0.1, 0.28);
+      assert_close(0.28, Black76 { volatility: 0.5, ..futures }.implied_volatility(OptionKind::Put, futures.price(OptionKind::Put)).unwrap());
+   }
+
+   #[test]
+   fn verify_arbitrage_violations() {
+      let option = BlackScholes::new(100.0, 90.0, 1.0, 0.0, 0.2);
+      assert_eq!(Err(ImpliedVolatilityError::BelowIntrinsic { price: 9.0, minimum: 10.0 }), option.implied_volatility(OptionKind::Call, 9.0));
+      assert_eq!(Err(ImpliedVolatilityError::AboveMaximum { price: 100.0, maximum: 100.0 }), option.implied_volatility(OptionKind::Call, 100.0));
+      assert!(option.implied_volatility(OptionKind::Put, 95.0).is_err());
+   }
+}
\ No newline at end of file
@@ -0,0 +1,189 @@
+use super::OptionKind;
+
+/// A cash dividend paid by the underlying
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CashDividend {
+   /// The time of the ex-dividend date in years
+   pub time: f64,
+
+   /// The amount paid per share
+   pub amount: f64
+}
+
+/// When an option can be exercised
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Exercise {
+   /// Only at expiry
+   European,
+
+   /// At any time up to expiry
+   American
+}
+
+/// The shape of a recombining tree
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tree {
+   /// Cox-Ross-Rubinstein - the price moves up or down each step
+   Binomial,
+
+   /// Boyle - the price moves up, down or stays the same each step
+   Trinomial
+}
+
+/// An option priced by backward induction through a tree of prices of the underlying.
+///
+/// Cash dividends use the escrowed dividend model - the tree is built for the spot less the present
+/// value of the dividends before expiry, and each node adds back the value of the dividends still to
+/// be paid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lattice {
+   /// The price of the underlying
+   pub spot: f64,
+
+   /// The price the option can be exercised at
+   pub strike: f64,
+
+   /// The time to expiry in years
+   pub time: f64,
+
+   /// The risk free interest rate
+   pub rate: f64,
+
+   /// The continuous dividend yield of the underlying
+   pub dividend_yield: f64,
+
+   /// The volatility of the underlying
+   pub volatility: f64,
+
+   /// The cash dividends paid by the underlying
+   pub dividends: Vec<CashDividend>,
+
+   /// When the option can be exercised
+   pub exercise: Exercise,
+
+   /// The shape of the tree
+   pub tree: Tree,
+
+   /// The number of steps to expiry
+   pub steps: usize
+}
+impl Lattice {
+   /// Creates an American option on an underlying without dividends, priced with a binomial tree of 500 steps
+   pub fn new(spot: f64, strike: f64, time: f64, rate: f64, volatility: f64) -> Self {
+      Self {
+         spot,
+         strike,
+         time,
+         rate,
+         dividend_yield: 0.0,
+         volatility,
+         dividends: vec![],
+         exercise: Exercise::American,
+         tree: Tree::Binomial,
+         steps: 500
+      }
+   }
+
+   /// The price of the option
+   pub fn price(&self, kind: OptionKind) -> f64 {
+      if self.time <= 0.0 || self.volatility <= 0.0 || self.steps == 0 { return kind.intrinsic(self.spot, self.strike) }
+
+      let step = self.time / self.steps as f64;
+      let discount = (-self.rate * step).exp();
+      let drift = self.rate - self.dividend_yield;
+      let escrowed = self.spot - self.dividend_value(0.0);
+
+      // the probabilities of each move from the lowest to the highest, and the size of a move in log price
+      let (probabilities, jump) = match self.tree {
+         Tree::Binomial => {
+            let jump = self.volatility * step.sqrt();
+            let up = (drift * step).exp() - (-jump).exp();
+            let up = up / (jump.exp() - (-jump).exp());
+            (vec![ 1.0 - up, up ], jump)
+         },
+         Tree::Trinomial => {
+            let skew = (step / (12.0 * self.volatility.powi(2))).sqrt() * (drift - self.volatility.powi(2) / 2.0);
+            (vec![ 1.0 / 6.0 - skew, 2.0 / 3.0, 1.0 / 6.0 + skew ], self.volatility * (3.0 * step).sqrt())
+         }
+      };
+      let width = probabilities.len() - 1;
+
+      // the node `index` at `level` has moved up `index` of the `width * level` possible moves
+      let price = |level: usize, index: usize| {
+         let moves = (2 * index) as f64 / width as f64 - level as f64;
+         escrowed * (moves * jump).exp() + self.dividend_value(level as f64 * step)
+      };
+
+      let mut values: Vec<f64> = (0..=width * self.steps).map(|index| kind.intrinsic(price(self.steps, index), self.strike)).collect();
+      for level in (0..self.steps).rev() {
+         for index in 0..=width * level {
+            let held = discount * probabilities.iter().enumerate().map(|(offset, probability)| probability * values[index + offset]).sum::<f64>();
+            values[index] = match self.exercise {
+               Exercise::European => held,
+               Exercise::American => held.max(kind.intrinsic(price(level, index), self.strike))
+            };
+         }
+         values.truncate(width * level + 1);
+      }
+
+      values[0]
+   }
+
+   /// The value at a time of the dividends still to be paid before expiry
+   fn dividend_value(&self, at: f64) -> f64 {
+      self.dividends.iter()
+         .filter(|dividend| dividend.time > at && dividend.time <= self.time)
+         .map(|dividend| dividend.amount * (-self.rate * (dividend.time - at)).exp())
+         .sum()
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::options::BlackScholes;
+
+   fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+      assert!((expected - actual).abs() < tolerance, "expected {} but was {}", expected, actual);
+   }
+
+   #[test]
+   fn verify_american() {
+      // Hull - Options, Futures and Other Derivatives, chapter 21
+      let option = Lattice::new(50.0, 50.0, 5.0 / 12.0, 0.1, 0.4);
+      assert_close(4.283, option.price(OptionKind::Put), 0.001);
+      assert_close(4.283, Lattice { tree: Tree::Trinomial, ..option.clone() }.price(OptionKind::Put), 0.002);
+
+      // without dividends an American call is never exercised early
+      let european = BlackScholes::new(50.0, 50.0, 5.0 / 12.0, 0.1, 0.4);
+      assert_close(european.price(OptionKind::Call), option.price(OptionKind::Call), 0.01);
+      assert!(option.price(OptionKind::Put) > european.price(OptionKind::Put) + 0.1);
+   }
+
+   #[test]
+   fn verify_european() {
+      let european = BlackScholes { dividend_yield: 0.02, ..BlackScholes::new(100.0, 95.0, 1.0, 0.05, 0.25) };
+      for tree in [ Tree::Binomial, Tree::Trinomial ].iter().copied() {
+         let option = Lattice { dividend_yield: 0.02, exercise: Exercise::European, tree, ..Lattice::new(100.0, 95.0, 1.0, 0.05, 0.25) };
+         assert_close(european.price(OptionKind::Call), option.price(OptionKind::Call), 0.01);
+         assert_close(european.price(OptionKind::Put), option.price(OptionKind::Put), 0.01);
+      }
+   }
+
+   #[test]
+   fn verify_dividends() {
+      let dividends = vec![ CashDividend { time: 0.25, amount: 2.0 }, CashDividend { time: 0.75, amount: 2.0 }, CashDividend { time: 1.5, amount: 2.0 } ];
+      let option = Lattice { dividends, exercise: Exercise::European, ..Lattice::new(100.0, 100.0, 1.0, 0.05, 0.3) };
+
+      // a European option only sees the spot less the dividends paid before expiry
+      let escrowed = 100.0 - 2.0 * (-0.05_f64 * 0.25).exp() - 2.0 * (-0.05_f64 * 0.75).exp();
+      let european = BlackScholes::new(escrowed, 100.0, 1.0, 0.05, 0.3);
+      assert_close(european.price(OptionKind::Call), option.price(OptionKind::Call), 0.02);
+
+      // an American call is worth exercising just before a dividend
+      let american = Lattice { exercise: Exercise::American, strike: 80.0, ..option.clone() };
+      assert!(american.price(OptionKind::Call) > Lattice { exercise: Exercise::European, ..american.clone() }.price(OptionKind::Call) + 0.01);
+      assert_eq!(20.0, Lattice { time: 0.0, strike: 120.0, ..option }.price(OptionKind::Put));
+   }
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+//! Option pricing.
+//!
+//! Times are in years and rates, yields and volatilities are continuously compounded annual rates.
+//! Greeks are per unit change - a vega of 12 is 0.12 per volatility point - and theta and charm are
+//! per year as time passes.
+
+mod black_scholes;
+pub use black_scholes::{ Black76, BlackScholes, call_from_put, parity_gap, put_from_call };
+
+mod implied;
+pub use implied::ImpliedVolatilityError;
+
+mod lattice;
+pub use lattice::{ CashDividend, Exercise, Lattice, Tree };
+
+mod monte_carlo;
+pub use monte_carlo::{ Average, Barrier, Dynamics, Estimate, Exotic, MonteCarlo };
+
+mod surface;
+pub use surface::{ Smile, VolatilitySurface };
+
+/// The number of milliseconds in a year of 365 days
+const MILLIS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0 * 1_000.0;
+
+/// Whether an option gives the right to buy or to sell
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OptionKind {
+   /// The right to buy at the strike
+   Call,
+
+   /// The right to sell at the strike
+   Put
+}
+impl OptionKind {
+   /// The value of exercising at a price
+   pub fn intrinsic(&self, price: f64, strike: f64) -> f64 {
+      match self {
+         Self::Call => (price - strike).max(0.0),
+         Self::Put => (strike - price).max(0.0)
+      }
+   }
+}
+
+/// The sensitivities of an option's price
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Greeks {
+   /// The change in price for a change in the underlying
+   pub delta: f64,
+
+   /// The change in delta for a change in the underlying
+   pub gamma: f64,
+
+   /// The change in price for a change in volatility
+   pub vega: f64,
+
+   /// The change in price as time passes
+   pub theta: f64,
+
+   /// The change in price for a change in the interest rate
+   pub rho: f64,
+
+   /// The change in delta for a change in volatility
+   pub vanna: f64,
+
+   /// The change in vega for a change in volatility
+   pub volga: f64,
+
+   /// The change in delta as time passes
+   pub charm: f64
+}
+
+/// The time in years between two timestamps, using years of 365 days
+pub fn year_fraction(start: i64, end: i64) -> f64 { (end - start) as f64 / MILLIS_PER_YEAR }
\ No newline at end of file
@@ -0,0 +1,330 @@
+use super::OptionKind;
+
+/// How the price of the underlying moves
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dynamics {
+   /// Geometric Brownian motion with a constant volatility
+   GeometricBrownian { volatility: f64 },
+
+   /// Heston stochastic volatility, simulated with a full truncation Euler scheme for the variance
+   Heston {
+      /// The variance today
+      variance: f64,
+
+      /// How quickly the variance reverts to its long run level
+      mean_reversion: f64,
+
+      /// The level the variance reverts to
+      long_run_variance: f64,
+
+      /// The volatility of the variance
+      vol_of_vol: f64,
+
+      /// The correlation between moves in the price and the variance
+      correlation: f64
+   }
+}
+
+/// How the prices of an Asian option are averaged
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Average {
+   Arithmetic,
+   Geometric
+}
+
+/// The level that knocks a barrier option in or out, and from which direction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Barrier {
+   UpAndOut(f64),
+   UpAndIn(f64),
+   DownAndOut(f64),
+   DownAndIn(f64)
+}
+impl Barrier {
+   /// Whether the option pays out for a path with the given extremes
+   fn is_alive(&self, lowest: f64, highest: f64) -> bool {
+      match *self {
+         Self::UpAndOut(level) => highest < level,
+         Self::UpAndIn(level) => highest >= level,
+         Self::DownAndOut(level) => lowest > level,
+         Self::DownAndIn(level) => lowest <= level
+      }
+   }
+}
+
+/// A path dependent option - prices are observed at the end of each step of the simulation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Exotic {
+   /// Pays against the average of the observed prices
+   Asian { kind: OptionKind, strike: f64, average: Average },
+
+   /// A European option that is knocked in or out by the observed prices crossing a level
+   Barrier { kind: OptionKind, strike: f64, barrier: Barrier },
+
+   /// Pays against the best observed price - with a fixed strike, or when `None` the price at expiry
+   /// against the worst observed price
+   Lookback { kind: OptionKind, strike: Option<f64> }
+}
+impl Exotic {
+   /// The payoff at expiry for the observed prices, starting with the spot
+   fn payoff(&self, path: &[f64]) -> f64 {
+      let observed = &path[1..];
+      let last = observed[observed.len() - 1];
+      let (lowest, highest) = path.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), price| (low.min(*price), high.max(*price)));
+
+      match *self {
+         Self::Asian { kind, strike, average } => {
+            let count = observed.len() as f64;
+            let average = match average {
+               Average::Arithmetic => observed.iter().sum::<f64>() / count,
+               Average::Geometric => (observed.iter().map(|price| price.ln()).sum::<f64>() / count).exp()
+            };
+            kind.intrinsic(average, strike)
+         },
+         Self::Barrier { kind, strike, barrier } => if barrier.is_alive(lowest, highest) { kind.intrinsic(last, strike) } else { 0.0 },
+         Self::Lookback { kind, strike } => match (kind, strike) {
+            (OptionKind::Call, Some(strike)) => kind.intrinsic(highest, strike),
+            (OptionKind::Put, Some(strike)) => kind.intrinsic(lowest, strike),
+            (OptionKind::Call, None) => last - lowest,
+            (OptionKind::Put, None) => highest - last
+         }
+      }
+   }
+}
+
+/// A Monte Carlo price and its standard error
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+   pub price: f64,
+   pub standard_error: f64
+}
+
+/// Prices path dependent options by simulating the underlying.
+///
+/// The simulation is seeded so the same settings always give the same estimate.  With antithetic
+/// variates each path is paired with its mirror image, and with a control variate the discounted
+/// price at expiry - whose expected value is known - is used to reduce the error of the estimate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MonteCarlo {
+   /// The price of the underlying
+   pub spot: f64,
+
+   /// The time to expiry in years
+   pub time: f64,
+
+   /// The risk free interest rate
+   pub rate: f64,
+
+   /// The continuous dividend yield of the underlying
+   pub dividend_yield: f64,
+
+   /// How the price of the underlying moves
+   pub dynamics: Dynamics,
+
+   /// The number of paths simulated - counting both paths of an antithetic pair
+   pub paths: usize,
+
+   /// The number of steps in each path
+   pub steps: usize,
+
+   /// The seed of the random numbers
+   pub seed: u64,
+
+   /// Whether to pair each path with its mirror image
+   pub antithetic: bool,
+
+   /// Whether to use the price at expiry as a control variate
+   pub control_variate: bool
+}
+impl MonteCarlo {
+   /// Creates a simulation of 10,000 paths with 100 steps on an underlying without a dividend yield,
+   /// using antithetic and control variates
+   pub fn new(spot: f64, time: f64, rate: f64, dynamics: Dynamics) -> Self {
+      Self { spot, time, rate, dividend_yield: 0.0, dynamics, paths: 10_000, steps: 100, seed: 0, antithetic: true, control_variate: true }
+   }
+
+   /// Estimates the price of an option
+   pub fn price(&self, option: &Exotic) -> Estimate {
+      let steps = self.steps.max(1);
+      let step = self.time / steps as f64;
+      let discount = (-self.rate * self.time).exp();
+      let factors = match self.dynamics { Dynamics::GeometricBrownian { .. } => 1, Dynamics::Heston { .. } => 2 };
+
+      let mut random = Random::new(self.seed);
+      let mut normals = vec![ 0.0; steps * factors ];
+      let mut path = vec![ 0.0; steps + 1 ];
+
+      // each sample is a discounted payoff with the discounted price at expiry as its control
+      let samples: Vec<(f64, f64)> = (0..self.samples()).map(|_| {
+         normals.iter_mut().for_each(|normal| *normal = random.normal());
+         let signs: &[f64] = if self.antithetic { &[ 1.0, -1.0 ] } else { &[ 1.0 ] };
+
+         let (payoff, control) = signs.iter().fold((0.0, 0.0), |(payoff, control), sign| {
+            self.simulate(&normals, *sign, step, &mut path);
+            (payoff + option.payoff(&path), control + path[steps])
+         });
+         let count = signs.len() as f64;
+         (discount * payoff / count, discount * control / count)
+      }).collect();
+
+      let expected = self.spot * (-self.dividend_yield * self.time).exp();
+      estimate(&samples, if self.control_variate { Some(expected) } else { None })
+   }
+
+   /// The number of independent samples - antithetic pairs count as a single sample
+   fn samples(&self) -> usize {
+      let samples = if self.antithetic { self.paths / 2 } else { self.paths };
+      samples.max(2)
+   }
+
+   /// Fills a path of prices from the spot using the normals, or their mirror image
+   fn simulate(&self, normals: &[f64], sign: f64, step: f64, path: &mut [f64]) {
+      let drift = self.rate - self.dividend_yield;
+      path[0] = self.spot;
+
+      match self.dynamics {
+         Dynamics::GeometricBrownian { volatility } => {
+            let (mean, deviation) = ((drift - volatility.powi(2) / 2.0) * step, volatility * step.sqrt());
+            for (index, normal) in normals.iter().enumerate() {
+               path[index + 1] = path[index] * (mean + deviation * sign * normal).exp();
+            }
+         },
+         Dynamics::Heston { variance, mean_reversion, long_run_variance, vol_of_vol, correlation } => {
+            let mut variance = variance;
+            let independent = (1.0 - correlation.powi(2)).sqrt();
+            for (index, pair) in normals.chunks(2).enumerate() {
+               let (price_shock, variance_shock) = (sign * pair[0], sign * (correlation * pair[0] + independent * pair[1]));
+               let positive = variance.max(0.0);
+               path[index + 1] = path[index] * ((drift - positive / 2.0) * step + (positive * step).sqrt() * price_shock).exp();
+               variance += mean_reversion * (long_run_variance - positive) * step + vol_of_vol * (positive * step).sqrt() * variance_shock;
+            }
+         }
+      }
+   }
+}
+
+/// The mean of the samples and its standard error, adjusted by the controls when their expected value is given
+fn estimate(samples: &[(f64, f64)], expected_control: Option<f64>) -> Estimate {
+   let count = samples.len() as f64;
+   let mean = |values: &dyn Fn(&(f64, f64)) -> f64| samples.iter().map(values).sum::<f64>() / count;
+   let (payoff_mean, control_mean) = (mean(&|sample| sample.0), mean(&|sample| sample.1));
+
+   let beta = match expected_control {
+      Some(_) => {
+         let covariance = mean(&|sample| (sample.0 - payoff_mean) * (sample.1 - control_mean));
+         let variance = mean(&|sample| (sample.1 - control_mean).powi(2));
+         if variance > 0.0 { covariance / variance } else { 0.0 }
+      },
+      None => 0.0
+   };
+
+   let price = payoff_mean - beta * (control_mean - expected_control.unwrap_or(control_mean));
+   let residual = mean(&|sample| (sample.0 - payoff_mean - beta * (sample.1 - control_mean)).powi(2)) * count / (count - 1.0);
+   Estimate { price, standard_error: (residual / count).sqrt() }
+}
+
+/// A SplitMix64 generator with Box-Muller normals - fast, dependency free and reproducible from a seed
+struct Random {
+   state: u64,
+   spare: Option<f64>
+}
+impl Random {
+   fn new(seed: u64) -> Self { Self { state: seed, spare: None } }
+
+   fn next(&mut self) -> u64 {
+      self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+      let mut value = self.state;
+      value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+      value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+      value ^ (value >> 31)
+   }
+
+   /// A uniform number strictly between zero and one
+   fn uniform(&mut self) -> f64 { ((self.next() >> 11) as f64 + 0.5) / (1u64 << 53) as f64 }
+
+   fn normal(&mut self) -> f64 {
+      if let Some(spare) = self.spare.take() { return spare }
+
+      let radius = (-2.0 * self.uniform().ln()).sqrt();
+      let angle = 2.0 * std::f64::consts::PI * self.uniform();
+      self.spare = Some(radius * angle.sin());
+      radius * angle.cos()
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::math::norm_cdf;
+   use crate::options::BlackScholes;
+
+   fn assert_within(expected: f64, estimate: Estimate) {
+      assert!((expected - estimate.price).abs() < 3.0 * estimate.standard_error, "expected {} but was {:?}", expected, estimate);
+   }
+
+   #[test]
+   fn verify_seeded() {
+      let simulation = MonteCarlo { paths: 2_000, ..MonteCarlo::new(100.0, 1.0, 0.05, Dynamics::GeometricBrownian { volatility: 0.2 }) };
+      let option = Exotic::Lookback { kind: OptionKind::Call, strike: None };
+      assert_eq!(simulation.price(&option), simulation.price(&option));
+      assert_ne!(simulation.price(&option), MonteCarlo { seed: 1, ..simulation }.price(&option));
+   }
+
+   #[test]
+   fn verify_barrier() {
+      let simulation = MonteCarlo { dividend_yield: 0.01, ..MonteCarlo::new(100.0, 1.0, 0.05, Dynamics::GeometricBrownian { volatility: 0.2 }) };
+      let european = BlackScholes { dividend_yield: 0.01, ..BlackScholes::new(100.0, 100.0, 1.0, 0.05, 0.2) };
+      let barrier = |barrier| simulation.price(&Exotic::Barrier { kind: OptionKind::Call, strike: 100.0, barrier });
+
+      // a barrier that can't be reached leaves a European option
+      assert_within(european.price(OptionKind::Call), barrier(Barrier::DownAndOut(0.0)));
+
+      // knocking in and knocking out at the same level adds up to the European option
+      let (knock_in, knock_out) = (barrier(Barrier::DownAndIn(90.0)), barrier(Barrier::DownAndOut(90.0)));
+      assert!((knock_in.price + knock_out.price - european.price(OptionKind::Call)).abs() < 3.0 * (knock_in.standard_error + knock_out.standard_error));
+      assert!(knock_in.price > 0.0 && knock_out.price > knock_in.price);
+   }
+
+   #[test]
+   fn verify_asian() {
+      // a geometric average of lognormal prices is lognormal, so has a closed form price
+      let (spot, strike, time, rate, volatility, steps): (f64, f64, f64, f64, f64, usize) = (100.0, 95.0, 1.0, 0.05, 0.3, 12);
+      let n = steps as f64;
+      let mean = spot.ln() + (rate - volatility * volatility / 2.0) * time * (n + 1.0) / (2.0 * n);
+      let variance = volatility * volatility * time * (n + 1.0) * (2.0 * n + 1.0) / (6.0 * n * n);
+      let d2 = (mean - strike.ln()) / variance.sqrt();
+      let geometric = (-rate * time).exp() * ((mean + variance / 2.0).exp() * norm_cdf(d2 + variance.sqrt()) - strike * norm_cdf(d2));
+
+      let simulation = MonteCarlo { steps, ..MonteCarlo::new(spot, time, rate, Dynamics::GeometricBrownian { volatility }) };
+      assert_within(geometric, simulation.price(&Exotic::Asian { kind: OptionKind::Call, strike, average: Average::Geometric }));
+
+      // the arithmetic average is never below the geometric average
+      let arithmetic = simulation.price(&Exotic::Asian { kind: OptionKind::Call, strike, average: Average::Arithmetic });
+      assert!(arithmetic.price > geometric);
+   }
+
+   #[test]
+   fn verify_variance_reduction() {
+      let plain = MonteCarlo { antithetic: false, control_variate: false, ..MonteCarlo::new(100.0, 1.0, 0.05, Dynamics::GeometricBrownian { volatility: 0.25 }) };
+      let option = Exotic::Asian { kind: OptionKind::Call, strike: 100.0, average: Average::Arithmetic };
+
+      let error = plain.price(&option).standard_error;
+      assert!(MonteCarlo { antithetic: true, ..plain }.price(&option).standard_error < error);
+      assert!(MonteCarlo { control_variate: true, ..plain }.price(&option).standard_error < error);
+   }
+
+   #[test]
+   fn verify_heston() {
+      // without any volatility of variance Heston is geometric Brownian motion
+      let dynamics = Dynamics::Heston { variance: 0.04, mean_reversion: 2.0, long_run_variance: 0.04, vol_of_vol: 0.0, correlation: -0.7 };
+      let simulation = MonteCarlo::new(100.0, 0.5, 0.03, dynamics);
+      let european = BlackScholes::new(100.0, 105.0, 0.5, 0.03, 0.2);
+      assert_within(european.price(OptionKind::Put), simulation.price(&Exotic::Barrier { kind: OptionKind::Put, strike: 105.0, barrier: Barrier::UpAndOut(f64::INFINITY) }));
+
+      // negative correlation makes low strikes more expensive than flat volatility would
+      let skewed = MonteCarlo { dynamics: Dynamics::Heston { variance: 0.04, mean_reversion: 2.0, long_run_variance: 0.04, vol_of_vol: 0.6, correlation: -0.7 }, ..simulation };
+      let option = Exotic::Barrier { kind: OptionKind::Put, strike: 80.0, barrier: Barrier::UpAndOut(f64::INFINITY) };
+      assert!(skewed.price(&option).price > simulation.price(&option).price);
+   }
+}
\ No newline at end of file
@@ -0,0 +1,147 @@
+use crate::math::CubicSpline;
+
+/// The implied volatilities of a single expiry, as a cubic spline across log moneyness
+#[derive(Clone, Debug, PartialEq)]
+pub struct Smile {
+   /// The time to expiry in years
+   pub time: f64,
+
+   /// The forward price of the underlying at expiry
+   pub forward: f64,
+
+   spline: CubicSpline
+}
+impl Smile {
+   /// Creates a smile from `(strike, volatility)` pairs, in any order
+   pub fn new(time: f64, forward: f64, points: &[(f64, f64)]) -> Self {
+      let mut points: Vec<(f64, f64)> = points.iter().map(|(strike, volatility)| ((strike / forward).ln(), *volatility)).collect();
+      points.sort_by(|a, b| a.0.total_cmp(&b.0));
+      points.dedup_by(|a, b| a.0 == b.0);
+
+      let (moneyness, volatilities): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+      Self { time, forward, spline: CubicSpline::new(&moneyness, &volatilities) }
+   }
+
+   /// The volatility at a log moneyness - the log of the strike over the forward
+   pub fn volatility_at(&self, moneyness: f64) -> f64 { self.spline.value(moneyness) }
+
+   /// The volatility at a strike
+   pub fn volatility(&self, strike: f64) -> f64 { self.volatility_at((strike / self.forward).ln()) }
+
+   /// The slope of the volatility against log moneyness at the money
+   pub fn skew(&self) -> f64 { self.spline.derivative(0.0) }
+
+   /// The total implied variance at a log moneyness
+   fn variance_at(&self, moneyness: f64) -> f64 { self.volatility_at(moneyness).powi(2) * self.time }
+}
+
+/// Implied volatilities across strikes and expiries.
+///
+/// Each expiry is a [`Smile`] - between expiries the total variance is interpolated linearly in time
+/// at the same log moneyness, and the forward is interpolated log-linearly.  Before the first expiry
+/// and after the last the volatility of the nearest smile is used.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VolatilitySurface {
+   smiles: Vec<Smile>
+}
+impl VolatilitySurface {
+   /// Creates a surface from smiles in any order
+   pub fn new(mut smiles: Vec<Smile>) -> Self {
+      smiles.sort_by(|a, b| a.time.total_cmp(&b.time));
+      Self { smiles }
+   }
+
+   /// The smiles sorted by expiry
+   pub fn smiles(&self) -> &[Smile] { &self.smiles }
+
+   /// The forward price at a time to expiry - `None` for an empty surface
+   pub fn forward(&self, time: f64) -> Option<f64> {
+      Some(match self.bracket(time)? {
+         (low, None) => low.forward,
+         (low, Some((high, weight))) => (low.forward.ln() * (1.0 - weight) + high.forward.ln() * weight).exp()
+      })
+   }
+
+   /// The volatility at a strike and time to expiry - `None` for an empty surface
+   pub fn volatility(&self, strike: f64, time: f64) -> Option<f64> {
+      let moneyness = (strike / self.forward(time)?).ln();
+      self.volatility_at(moneyness, time)
+   }
+
+   /// The volatility at a log moneyness and time to expiry - `None` for an empty surface
+   pub fn volatility_at(&self, moneyness: f64, time: f64) -> Option<f64> {
+      Some(match self.bracket(time)? {
+         (low, None) => low.volatility_at(moneyness),
+         (low, Some((high, weight))) => {
+            let variance = low.variance_at(moneyness) * (1.0 - weight) + high.variance_at(moneyness) * weight;
+            (variance / time).sqrt()
+         }
+      })
+   }
+
+   /// The at the money forward volatility at a time to expiry
+   pub fn atm_volatility(&self, time: f64) -> Option<f64> { self.volatility_at(0.0, time) }
+
+   /// The slope of the volatility against log moneyness at the money
+   pub fn skew(&self, time: f64) -> Option<f64> {
+      const BUMP: f64 = 1e-4;
+      Some((self.volatility_at(BUMP, time)? - self.volatility_at(-BUMP, time)?) / (2.0 * BUMP))
+   }
+
+   /// The at the money volatility of each expiry
+   pub fn term_structure(&self) -> Vec<(f64, f64)> {
+      self.smiles.iter().map(|smile| (smile.time, smile.volatility_at(0.0))).collect()
+   }
+
+   /// The smile at or before a time, with the smile after it and how far the time is between them
+   fn bracket(&self, time: f64) -> Option<(&Smile, Option<(&Smile, f64)>)> {
+      let first = self.smiles.first()?;
+      let after = self.smiles.partition_point(|smile| smile.time <= time);
+      if after == 0 { return Some((first, None)) }
+      if after == self.smiles.len() { return Some((&self.smiles[after - 1], None)) }
+
+      let (low, high) = (&self.smiles[after - 1], &self.smiles[after]);
+      Some((low, Some((high, (time - low.time) / (high.time - low.time)))))
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn assert_close(expected: f64, actual: f64) {
+      assert!((expected - actual).abs() < 1e-9, "expected {} but was {}", expected, actual);
+   }
+
+   fn surface() -> VolatilitySurface {
+      VolatilitySurface::new(vec![
+         Smile::new(1.0, 105.0, &[ (80.0, 0.30), (105.0, 0.20), (130.0, 0.18) ]),
+         Smile::new(0.25, 101.0, &[ (101.0, 0.25), (80.0, 0.35), (120.0, 0.22) ])
+      ])
+   }
+
+   #[test]
+   fn verify_smiles() {
+      let surface = surface();
+      assert_eq!(0.25, surface.smiles()[0].time);
+      assert_close(0.25, surface.volatility(101.0, 0.25).unwrap());
+      assert_close(0.35, surface.volatility(80.0, 0.25).unwrap());
+      assert_close(0.35, surface.volatility(50.0, 0.1).unwrap());
+      assert!(surface.skew(0.25).unwrap() < 0.0);
+      assert!((surface.skew(0.25).unwrap() - surface.smiles()[0].skew()).abs() < 1e-6);
+   }
+
+   #[test]
+   fn verify_term_structure() {
+      let surface = surface();
+      assert_eq!(vec![ (0.25, 0.25), (1.0, 0.2) ], surface.term_structure());
+
+      // halfway in time the total variance is halfway between the expiries
+      let variance: f64 = (0.25 * 0.25 * 0.25 + 0.2 * 0.2 * 1.0) / 2.0;
+      assert_close((variance / 0.625).sqrt(), surface.atm_volatility(0.625).unwrap());
+      assert_close((101.0_f64 * 105.0).sqrt(), surface.forward(0.625).unwrap());
+      assert_close(0.2, surface.atm_volatility(2.0).unwrap());
+      assert_eq!(None, VolatilitySurface::default().atm_volatility(1.0));
+   }
+}
\ No newline at end of file
@@ -0,0 +1,457 @@
+//! Orders, fills and positions.
+
+use std::fmt;
+use crate::{ Quote, Timestamped };
+
+/// The side of an order or fill
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Side {
+   Buy,
+   Sell
+}
+impl Side {
+   /// The sign of quantities on this side - positive for buys and negative for sells
+   pub fn sign(&self) -> f64 {
+      match self {
+         Self::Buy => 1.0,
+         Self::Sell => -1.0
+      }
+   }
+
+   /// The other side
+   pub fn opposite(&self) -> Self {
+      match self {
+         Self::Buy => Self::Sell,
+         Self::Sell => Self::Buy
+      }
+   }
+}
+
+/// How far a trailing stop follows the best price since the order was placed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Trail {
+   /// A fixed amount from the best price
+   Amount(f64),
+
+   /// A fraction of the best price
+   Percent(f64)
+}
+impl Trail {
+   /// The stop price for a side given the best price so far - the highest for sells and the lowest for buys
+   pub fn stop_price(&self, side: Side, best: f64) -> f64 {
+      let distance = match self {
+         Self::Amount(amount) => *amount,
+         Self::Percent(fraction) => best * fraction
+      };
+      best + side.sign() * distance
+   }
+}
+
+/// How the price of an order is determined
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrderType {
+   /// Fills at the next available price
+   Market,
+
+   /// Fills at the given price or better
+   Limit(f64),
+
+   /// Becomes a market order once the price trades through the given price
+   Stop(f64),
+
+   /// Becomes a limit order once the price trades through the stop price
+   StopLimit { stop: f64, limit: f64 },
+
+   /// A stop order whose price follows the best price since the order was placed
+   TrailingStop(Trail)
+}
+
+/// How long an order remains active
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimeInForce {
+   /// Until the end of the trading period it was placed for
+   Day,
+
+   /// Until it is filled or cancelled
+   GoodTillCancelled,
+
+   /// Until the given timestamp
+   GoodTillDate(i64),
+
+   /// Fills as much as it can immediately and cancels the rest
+   ImmediateOrCancel,
+
+   /// Fills completely immediately or is cancelled
+   FillOrKill
+}
+
+/// An order to buy or sell a quantity
+#[derive(Clone, Debug, PartialEq)]
+pub struct Order {
+   /// An optional identifier chosen by whoever placed the order
+   pub client_id: Option<String>,
+
+   /// The timestamp the order was placed in millisecond accuracy
+   pub timestamp: i64,
+
+   pub side: Side,
+   pub order_type: OrderType,
+   pub time_in_force: TimeInForce,
+
+   /// The quantity to buy or sell - always positive
+   pub quantity: f64
+}
+impl Order {
+   /// Creates a good till cancelled order without a client id or timestamp
+   pub fn new(side: Side, order_type: OrderType, quantity: f64) -> Self {
+      Self { client_id: None, timestamp: 0, side, order_type, time_in_force: TimeInForce::GoodTillCancelled, quantity }
+   }
+
+   /// Creates a market order
+   pub fn market(side: Side, quantity: f64) -> Self { Self::new(side, OrderType::Market, quantity) }
+
+   /// Creates a limit order
+   pub fn limit(side: Side, quantity: f64, price: f64) -> Self { Self::new(side, OrderType::Limit(price), quantity) }
+
+   /// Creates a stop order
+   pub fn stop(side: Side, quantity: f64, price: f64) -> Self { Self::new(side, OrderType::Stop(price), quantity) }
+
+   /// Creates a stop limit order
+   pub fn stop_limit(side: Side, quantity: f64, stop: f64, limit: f64) -> Self {
+      Self::new(side, OrderType::StopLimit { stop, limit }, quantity)
+   }
+
+   /// Creates a trailing stop order
+   pub fn trailing_stop(side: Side, quantity: f64, trail: Trail) -> Self { Self::new(side, OrderType::TrailingStop(trail), quantity) }
+
+   /// Whether a price is the limit price or better - always true for orders without a limit
+   pub fn is_marketable(&self, price: f64) -> bool {
+      match self.order_type {
+         OrderType::Limit(limit) | OrderType::StopLimit { limit, .. } => (price - limit) * self.side.sign() <= 0.0,
+         _ => true
+      }
+   }
+}
+impl Timestamped for Order {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// The stage of an order's life
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OrderStatus {
+   /// Created but not yet accepted
+   Pending,
+
+   /// Accepted and waiting to be filled
+   Open,
+
+   PartiallyFilled,
+   Filled,
+   Cancelled,
+   Rejected,
+   Expired
+}
+impl OrderStatus {
+   /// Whether the order can no longer change
+   pub fn is_terminal(&self) -> bool {
+      matches!(self, Self::Filled | Self::Cancelled | Self::Rejected | Self::Expired)
+   }
+
+   /// Whether an order can move from this status to another
+   pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+      use OrderStatus::*;
+
+      matches!((self, next),
+         (Pending, Open) | (Pending, Rejected) | (Pending, Cancelled) |
+         (Open, PartiallyFilled) | (Open, Filled) | (Open, Cancelled) | (Open, Expired) |
+         (PartiallyFilled, PartiallyFilled) | (PartiallyFilled, Filled) | (PartiallyFilled, Cancelled) | (PartiallyFilled, Expired))
+   }
+}
+
+/// Why a change to an order was refused
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrderError {
+   /// The order can't move between the statuses
+   InvalidTransition { from: OrderStatus, to: OrderStatus },
+
+   /// A fill is for more than the quantity remaining
+   Overfill { remaining: f64, quantity: f64 },
+
+   /// A fill is on the other side to the order
+   WrongSide
+}
+impl fmt::Display for OrderError {
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+         Self::InvalidTransition { from, to } => write!(f, "an order can't move from {:?} to {:?}", from, to),
+         Self::Overfill { remaining, quantity } => write!(f, "a fill of {} is more than the {} remaining", quantity, remaining),
+         Self::WrongSide => write!(f, "a fill is on the other side to the order")
+      }
+   }
+}
+impl std::error::Error for OrderError {}
+
+/// An order and its progress through its life
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderState {
+   pub order: Order,
+   status: OrderStatus,
+   filled: f64,
+   average_price: f64,
+   timestamp: i64
+}
+impl OrderState {
+   /// Starts tracking a pending order
+   pub fn new(order: Order) -> Self {
+      let timestamp = order.timestamp;
+      Self { order, status: OrderStatus::Pending, filled: 0.0, average_price: 0.0, timestamp }
+   }
+
+   /// The current status
+   pub fn status(&self) -> OrderStatus { self.status }
+
+   /// The quantity filled so far
+   pub fn filled(&self) -> f64 { self.filled }
+
+   /// The quantity still to fill - 0 once the order has ended
+   pub fn remaining(&self) -> f64 {
+      if self.status.is_terminal() { 0.0 } else { self.order.quantity - self.filled }
+   }
+
+   /// The average price of the fills so far - 0 if there haven't been any
+   pub fn average_price(&self) -> f64 { self.average_price }
+
+   /// Moves to another status, as long as that is a valid transition
+   pub fn transition(&mut self, status: OrderStatus, timestamp: i64) -> Result<(), OrderError> {
+      if !self.status.can_transition_to(status) {
+         return Err(OrderError::InvalidTransition { from: self.status, to: status })
+      }
+
+      self.status = status;
+      self.timestamp = timestamp;
+      Ok(())
+   }
+
+   /// Accepts a pending order
+   pub fn accept(&mut self, timestamp: i64) -> Result<(), OrderError> { self.transition(OrderStatus::Open, timestamp) }
+
+   /// Rejects a pending order
+   pub fn reject(&mut self, timestamp: i64) -> Result<(), OrderError> { self.transition(OrderStatus::Rejected, timestamp) }
+
+   /// Cancels whatever remains of an order
+   pub fn cancel(&mut self, timestamp: i64) -> Result<(), OrderError> { self.transition(OrderStatus::Cancelled, timestamp) }
+
+   /// Expires whatever remains of an order
+   pub fn expire(&mut self, timestamp: i64) -> Result<(), OrderError> { self.transition(OrderStatus::Expired, timestamp) }
+
+   /// Applies a fill, moving to partially filled or filled - quantities within a billionth of the order
+   /// quantity of the remainder fill the order, allowing for floating point error
+   pub fn fill(&mut self, fill: &Fill) -> Result<(), OrderError> {
+      if fill.side != self.order.side { return Err(OrderError::WrongSide) }
+
+      let remaining = self.remaining();
+      let tolerance = self.order.quantity.abs() * 1e-9;
+      if fill.quantity > remaining + tolerance { return Err(OrderError::Overfill { remaining, quantity: fill.quantity }) }
+
+      let status = if fill.quantity >= remaining - tolerance { OrderStatus::Filled } else { OrderStatus::PartiallyFilled };
+      self.transition(status, fill.timestamp)?;
+      let filled = if status == OrderStatus::Filled { self.order.quantity } else { self.filled + fill.quantity };
+      self.average_price = (self.average_price * self.filled + fill.price * (filled - self.filled)) / filled;
+      self.filled = filled;
+      Ok(())
+   }
+}
+impl Timestamped for OrderState {
+   /// Gets the timestamp of the latest change in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// The execution of some or all of an order
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fill {
+   /// The timestamp of the fill in millisecond accuracy
+   pub timestamp: i64,
+
+   pub side: Side,
+
+   /// The quantity filled - always positive
+   pub quantity: f64,
+
+   /// The price of the fill, including any slippage
+   pub price: f64,
+
+   /// The commission charged for the fill
+   pub commission: f64
+}
+impl Fill {
+   /// The quantity with the sign of the side - positive for buys and negative for sells
+   pub fn signed_quantity(&self) -> f64 { self.side.sign() * self.quantity }
+}
+impl Timestamped for Fill {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// A holding built up from fills, valued at the average cost of the fills that opened it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+   /// The quantity held - negative when short
+   pub quantity: f64,
+
+   /// The average price of the open quantity - 0 when flat
+   pub average_price: f64,
+
+   /// The profit or loss from closed quantities, before commission
+   pub realized_pnl: f64,
+
+   /// The commission paid on all fills
+   pub commission: f64,
+
+   /// The timestamp the current position was opened
+   pub opened: i64,
+
+   /// The timestamp of the latest fill in millisecond accuracy
+   pub timestamp: i64
+}
+impl Position {
+   /// Whether nothing is held
+   pub fn is_flat(&self) -> bool { self.quantity == 0.0 }
+
+   /// `Buy` when long, `Sell` when short and `None` when flat
+   pub fn side(&self) -> Option<Side> {
+      if self.quantity > 0.0 { Some(Side::Buy) } else if self.quantity < 0.0 { Some(Side::Sell) } else { None }
+   }
+
+   /// The value of the position at a price - negative when short
+   pub fn market_value(&self, price: f64) -> f64 { self.quantity * price }
+
+   /// The profit or loss of the open quantity at a price
+   pub fn unrealized_pnl(&self, price: f64) -> f64 { self.quantity * (price - self.average_price) }
+
+   /// The profit or loss of the open quantity at a quote's price
+   pub fn unrealized_pnl_at(&self, quote: &Quote) -> f64 { self.unrealized_pnl(quote.price) }
+
+   /// The realized and unrealized profit or loss at a price, less commission
+   pub fn total_pnl(&self, price: f64) -> f64 { self.realized_pnl + self.unrealized_pnl(price) - self.commission }
+
+   /// Applies a fill, returning the profit or loss it realized
+   pub fn apply(&mut self, fill: &Fill) -> f64 {
+      let change = fill.signed_quantity();
+      self.commission += fill.commission;
+      self.timestamp = fill.timestamp;
+
+      if self.quantity == 0.0 || self.quantity.signum() == change.signum() {
+         let held = self.quantity.abs();
+         self.average_price = (self.average_price * held + fill.price * fill.quantity) / (held + fill.quantity);
+         if self.quantity == 0.0 { self.opened = fill.timestamp }
+         self.quantity += change;
+         return 0.0
+      }
+
+      let closed = fill.quantity.min(self.quantity.abs());
+      let realized = closed * (fill.price - self.average_price) * self.quantity.signum();
+      self.realized_pnl += realized;
+
+      self.quantity += change;
+      if self.quantity == 0.0 {
+         self.average_price = 0.0;
+      } else if self.quantity.signum() == change.signum() {
+         self.average_price = fill.price;
+         self.opened = fill.timestamp;
+      }
+
+      realized
+   }
+}
+impl Timestamped for Position {
+   /// Gets the timestamp of the latest fill in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::TradingSession;
+
+   fn fill(timestamp: i64, side: Side, quantity: f64, price: f64) -> Fill {
+      Fill { timestamp, side, quantity, price, commission: 1.0 }
+   }
+
+   #[test]
+   fn verify_order_lifecycle() {
+      let mut state = OrderState::new(Order::limit(Side::Buy, 10.0, 50.0));
+      assert_eq!(Err(OrderError::InvalidTransition { from: OrderStatus::Pending, to: OrderStatus::Filled }), state.fill(&fill(1, Side::Buy, 10.0, 50.0)));
+
+      state.accept(1).unwrap();
+      assert_eq!(Err(OrderError::WrongSide), state.fill(&fill(2, Side::Sell, 4.0, 50.0)));
+      state.fill(&fill(2, Side::Buy, 4.0, 50.0)).unwrap();
+      assert_eq!(OrderStatus::PartiallyFilled, state.status());
+      assert_eq!(Err(OrderError::Overfill { remaining: 6.0, quantity: 7.0 }), state.fill(&fill(3, Side::Buy, 7.0, 49.0)));
+
+      state.fill(&fill(3, Side::Buy, 6.0, 45.0)).unwrap();
+      assert_eq!(OrderStatus::Filled, state.status());
+      assert_eq!(47.0, state.average_price());
+      assert_eq!(0.0, state.remaining());
+      assert_eq!(3, state.timestamp_millis());
+      assert!(state.cancel(4).is_err());
+   }
+
+   #[test]
+   fn verify_fractional_fills() {
+      // 0.3 - 0.1 is 0.19999999999999998, which a fill of 0.2 still completes
+      let mut state = OrderState::new(Order::limit(Side::Sell, 0.3, 50.0));
+      state.accept(1).unwrap();
+      state.fill(&fill(2, Side::Sell, 0.1, 50.0)).unwrap();
+      state.fill(&fill(3, Side::Sell, 0.2, 50.0)).unwrap();
+      assert_eq!((OrderStatus::Filled, 0.3), (state.status(), state.filled()));
+
+      // three fills of 0.1 add up to 0.30000000000000004
+      let mut state = OrderState::new(Order::limit(Side::Buy, 0.3, 50.0));
+      state.accept(1).unwrap();
+      for timestamp in 2..5 { state.fill(&fill(timestamp, Side::Buy, 0.1, 50.0)).unwrap() }
+      assert_eq!((OrderStatus::Filled, 0.3), (state.status(), state.filled()));
+      assert_eq!(50.0, state.average_price());
+   }
+
+   #[test]
+   fn verify_transitions() {
+      assert!(OrderStatus::Open.can_transition_to(OrderStatus::Expired));
+      assert!(!OrderStatus::Pending.can_transition_to(OrderStatus::Expired));
+      assert!(!OrderStatus::Rejected.can_transition_to(OrderStatus::Open));
+      assert!(OrderStatus::Cancelled.is_terminal());
+   }
+
+   #[test]
+   fn verify_marketable() {
+      assert!(Order::limit(Side::Buy, 1.0, 10.0).is_marketable(9.5));
+      assert!(!Order::limit(Side::Sell, 1.0, 10.0).is_marketable(9.5));
+      assert!(Order::market(Side::Sell, 1.0).is_marketable(9.5));
+      assert_eq!(95.0, Trail::Percent(0.05).stop_price(Side::Sell, 100.0));
+      assert_eq!(102.0, Trail::Amount(2.0).stop_price(Side::Buy, 100.0));
+   }
+
+   #[test]
+   fn verify_position() {
+      let mut position = Position::default();
+      assert_eq!(0.0, position.apply(&fill(1, Side::Buy, 10.0, 100.0)));
+      assert_eq!(0.0, position.apply(&fill(2, Side::Buy, 10.0, 110.0)));
+      assert_eq!(105.0, position.average_price);
+
+      assert_eq!(75.0, position.apply(&fill(3, Side::Sell, 5.0, 120.0)));
+      assert_eq!(Some(Side::Buy), position.side());
+      assert_eq!(105.0, position.average_price);
+
+      let quote = Quote { symbol: "ABC".to_string(), timestamp: 4, session: TradingSession::Regular, price: 100.0, volume: 0 };
+      assert_eq!(-75.0, position.unrealized_pnl_at(&quote));
+      assert_eq!(-3.0, position.total_pnl(100.0));
+
+      // reversing closes the long and opens a short at the fill price
+      assert_eq!(-75.0, position.apply(&fill(5, Side::Sell, 20.0, 100.0)));
+      assert_eq!(-5.0, position.quantity);
+      assert_eq!((100.0, 5), (position.average_price, position.opened));
+      assert_eq!(Some(Side::Sell), position.side());
+      assert_eq!(5, position.timestamp_millis());
+   }
+}
\ No newline at end of file
@@ -0,0 +1,425 @@
+//! Candlestick pattern recognition.
+//!
+//! Bars are measured against their own range from low to high, so the thresholds work the same for
+//! any price.  Patterns that depend on the trend before them - a hammer and a hanging man are the
+//! same candle - compare the close before the pattern with the close a number of bars earlier.
+
+use std::collections::VecDeque;
+use crate::{ Bar, Timestamped };
+
+/// A candlestick pattern
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pattern {
+   /// A bar opening and closing at about the same price
+   Doji,
+
+   /// A small body at the top of a long lower shadow after a fall
+   Hammer,
+
+   /// A small body at the top of a long lower shadow after a rise
+   HangingMan,
+
+   /// A small body at the bottom of a long upper shadow after a fall
+   InvertedHammer,
+
+   /// A small body at the bottom of a long upper shadow after a rise
+   ShootingStar,
+
+   /// A body covering the opposite body of the bar before
+   Engulfing,
+
+   /// A body inside the long opposite body of the bar before
+   Harami,
+
+   /// A long falling bar, a small body below it, then a rise into the first bar's body
+   MorningStar,
+
+   /// A long rising bar, a small body above it, then a fall into the first bar's body
+   EveningStar,
+
+   /// Three long rising bars, each opening within the body before and closing higher
+   ThreeWhiteSoldiers,
+
+   /// Three long falling bars, each opening within the body before and closing lower
+   ThreeBlackCrows
+}
+impl Pattern {
+   /// The number of bars in the pattern
+   pub fn bars(&self) -> usize {
+      match self {
+         Self::Doji | Self::Hammer | Self::HangingMan | Self::InvertedHammer | Self::ShootingStar => 1,
+         Self::Engulfing | Self::Harami => 2,
+         Self::MorningStar | Self::EveningStar | Self::ThreeWhiteSoldiers | Self::ThreeBlackCrows => 3
+      }
+   }
+}
+
+/// Which way a pattern suggests the price will move
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+   Bullish,
+   Bearish,
+
+   /// Indecision - either way
+   Neutral
+}
+impl Direction {
+   fn opposite(&self) -> Self {
+      match self {
+         Self::Bullish => Self::Bearish,
+         Self::Bearish => Self::Bullish,
+         Self::Neutral => Self::Neutral
+      }
+   }
+}
+
+/// A pattern found in a series of bars
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PatternEvent {
+   /// The timestamp of the last bar of the pattern
+   pub timestamp: i64,
+
+   /// The timestamp of the first bar of the pattern
+   pub start: i64,
+
+   /// The pattern found
+   pub pattern: Pattern,
+
+   /// Which way the pattern suggests the price will move
+   pub direction: Direction
+}
+impl Timestamped for PatternEvent {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// The thresholds patterns are found with, as fractions of a bar's range unless noted
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PatternConfig {
+   /// The largest body of a doji
+   pub doji_body: f64,
+
+   /// The largest body of a hammer or shooting star - and of the middle bar of a morning or evening
+   /// star and the inside bar of a harami, as a fraction of the long body before
+   pub small_body: f64,
+
+   /// The smallest body of a long bar
+   pub long_body: f64,
+
+   /// The smallest long shadow of a hammer or shooting star, as a multiple of the body
+   pub shadow_ratio: f64,
+
+   /// The largest short shadow of a hammer or shooting star
+   pub short_shadow: f64,
+
+   /// The number of bars the trend before a pattern is measured over
+   pub trend_bars: usize
+}
+impl Default for PatternConfig {
+   fn default() -> Self {
+      Self { doji_body: 0.1, small_body: 0.3, long_body: 0.6, shadow_ratio: 2.0, short_shadow: 0.1, trend_bars: 5 }
+   }
+}
+
+/// Finds patterns in bars as they arrive.
+///
+/// Finished bars are added with `commit`, and the bar still forming can be checked with `update`
+/// as often as it changes without being kept.
+#[derive(Clone, Debug)]
+pub struct PatternScanner {
+   config: PatternConfig,
+
+   /// The latest committed bars, enough for the longest pattern and the trend before it
+   bars: VecDeque<Bar>
+}
+impl PatternScanner {
+   /// Creates a scanner that hasn't seen any bars
+   pub fn new(config: PatternConfig) -> Self { Self { config, bars: VecDeque::new() } }
+
+   /// The thresholds patterns are found with
+   pub fn config(&self) -> &PatternConfig { &self.config }
+
+   /// Adds a finished bar, returning the patterns ending with it
+   pub fn commit(&mut self, bar: &Bar) -> Vec<PatternEvent> {
+      self.bars.push_back(*bar);
+      while self.bars.len() > self.capacity() { self.bars.pop_front(); }
+      find(self.bars.make_contiguous(), &self.config)
+   }
+
+   /// Checks a bar that is still forming, returning the patterns ending with it - the bar isn't kept
+   pub fn update(&mut self, bar: &Bar) -> Vec<PatternEvent> {
+      self.bars.push_back(*bar);
+      let capacity = self.capacity();
+      let bars = self.bars.make_contiguous();
+      let events = find(&bars[bars.len().saturating_sub(capacity)..], &self.config);
+      self.bars.pop_back();
+      events
+   }
+
+   /// The number of bars kept - enough for the longest pattern and the trend before it
+   fn capacity(&self) -> usize { self.config.trend_bars.saturating_add(3) }
+}
+
+#[cfg(feature = "indicators")]
+impl crate::indicators::UpdatableIndicator<Bar> for PatternScanner {
+   type Output = Vec<PatternEvent>;
+
+   fn commit(&mut self, current: &Bar) -> Self::Output { PatternScanner::commit(self, current) }
+
+   fn update(&mut self, current: &Bar) -> Self::Output { PatternScanner::update(self, current) }
+}
+
+/// Finds the patterns in a series of bars sorted by timestamp
+pub fn scan(bars: &[Bar], config: PatternConfig) -> Vec<PatternEvent> {
+   let mut scanner = PatternScanner::new(config);
+   bars.iter().flat_map(|bar| scanner.commit(bar)).collect()
+}
+
+/// The patterns ending with the last of the bars
+fn find(bars: &[Bar], config: &PatternConfig) -> Vec<PatternEvent> {
+   let bar = &bars[bars.len() - 1];
+   let mut events = vec![];
+   let mut found = |pattern: Pattern, direction: Direction| {
+      let start = bars[bars.len() - pattern.bars()].timestamp;
+      events.push(PatternEvent { timestamp: bar.timestamp, start, pattern, direction });
+   };
+
+   let last = Candle::new(bar);
+
+   // a bar without a range has no shape of its own, but can still finish a longer pattern
+   if last.range > 0.0 {
+      if last.body <= config.doji_body * last.range {
+         found(Pattern::Doji, Direction::Neutral);
+      } else if last.body <= config.small_body * last.range {
+         let trend = trend(&bars[..bars.len() - 1], config.trend_bars);
+         if last.lower >= config.shadow_ratio * last.body && last.upper <= config.short_shadow * last.range {
+            match trend {
+               Some(Direction::Bearish) => found(Pattern::Hammer, Direction::Bullish),
+               Some(Direction::Bullish) => found(Pattern::HangingMan, Direction::Bearish),
+               _ => ()
+            }
+         }
+         if last.upper >= config.shadow_ratio * last.body && last.lower <= config.short_shadow * last.range {
+            match trend {
+               Some(Direction::Bearish) => found(Pattern::InvertedHammer, Direction::Bullish),
+               Some(Direction::Bullish) => found(Pattern::ShootingStar, Direction::Bearish),
+               _ => ()
+            }
+         }
+      }
+   }
+
+   if bars.len() >= 2 {
+      let previous = Candle::new(&bars[bars.len() - 2]);
+      if previous.direction() != last.direction() && previous.body > config.doji_body * previous.range {
+         let direction = last.direction();
+         if last.bottom() <= previous.bottom() && last.top() >= previous.top() && last.body > previous.body {
+            found(Pattern::Engulfing, direction);
+         } else if previous.is_long(config) && last.body <= config.small_body * previous.body
+            && last.bottom() >= previous.bottom() && last.top() <= previous.top() {
+            found(Pattern::Harami, previous.direction().opposite());
+         }
+      }
+   }
+
+   if bars.len() >= 3 {
+      let first = Candle::new(&bars[bars.len() - 3]);
+      let second = Candle::new(&bars[bars.len() - 2]);
+      let star = second.body <= config.small_body * first.body;
+      let middle = (first.open + first.close) / 2.0;
+
+      match (first.direction(), last.direction()) {
+         (Direction::Bearish, Direction::Bullish) if first.is_long(config) && star && second.top() <= first.close && last.close > middle => {
+            found(Pattern::MorningStar, Direction::Bullish)
+         },
+         (Direction::Bullish, Direction::Bearish) if first.is_long(config) && star && second.bottom() >= first.close && last.close < middle => {
+            found(Pattern::EveningStar, Direction::Bearish)
+         },
+         _ => ()
+      }
+
+      let candles = [ first, second, last ];
+      let advancing = |direction: Direction| candles.iter().all(|candle| candle.direction() == direction && candle.is_long(config))
+         && candles.windows(2).all(|pair| pair[1].open >= pair[0].bottom() && pair[1].open <= pair[0].top());
+      if advancing(Direction::Bullish) && candles.windows(2).all(|pair| pair[1].close > pair[0].close) {
+         found(Pattern::ThreeWhiteSoldiers, Direction::Bullish);
+      }
+      if advancing(Direction::Bearish) && candles.windows(2).all(|pair| pair[1].close < pair[0].close) {
+         found(Pattern::ThreeBlackCrows, Direction::Bearish);
+      }
+   }
+
+   events
+}
+
+/// The parts of a bar patterns are made of
+#[derive(Clone, Copy, Debug)]
+struct Candle {
+   open: f64,
+   close: f64,
+   body: f64,
+   range: f64,
+   upper: f64,
+   lower: f64
+}
+impl Candle {
+   fn new(bar: &Bar) -> Self {
+      let (top, bottom) = (bar.open.max(bar.close), bar.open.min(bar.close));
+      Self { open: bar.open, close: bar.close, body: top - bottom, range: bar.high - bar.low, upper: bar.high - top, lower: bottom - bar.low }
+   }
+
+   fn top(&self) -> f64 { self.open.max(self.close) }
+
+   fn bottom(&self) -> f64 { self.open.min(self.close) }
+
+   fn direction(&self) -> Direction {
+      if self.close > self.open { Direction::Bullish } else if self.close < self.open { Direction::Bearish } else { Direction::Neutral }
+   }
+
+   fn is_long(&self, config: &PatternConfig) -> bool { self.range > 0.0 && self.body >= config.long_body * self.range }
+}
+
+/// The direction of the closes over a number of bars up to the last - `None` without enough bars
+fn trend(bars: &[Bar], trend_bars: usize) -> Option<Direction> {
+   if trend_bars == 0 || bars.len() <= trend_bars { return None }
+   let (first, last) = (bars[bars.len() - 1 - trend_bars].close, bars[bars.len() - 1].close);
+   if last > first { Some(Direction::Bullish) } else if last < first { Some(Direction::Bearish) } else { None }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn bar(timestamp: i64, open: f64, high: f64, low: f64, close: f64) -> Bar {
+      Bar { timestamp, open, high, low, close, volume: None }
+   }
+
+   /// Long bars moving steadily in a direction, ending at a price
+   fn trend_to(direction: Direction, end: f64) -> Vec<Bar> {
+      let step = if direction == Direction::Bullish { 1.0 } else { -1.0 };
+      (0..6).map(|index| {
+         let close = end - step * (5 - index) as f64;
+         let open = close - step * 0.8;
+         bar(index, open, open.max(close) + 0.1, open.min(close) - 0.1, close)
+      }).collect()
+   }
+
+   fn patterns(bars: &[Bar]) -> Vec<(Pattern, Direction)> {
+      scan(bars, PatternConfig::default()).iter().map(|event| (event.pattern, event.direction)).collect()
+   }
+
+   fn last_patterns(bars: &[Bar]) -> Vec<(Pattern, Direction)> {
+      let events = scan(bars, PatternConfig::default());
+      let last = bars[bars.len() - 1].timestamp;
+      events.iter().filter(|event| event.timestamp == last).map(|event| (event.pattern, event.direction)).collect()
+   }
+
+   #[test]
+   fn verify_single_bars() {
+      assert_eq!(vec![ (Pattern::Doji, Direction::Neutral) ], patterns(&[ bar(0, 10.0, 11.0, 9.0, 10.05) ]));
+
+      // no trend before a hammer shaped candle, so it isn't a hammer
+      let hammer = |timestamp: i64, price: f64| bar(timestamp, price, price + 0.55, price - 2.0, price + 0.5);
+      assert!(patterns(&[ hammer(0, 10.0) ]).is_empty());
+
+      let mut bars = trend_to(Direction::Bearish, 10.0);
+      bars.push(hammer(6, 9.5));
+      assert_eq!(vec![ (Pattern::Hammer, Direction::Bullish) ], last_patterns(&bars));
+
+      let mut bars = trend_to(Direction::Bullish, 10.0);
+      bars.push(hammer(6, 10.5));
+      assert_eq!(vec![ (Pattern::HangingMan, Direction::Bearish) ], last_patterns(&bars));
+
+      let star = |timestamp: i64, price: f64| bar(timestamp, price, price + 2.0, price - 0.05, price - 0.5);
+      let mut bars = trend_to(Direction::Bullish, 10.0);
+      bars.push(star(6, 10.5));
+      assert_eq!(vec![ (Pattern::ShootingStar, Direction::Bearish) ], last_patterns(&bars));
+
+      let mut bars = trend_to(Direction::Bearish, 10.0);
+      bars.push(star(6, 10.0));
+      assert_eq!(vec![ (Pattern::InvertedHammer, Direction::Bullish) ], last_patterns(&bars));
+   }
+
+   #[test]
+   fn verify_two_bars() {
+      let engulfing = [ bar(0, 10.0, 10.1, 8.9, 9.0), bar(1, 8.8, 10.4, 8.7, 10.3) ];
+      assert_eq!(vec![ (Pattern::Engulfing, Direction::Bullish) ], last_patterns(&engulfing));
+      let engulfing = [ bar(0, 9.0, 10.1, 8.9, 10.0), bar(1, 10.2, 10.3, 8.7, 8.8) ];
+      assert_eq!(vec![ (Pattern::Engulfing, Direction::Bearish) ], last_patterns(&engulfing));
+
+      let harami = [ bar(0, 12.0, 12.1, 9.9, 10.0), bar(1, 10.5, 11.2, 10.4, 10.9) ];
+      assert_eq!(vec![ (Pattern::Harami, Direction::Bullish) ], last_patterns(&harami));
+      let harami = [ bar(0, 10.0, 12.1, 9.9, 12.0), bar(1, 11.5, 11.6, 10.8, 11.1) ];
+      assert_eq!(vec![ (Pattern::Harami, Direction::Bearish) ], last_patterns(&harami));
+
+      // the same colour twice isn't either
+      assert!(last_patterns(&[ bar(0, 12.0, 12.1, 9.9, 10.0), bar(1, 10.9, 11.2, 10.4, 10.5) ]).is_empty());
+
+      // a flat bar has no shape of its own but still sits inside the body before
+      let flat = [ bar(0, 12.0, 12.1, 9.9, 10.0), bar(1, 11.0, 11.0, 11.0, 11.0) ];
+      assert_eq!(vec![ (Pattern::Harami, Direction::Bullish) ], last_patterns(&flat));
+   }
+
+   #[test]
+   fn verify_three_bars() {
+      let morning = [ bar(0, 12.0, 12.1, 9.9, 10.0), bar(1, 9.7, 9.9, 9.4, 9.6), bar(2, 9.8, 11.6, 9.7, 11.5) ];
+      let events = scan(&morning, PatternConfig::default());
+      assert!(events.contains(&PatternEvent { timestamp: 2, start: 0, pattern: Pattern::MorningStar, direction: Direction::Bullish }));
+
+      let evening = [ bar(0, 10.0, 12.1, 9.9, 12.0), bar(1, 12.3, 12.6, 12.1, 12.4), bar(2, 12.2, 12.3, 10.4, 10.5) ];
+      assert!(last_patterns(&evening).contains(&(Pattern::EveningStar, Direction::Bearish)));
+
+      let soldiers = [ bar(0, 10.0, 11.1, 9.9, 11.0), bar(1, 10.8, 12.1, 10.7, 12.0), bar(2, 11.8, 13.1, 11.7, 13.0) ];
+      assert_eq!(vec![ (Pattern::ThreeWhiteSoldiers, Direction::Bullish) ], last_patterns(&soldiers));
+
+      let crows = [ bar(0, 13.0, 13.1, 11.9, 12.0), bar(1, 12.2, 12.3, 10.9, 11.0), bar(2, 11.2, 11.3, 9.9, 10.0) ];
+      assert_eq!(vec![ (Pattern::ThreeBlackCrows, Direction::Bearish) ], last_patterns(&crows));
+
+      // opening above the body before breaks the pattern
+      let gapped = [ bar(0, 10.0, 11.1, 9.9, 11.0), bar(1, 11.2, 12.1, 11.1, 12.0), bar(2, 11.8, 13.1, 11.7, 13.0) ];
+      assert!(!last_patterns(&gapped).contains(&(Pattern::ThreeWhiteSoldiers, Direction::Bullish)));
+   }
+
+   #[test]
+   fn verify_streaming() {
+      let mut bars = trend_to(Direction::Bearish, 10.0);
+      bars.push(bar(6, 9.5, 10.05, 7.5, 10.0));
+
+      // each bar gives the patterns ending with it - the falling bars open below the body before, so aren't crows
+      let mut scanner = PatternScanner::new(PatternConfig::default());
+      let streamed: Vec<Vec<PatternEvent>> = bars.iter().map(|bar| scanner.commit(bar)).collect();
+      let hammer = PatternEvent { timestamp: 6, start: 6, pattern: Pattern::Hammer, direction: Direction::Bullish };
+      assert_eq!(vec![ vec![], vec![], vec![], vec![], vec![], vec![], vec![ hammer ] ], streamed);
+
+      // a stricter shadow no longer counts the last bar as a hammer
+      let strict = PatternConfig { shadow_ratio: 5.0, ..PatternConfig::default() };
+      assert!(scan(&bars, strict).iter().all(|event| event.pattern != Pattern::Hammer));
+      assert!(scan(&bars, PatternConfig::default()).iter().any(|event| event.pattern == Pattern::Hammer && event.timestamp == 6));
+   }
+
+   #[test]
+   fn verify_live_bars() {
+      let mut live = PatternScanner::new(PatternConfig::default());
+      let trend = trend_to(Direction::Bearish, 10.0);
+      for bar in trend.iter() { live.commit(bar); }
+
+      // the forming bar is a doji, then a hammer, without either being kept
+      let doji = PatternEvent { timestamp: 6, start: 6, pattern: Pattern::Doji, direction: Direction::Neutral };
+      let hammer = PatternEvent { timestamp: 6, start: 6, pattern: Pattern::Hammer, direction: Direction::Bullish };
+      assert_eq!(vec![ doji ], live.update(&bar(6, 9.5, 9.6, 9.4, 9.5)));
+      assert_eq!(vec![ hammer ], live.update(&bar(6, 9.5, 10.05, 7.5, 10.0)));
+      assert_eq!(vec![ hammer ], live.commit(&bar(6, 9.5, 10.05, 7.5, 10.0)));
+
+      // the next bar sees the same history as a scan of the finished bars
+      let mut bars = trend;
+      bars.push(bar(6, 9.5, 10.05, 7.5, 10.0));
+      bars.push(bar(7, 10.1, 10.2, 9.3, 9.4));
+      assert!(last_patterns(&bars).contains(&(Pattern::Engulfing, Direction::Bearish)));
+      assert_eq!(last_patterns(&bars), live.update(&bars[7]).iter().map(|event| (event.pattern, event.direction)).collect::<Vec<_>>());
+
+      // a trend longer than memory doesn't overflow
+      let mut long = PatternScanner::new(PatternConfig { trend_bars: usize::MAX, ..PatternConfig::default() });
+      assert_eq!(vec![ doji ], long.commit(&bar(6, 9.5, 9.6, 9.4, 9.5)));
+   }
+}
\ No newline at end of file
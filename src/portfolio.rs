@@ -0,0 +1,377 @@
+//! Portfolio accounting across symbols and cash currencies.
+//!
+//! Holdings are made of tax lots, closed in the order given by the portfolio's [`LotMethod`].  Cash is
+//! held per currency and valued in the base currency using the rates set on the portfolio.  All of
+//! the state is kept in ordered maps so that the same history always produces the same portfolio,
+//! and with the `serialization` feature it can be serialized with serde.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "serialization")]
+use serde::{ Deserialize, Serialize };
+use crate::Quote;
+use crate::adjustments::{ Dividend, Split };
+use crate::metrics::EquityPoint;
+use crate::orders::Fill;
+
+/// The number of milliseconds in a day
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1_000;
+
+/// Which lots are closed first when a holding is reduced
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+pub enum LotMethod {
+   /// First in, first out
+   Fifo,
+
+   /// Last in, first out
+   Lifo,
+
+   /// A single lot at the average cost of everything opened
+   AverageCost
+}
+
+/// A quantity opened at a single price
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+pub struct Lot {
+   /// The timestamp the lot was opened in millisecond accuracy
+   pub timestamp: i64,
+
+   /// The quantity still open - negative for short lots
+   pub quantity: f64,
+
+   /// The price paid for each unit
+   pub price: f64
+}
+
+/// The open lots of a single symbol
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+pub struct Holding {
+   /// The currency the symbol trades in
+   pub currency: String,
+
+   /// The open lots in the order they were opened
+   pub lots: Vec<Lot>,
+
+   /// The profit or loss from closed lots, before commission
+   pub realized_pnl: f64,
+
+   /// The latest price the holding was marked at
+   pub price: f64
+}
+impl Holding {
+   /// The total quantity held - negative when short
+   pub fn quantity(&self) -> f64 { self.lots.iter().map(|lot| lot.quantity).sum() }
+
+   /// The amount paid for the open lots
+   pub fn cost(&self) -> f64 { self.lots.iter().map(|lot| lot.quantity * lot.price).sum() }
+
+   /// The average price of the open lots - 0 when nothing is held
+   pub fn average_price(&self) -> f64 {
+      let quantity = self.quantity();
+      if quantity == 0.0 { 0.0 } else { self.cost() / quantity }
+   }
+
+   /// The value of the holding at the latest price, in its currency
+   pub fn market_value(&self) -> f64 { self.quantity() * self.price }
+
+   /// The profit or loss of the open lots at the latest price, in its currency
+   pub fn unrealized_pnl(&self) -> f64 { self.market_value() - self.cost() }
+}
+
+/// The state of a portfolio at the end of a day
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+pub struct Snapshot {
+   /// The timestamp of the snapshot in millisecond accuracy
+   pub timestamp: i64,
+
+   /// The cash held in each currency
+   pub cash: BTreeMap<String, f64>,
+
+   /// The quantity held and the latest price of each symbol
+   pub holdings: BTreeMap<String, (f64, f64)>,
+
+   /// The total value in the base currency - `None` if a currency with a value had no rate
+   pub equity: Option<f64>
+}
+
+/// Holdings and cash across symbols and currencies
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Deserialize, Serialize))]
+pub struct Portfolio {
+   base_currency: String,
+   method: LotMethod,
+
+   /// The value of one unit of each currency in the base currency
+   rates: BTreeMap<String, f64>,
+
+   cash: BTreeMap<String, f64>,
+   holdings: BTreeMap<String, Holding>,
+   snapshots: Vec<Snapshot>
+}
+impl Portfolio {
+   /// Creates an empty portfolio valued in a base currency
+   pub fn new(base_currency: &str, method: LotMethod) -> Self {
+      Self {
+         base_currency: base_currency.to_string(),
+         method,
+         rates: BTreeMap::new(),
+         cash: BTreeMap::new(),
+         holdings: BTreeMap::new(),
+         snapshots: vec![]
+      }
+   }
+
+   /// The currency the portfolio is valued in
+   pub fn base_currency(&self) -> &str { &self.base_currency }
+
+   /// The cash held in a currency
+   pub fn cash(&self, currency: &str) -> f64 { self.cash.get(currency).copied().unwrap_or(0.0) }
+
+   /// The cash held in every currency
+   pub fn cash_balances(&self) -> &BTreeMap<String, f64> { &self.cash }
+
+   /// The holding of a symbol - `None` if it has never been traded
+   pub fn holding(&self, symbol: &str) -> Option<&Holding> { self.holdings.get(symbol) }
+
+   /// Every holding by symbol, including those that have been closed
+   pub fn holdings(&self) -> &BTreeMap<String, Holding> { &self.holdings }
+
+   /// The snapshots taken so far
+   pub fn snapshots(&self) -> &[Snapshot] { &self.snapshots }
+
+   /// Sets the value of one unit of a currency in the base currency
+   pub fn set_rate(&mut self, currency: &str, rate: f64) { self.rates.insert(currency.to_string(), rate); }
+
+   /// The value of one unit of a currency in the base currency - `None` if no rate has been set
+   pub fn rate(&self, currency: &str) -> Option<f64> {
+      if currency == self.base_currency { Some(1.0) } else { self.rates.get(currency).copied() }
+   }
+
+   /// Adds cash in a currency - negative amounts are withdrawals
+   pub fn deposit(&mut self, currency: &str, amount: f64) { *self.cash.entry(currency.to_string()).or_insert(0.0) += amount }
+
+   /// Applies a fill of a symbol traded in a currency, returning the profit or loss it realized
+   pub fn apply_fill(&mut self, symbol: &str, currency: &str, fill: &Fill) -> f64 {
+      self.deposit(currency, -(fill.signed_quantity() * fill.price + fill.commission));
+
+      let method = self.method;
+      let holding = self.holdings.entry(symbol.to_string()).or_insert_with(|| Holding {
+         currency: currency.to_string(), lots: vec![], realized_pnl: 0.0, price: fill.price
+      });
+      holding.price = fill.price;
+
+      let mut remaining = fill.signed_quantity();
+      let mut realized = 0.0;
+      while remaining != 0.0 {
+         let index = match method {
+            LotMethod::Lifo => holding.lots.len().checked_sub(1),
+            _ => if holding.lots.is_empty() { None } else { Some(0) }
+         };
+         let lot = match index.map(|index| &mut holding.lots[index]) {
+            Some(lot) if lot.quantity.signum() != remaining.signum() => lot,
+            _ => break
+         };
+
+         let closed = remaining.abs().min(lot.quantity.abs()) * lot.quantity.signum();
+         realized += closed * (fill.price - lot.price);
+         lot.quantity -= closed;
+         remaining += closed;
+         if lot.quantity == 0.0 { holding.lots.remove(index.unwrap_or(0)); }
+      }
+
+      if remaining != 0.0 {
+         let lot = Lot { timestamp: fill.timestamp, quantity: remaining, price: fill.price };
+         match holding.lots.first_mut() {
+            Some(first) if method == LotMethod::AverageCost => {
+               first.price = (first.quantity * first.price + lot.quantity * lot.price) / (first.quantity + lot.quantity);
+               first.quantity += lot.quantity;
+            },
+            _ => holding.lots.push(lot)
+         }
+      }
+
+      holding.realized_pnl += realized;
+      realized
+   }
+
+   /// Marks a symbol to the price of a quote - ignored for symbols that aren't held
+   pub fn mark(&mut self, quote: &Quote) {
+      if let Some(holding) = self.holdings.get_mut(&quote.symbol) { holding.price = quote.price }
+   }
+
+   /// Pays a cash dividend on the quantity of a symbol held, returning the amount paid
+   pub fn apply_dividend(&mut self, symbol: &str, dividend: &Dividend) -> f64 {
+      let (currency, amount) = match self.holdings.get(symbol) {
+         Some(holding) => (holding.currency.clone(), holding.quantity() * dividend.amount),
+         None => return 0.0
+      };
+      self.deposit(&currency, amount);
+      amount
+   }
+
+   /// Splits the lots of a symbol, keeping the cost of each lot the same
+   pub fn apply_split(&mut self, symbol: &str, split: &Split) {
+      if let Some(holding) = self.holdings.get_mut(symbol) {
+         let ratio = split.ratio();
+         for lot in holding.lots.iter_mut() {
+            lot.quantity *= ratio;
+            lot.price /= ratio;
+         }
+         holding.price /= ratio;
+      }
+   }
+
+   /// The value of the cash and holdings in each currency
+   pub fn values(&self) -> BTreeMap<String, f64> {
+      let mut values = self.cash.clone();
+      for holding in self.holdings.values() {
+         *values.entry(holding.currency.clone()).or_insert(0.0) += holding.market_value();
+      }
+      values
+   }
+
+   /// The total value in the base currency - `None` if a currency with a value has no rate
+   pub fn equity(&self) -> Option<f64> {
+      self.values().iter()
+         .filter(|(_, value)| **value != 0.0)
+         .map(|(currency, value)| self.rate(currency).map(|rate| value * rate))
+         .sum()
+   }
+
+   /// Records the state of the portfolio, replacing any snapshot already taken on the same (UTC) day
+   pub fn snapshot(&mut self, timestamp: i64) -> &Snapshot {
+      let snapshot = Snapshot {
+         timestamp,
+         cash: self.cash.clone(),
+         holdings: self.holdings.iter().map(|(symbol, holding)| (symbol.clone(), (holding.quantity(), holding.price))).collect(),
+         equity: self.equity()
+      };
+
+      let day = timestamp.div_euclid(MILLIS_PER_DAY);
+      match self.snapshots.last_mut() {
+         Some(last) if last.timestamp.div_euclid(MILLIS_PER_DAY) == day => *last = snapshot,
+         _ => self.snapshots.push(snapshot)
+      }
+      &self.snapshots[self.snapshots.len() - 1]
+   }
+
+   /// The equity of each snapshot, skipping any that couldn't be valued
+   pub fn equity_curve(&self) -> Vec<EquityPoint> {
+      self.snapshots.iter().filter_map(|snapshot| snapshot.equity.map(|value| EquityPoint { timestamp: snapshot.timestamp, value })).collect()
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::TradingSession;
+   use crate::orders::Side;
+
+   fn fill(timestamp: i64, side: Side, quantity: f64, price: f64) -> Fill {
+      Fill { timestamp, side, quantity, price, commission: 0.0 }
+   }
+
+   fn quote(symbol: &str, price: f64) -> Quote {
+      Quote { symbol: symbol.to_string(), timestamp: 0, session: TradingSession::Regular, price, volume: 0 }
+   }
+
+   fn bought(method: LotMethod) -> Portfolio {
+      let mut portfolio = Portfolio::new("USD", method);
+      portfolio.deposit("USD", 10_000.0);
+      portfolio.apply_fill("ABC", "USD", &fill(1, Side::Buy, 10.0, 100.0));
+      portfolio.apply_fill("ABC", "USD", &fill(2, Side::Buy, 10.0, 120.0));
+      portfolio
+   }
+
+   #[test]
+   fn verify_lot_methods() {
+      let mut fifo = bought(LotMethod::Fifo);
+      assert_eq!(50.0, fifo.apply_fill("ABC", "USD", &fill(3, Side::Sell, 15.0, 110.0)));
+      assert_eq!(vec![ Lot { timestamp: 2, quantity: 5.0, price: 120.0 } ], fifo.holding("ABC").unwrap().lots);
+
+      let mut lifo = bought(LotMethod::Lifo);
+      assert_eq!(-50.0, lifo.apply_fill("ABC", "USD", &fill(3, Side::Sell, 15.0, 110.0)));
+      assert_eq!(vec![ Lot { timestamp: 1, quantity: 5.0, price: 100.0 } ], lifo.holding("ABC").unwrap().lots);
+
+      let mut average = bought(LotMethod::AverageCost);
+      assert_eq!(0.0, average.apply_fill("ABC", "USD", &fill(3, Side::Sell, 15.0, 110.0)));
+      assert_eq!(vec![ Lot { timestamp: 1, quantity: 5.0, price: 110.0 } ], average.holding("ABC").unwrap().lots);
+
+      // selling through the holding opens a short lot
+      assert_eq!(0.0, average.apply_fill("ABC", "USD", &fill(4, Side::Sell, 7.0, 110.0)));
+      assert_eq!(-2.0, average.holding("ABC").unwrap().quantity());
+      assert_eq!(Some(10_000.0), average.equity());
+   }
+
+   #[test]
+   fn verify_corporate_actions() {
+      let mut portfolio = bought(LotMethod::Fifo);
+      portfolio.apply_split("ABC", &Split { timestamp: 3, numerator: 2.0, denominator: 1.0 });
+      let holding = portfolio.holding("ABC").unwrap();
+      assert_eq!(40.0, holding.quantity());
+      assert_eq!(2_200.0, holding.cost());
+      assert_eq!(Lot { timestamp: 1, quantity: 20.0, price: 50.0 }, holding.lots[0]);
+
+      assert_eq!(20.0, portfolio.apply_dividend("ABC", &Dividend { timestamp: 4, amount: 0.5 }));
+      assert_eq!(0.0, portfolio.apply_dividend("XYZ", &Dividend { timestamp: 4, amount: 0.5 }));
+      assert_eq!(7_820.0, portfolio.cash("USD"));
+   }
+
+   #[test]
+   fn verify_multi_currency_equity() {
+      let mut portfolio = bought(LotMethod::Fifo);
+      portfolio.deposit("EUR", 1_000.0);
+      portfolio.apply_fill("SAP", "EUR", &fill(3, Side::Buy, 5.0, 100.0));
+      assert_eq!(None, portfolio.equity());
+
+      portfolio.set_rate("EUR", 1.25);
+      portfolio.mark(&quote("ABC", 130.0));
+      portfolio.mark(&quote("SAP", 110.0));
+      assert_eq!(Some(400.0), portfolio.holding("ABC").map(|holding| holding.unrealized_pnl()));
+      assert_eq!(1_050.0, portfolio.values()["EUR"]);
+      assert_eq!(Some(10_400.0 + 1_312.5), portfolio.equity());
+   }
+
+   #[test]
+   fn verify_snapshots() {
+      let day = MILLIS_PER_DAY;
+      let mut portfolio = bought(LotMethod::Fifo);
+      portfolio.snapshot(day);
+      portfolio.mark(&quote("ABC", 130.0));
+      portfolio.snapshot(day + 1_000);
+      portfolio.mark(&quote("ABC", 90.0));
+      portfolio.snapshot(2 * day);
+
+      let curve = portfolio.equity_curve();
+      assert_eq!(vec![ EquityPoint { timestamp: day + 1_000, value: 10_400.0 }, EquityPoint { timestamp: 2 * day, value: 9_600.0 } ], curve);
+      assert_eq!(Some(&(20.0, 90.0)), portfolio.snapshots()[1].holdings.get("ABC"));
+   }
+
+   #[cfg(feature = "serialization")]
+   #[test]
+   fn verify_serialization() {
+      let mut portfolio = bought(LotMethod::Lifo);
+      portfolio.snapshot(0);
+
+      let json = serde_json::to_string(&portfolio).unwrap();
+      assert_eq!(portfolio, serde_json::from_str(&json).unwrap());
+      assert_eq!(json, serde_json::to_string(&portfolio.clone()).unwrap());
+   }
+
+   #[cfg(feature = "serialization")]
+   #[test]
+   fn verify_serialization_with_missing_rate() {
+      let mut portfolio = bought(LotMethod::Fifo);
+      portfolio.deposit("EUR", 1_000.0);
+      assert_eq!(None, portfolio.snapshot(0).equity);
+
+      let json = serde_json::to_string(&portfolio).unwrap();
+      let restored: Portfolio = serde_json::from_str(&json).unwrap();
+      assert_eq!(portfolio, restored);
+      assert!(restored.equity_curve().is_empty());
+   }
+}
\ No newline at end of file
@@ -1,7 +1,7 @@
 use super::Timestamped;
 
 /// The trading session where a quote has occurred
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TradingSession {
    /// The period of trading before the regular market session
    PreMarket,
@@ -43,13 +43,13 @@ impl Timestamped for Quote {
 #[cfg(test)]
 mod tests {
    #[cfg(feature = "formatting")]
-   use chrono::{ TimeZone, Utc };
+   use chrono::{ NaiveDate, TimeZone, Utc };
    use super::{ Quote, Timestamped, TradingSession };
 
    #[cfg(feature = "formatting")]
    #[test]
    fn verify_datetime() {
-      let dt = Utc.ymd(1970, 1, 1).and_hms_milli(0, 0, 3, 123);
+      let dt = Utc.from_utc_datetime(&NaiveDate::from_ymd_opt(1970, 1, 1).unwrap().and_hms_milli_opt(0, 0, 3, 123).unwrap());
       let quote = Quote { symbol: "none".to_string(), timestamp: dt.timestamp_millis(), session: TradingSession::Other, price: 0.1, volume: 0 };
       assert_eq!(dt, quote.datetime())
    }
@@ -0,0 +1,169 @@
+//! Return series and annualization.
+//!
+//! Returns are fractions - a 5% gain is `0.05`.
+
+use crate::{ Bar, Interval };
+
+/// The number of trading days in a year
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// The number of minutes in a US equity regular trading session
+pub const US_SESSION_MINUTES: u32 = 390;
+
+/// Gets the closes of a series of bars
+pub fn closes(bars: &[Bar]) -> Vec<f64> { bars.iter().map(|bar| bar.close).collect() }
+
+/// Calculates the simple returns between consecutive values - one less than the number of values
+pub fn simple_returns(values: &[f64]) -> Vec<f64> {
+   values.windows(2).map(|pair| pair[1] / pair[0] - 1.0).collect()
+}
+
+/// Calculates the log returns between consecutive values - one less than the number of values
+pub fn log_returns(values: &[f64]) -> Vec<f64> {
+   values.windows(2).map(|pair| (pair[1] / pair[0]).ln()).collect()
+}
+
+/// Calculates the simple returns between the closes of consecutive bars
+pub fn bar_returns(bars: &[Bar]) -> Vec<f64> { simple_returns(&closes(bars)) }
+
+/// Calculates the returns in excess of a risk free rate for the same period
+pub fn excess_returns(returns: &[f64], risk_free: f64) -> Vec<f64> {
+   returns.iter().map(|value| value - risk_free).collect()
+}
+
+/// Compounds a series of returns into a single return for the whole period
+pub fn compound(returns: &[f64]) -> f64 {
+   returns.iter().fold(1.0, |total, value| total * (1.0 + value)) - 1.0
+}
+
+/// Calculates the compounded return from the start of the series up to each return
+pub fn cumulative_returns(returns: &[f64]) -> Vec<f64> {
+   let mut total = 1.0;
+   returns.iter().map(|value| { total *= 1.0 + value; total - 1.0 }).collect()
+}
+
+/// Builds the value of an investment over time from its returns.
+///
+/// The curve starts with the initial value, so it's one longer than the returns.
+pub fn equity_curve(returns: &[f64], initial: f64) -> Vec<f64> {
+   let mut curve = Vec::with_capacity(returns.len() + 1);
+   curve.push(initial);
+   curve.extend(cumulative_returns(returns).into_iter().map(|value| initial * (1.0 + value)));
+   curve
+}
+
+/// Rescales a series so that it starts at the given base - often 100
+pub fn rebase(values: &[f64], base: f64) -> Vec<f64> {
+   match values.first() {
+      Some(first) => values.iter().map(|value| value / first * base).collect(),
+      None => vec![]
+   }
+}
+
+/// The number of bars of an interval in a year, assuming a US equity session for intraday intervals.
+///
+/// Returns `None` for intervals without a fixed length - `_ytd` and `_max`.
+pub fn periods_per_year(interval: Interval) -> Option<f64> {
+   intraday_periods_per_year(interval, US_SESSION_MINUTES)
+}
+
+/// The number of bars of an interval in a year, with intraday intervals based on the given session length.
+///
+/// Returns `None` for intervals without a fixed length - `_ytd` and `_max`.
+pub fn intraday_periods_per_year(interval: Interval, session_minutes: u32) -> Option<f64> {
+   if let Some(minutes) = interval.minutes() {
+      let bars_per_session = (f64::from(session_minutes) / f64::from(minutes)).ceil();
+      return Some(bars_per_session * TRADING_DAYS_PER_YEAR)
+   }
+
+   match interval {
+      Interval::_1d => Some(TRADING_DAYS_PER_YEAR),
+      Interval::_5d => Some(52.0),
+      Interval::_1mo => Some(12.0),
+      Interval::_3mo => Some(4.0),
+      Interval::_6mo => Some(2.0),
+      Interval::_1y => Some(1.0),
+      Interval::_2y => Some(0.5),
+      Interval::_5y => Some(0.2),
+      Interval::_10y => Some(0.1),
+      _ => None
+   }
+}
+
+/// Converts a rate for a single period into an annual rate
+pub fn annualize_rate(rate: f64, periods_per_year: f64) -> f64 {
+   (1.0 + rate).powf(periods_per_year) - 1.0
+}
+
+/// Converts an annual rate into the rate for a single period - e.g. a risk free rate for excess returns
+pub fn deannualize_rate(rate: f64, periods_per_year: f64) -> f64 {
+   (1.0 + rate).powf(1.0 / periods_per_year) - 1.0
+}
+
+/// Calculates the compound annual return of a series of returns
+pub fn annualized_return(returns: &[f64], periods_per_year: f64) -> f64 {
+   if returns.is_empty() { return 0.0 }
+   (1.0 + compound(returns)).powf(periods_per_year / returns.len() as f64) - 1.0
+}
+
+/// Scales the standard deviation of returns for a single period to a year
+pub fn annualize_volatility(stdev: f64, periods_per_year: f64) -> f64 {
+   stdev * periods_per_year.sqrt()
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn assert_close(expected: f64, actual: f64) {
+      assert!((expected - actual).abs() < 1e-10, "expected {} but was {}", expected, actual);
+   }
+
+   #[test]
+   fn verify_returns() {
+      let values = [ 100.0, 110.0, 99.0 ];
+
+      let simple = simple_returns(&values);
+      assert_close(0.1, simple[0]);
+      assert_close(-0.1, simple[1]);
+
+      let log = log_returns(&values);
+      assert_close(1.1_f64.ln(), log[0]);
+
+      let excess = excess_returns(&simple, 0.01);
+      assert_close(0.09, excess[0]);
+   }
+
+   #[test]
+   fn verify_cumulative() {
+      let returns = [ 0.1, -0.1, 0.5 ];
+
+      assert_close(0.485, compound(&returns));
+      let cumulative = cumulative_returns(&returns);
+      assert_close(-0.01, cumulative[1]);
+      assert_close(0.485, cumulative[2]);
+
+      let curve = equity_curve(&returns, 1000.0);
+      assert_eq!(4, curve.len());
+      assert_close(1485.0, curve[3]);
+
+      assert_eq!(vec![ 100.0, 50.0, 150.0 ], rebase(&[ 50.0, 25.0, 75.0 ], 100.0));
+   }
+
+   #[test]
+   fn verify_periods_per_year() {
+      assert_eq!(Some(252.0), periods_per_year(Interval::_1d));
+      assert_eq!(Some(12.0), periods_per_year(Interval::_1mo));
+      assert_eq!(Some(7.0 * 252.0), periods_per_year(Interval::_60m));
+      assert_eq!(Some(17.0 * 252.0), intraday_periods_per_year(Interval::_30m, 510));
+      assert_eq!(None, periods_per_year(Interval::_max));
+   }
+
+   #[test]
+   fn verify_annualization() {
+      assert_close(0.1, annualized_return(&[ 0.21 ], 0.5));
+      assert_close(0.01, deannualize_rate(annualize_rate(0.01, 12.0), 12.0));
+      assert_close(0.16, annualize_volatility(0.01, 256.0));
+   }
+}
\ No newline at end of file
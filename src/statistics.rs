@@ -0,0 +1,234 @@
+//! Statistics across multiple series - alignment, beta, correlation and linear regression.
+
+use std::collections::HashSet;
+use crate::Bar;
+use crate::math::mean;
+use crate::returns::bar_returns;
+
+/// How correlation between series is measured
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CorrelationMethod {
+   /// Linear correlation of the values
+   Pearson,
+
+   /// Linear correlation of the ranks of the values - captures any monotonic relationship
+   Spearman
+}
+
+/// An ordinary least squares fit of `y = intercept + slope * x`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinearFit {
+   /// The change in y for each unit of x
+   pub slope: f64,
+
+   /// The value of y where x is 0
+   pub intercept: f64,
+
+   /// The fraction of the variance of y explained by the fit
+   pub r_squared: f64,
+
+   /// The standard error of the slope
+   pub slope_error: f64,
+
+   /// The standard error of the intercept
+   pub intercept_error: f64,
+
+   /// The standard deviation of the residuals
+   pub residual_stdev: f64
+}
+impl LinearFit {
+   /// Gets the fitted value of y for an x
+   pub fn predict(&self, x: f64) -> f64 { self.intercept + self.slope * x }
+}
+
+/// Aligns series of bars so that they only contain the timestamps that are in every series.
+///
+/// Each series is expected to be sorted by timestamp, and the results are in the same order as given.
+pub fn align(series: &[&[Bar]]) -> Vec<Vec<Bar>> {
+   let mut common: Option<HashSet<i64>> = None;
+   for bars in series {
+      let timestamps: HashSet<i64> = bars.iter().map(|bar| bar.timestamp).collect();
+      common = Some(match common {
+         Some(common) => common.intersection(&timestamps).copied().collect(),
+         None => timestamps
+      });
+   }
+
+   let common = common.unwrap_or_default();
+   series.iter().map(|bars| bars.iter().filter(|bar| common.contains(&bar.timestamp)).copied().collect()).collect()
+}
+
+/// Fits a straight line through pairs of values - `None` if there are fewer than 2 pairs or x doesn't vary
+pub fn linear_regression(x: &[f64], y: &[f64]) -> Option<LinearFit> {
+   let count = x.len().min(y.len());
+   if count < 2 { return None }
+
+   let (x, y) = (&x[..count], &y[..count]);
+   let (mean_x, mean_y) = (mean(x), mean(y));
+   let sxx: f64 = x.iter().map(|value| (value - mean_x).powi(2)).sum();
+   let syy: f64 = y.iter().map(|value| (value - mean_y).powi(2)).sum();
+   let sxy: f64 = x.iter().zip(y).map(|(a, b)| (a - mean_x) * (b - mean_y)).sum();
+   if sxx == 0.0 { return None }
+
+   let slope = sxy / sxx;
+   let intercept = mean_y - slope * mean_x;
+   let residuals: f64 = x.iter().zip(y).map(|(a, b)| (b - intercept - slope * a).powi(2)).sum();
+   let residual_variance = if count > 2 { residuals / (count - 2) as f64 } else { 0.0 };
+
+   Some(LinearFit {
+      slope,
+      intercept,
+      r_squared: if syy == 0.0 { 1.0 } else { 1.0 - residuals / syy },
+      slope_error: (residual_variance / sxx).sqrt(),
+      intercept_error: (residual_variance * (1.0 / count as f64 + mean_x * mean_x / sxx)).sqrt(),
+      residual_stdev: residual_variance.sqrt()
+   })
+}
+
+/// The beta of an asset's returns against a benchmark's returns for the same periods
+pub fn beta(returns: &[f64], benchmark: &[f64]) -> f64 {
+   linear_regression(benchmark, returns).map_or(f64::NAN, |fit| fit.slope)
+}
+
+/// The beta of an asset against a benchmark, using the close to close returns of the bars they share
+pub fn bar_beta(bars: &[Bar], benchmark: &[Bar]) -> f64 {
+   let aligned = align(&[ bars, benchmark ]);
+   beta(&bar_returns(&aligned[0]), &bar_returns(&aligned[1]))
+}
+
+/// The beta over each window of returns - the first value is for the first full window
+pub fn rolling_beta(returns: &[f64], benchmark: &[f64], period: usize) -> Vec<f64> {
+   let count = returns.len().min(benchmark.len());
+   if period == 0 || count < period { return vec![] }
+
+   (period..=count).map(|end| beta(&returns[end - period..end], &benchmark[end - period..end])).collect()
+}
+
+/// The correlation between two series of the same length
+pub fn correlation(x: &[f64], y: &[f64], method: CorrelationMethod) -> f64 {
+   match method {
+      CorrelationMethod::Pearson => pearson(x, y),
+      CorrelationMethod::Spearman => pearson(&ranks(x), &ranks(y))
+   }
+}
+
+/// The correlation between every pair of series - the diagonal is always 1
+pub fn correlation_matrix(series: &[Vec<f64>], method: CorrelationMethod) -> Vec<Vec<f64>> {
+   let ranked: Vec<Vec<f64>> = match method {
+      CorrelationMethod::Pearson => series.to_vec(),
+      CorrelationMethod::Spearman => series.iter().map(|values| ranks(values)).collect()
+   };
+
+   let mut matrix = vec![ vec![ 1.0; series.len() ]; series.len() ];
+   for row in 0..series.len() {
+      for column in row + 1..series.len() {
+         let value = pearson(&ranked[row], &ranked[column]);
+         matrix[row][column] = value;
+         matrix[column][row] = value;
+      }
+   }
+
+   matrix
+}
+
+/// The correlation between the returns of every pair of bar series, using only the timestamps they all share
+pub fn bar_correlation_matrix(series: &[&[Bar]], method: CorrelationMethod) -> Vec<Vec<f64>> {
+   let returns: Vec<Vec<f64>> = align(series).iter().map(|bars| bar_returns(bars)).collect();
+   correlation_matrix(&returns, method)
+}
+
+fn pearson(x: &[f64], y: &[f64]) -> f64 {
+   let count = x.len().min(y.len());
+   let (x, y) = (&x[..count], &y[..count]);
+   let (mean_x, mean_y) = (mean(x), mean(y));
+
+   let sxy: f64 = x.iter().zip(y).map(|(a, b)| (a - mean_x) * (b - mean_y)).sum();
+   let sxx: f64 = x.iter().map(|value| (value - mean_x).powi(2)).sum();
+   let syy: f64 = y.iter().map(|value| (value - mean_y).powi(2)).sum();
+   sxy / (sxx * syy).sqrt()
+}
+
+/// Ranks values from 1, giving tied values the average of their ranks
+fn ranks(values: &[f64]) -> Vec<f64> {
+   let mut order: Vec<usize> = (0..values.len()).collect();
+   order.sort_by(|a, b| values[*a].partial_cmp(&values[*b]).unwrap_or(std::cmp::Ordering::Equal));
+
+   let mut ranks = vec![ 0.0; values.len() ];
+   let mut start = 0;
+   while start < order.len() {
+      let mut end = start;
+      while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] { end += 1 }
+
+      let rank = (start + end) as f64 / 2.0 + 1.0;
+      for index in &order[start..=end] { ranks[*index] = rank }
+      start = end + 1;
+   }
+
+   ranks
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn assert_close(expected: f64, actual: f64) {
+      assert!((expected - actual).abs() < 1e-9, "expected {} but was {}", expected, actual);
+   }
+
+   fn bar(timestamp: i64, close: f64) -> Bar {
+      Bar { timestamp, open: close, high: close, low: close, close, volume: None }
+   }
+
+   #[test]
+   fn verify_align() {
+      let first = vec![ bar(1, 1.0), bar(2, 2.0), bar(3, 3.0), bar(4, 4.0) ];
+      let second = vec![ bar(2, 20.0), bar(4, 40.0), bar(5, 50.0) ];
+
+      let aligned = align(&[ &first, &second ]);
+      assert_eq!(vec![ 2.0, 4.0 ], aligned[0].iter().map(|bar| bar.close).collect::<Vec<f64>>());
+      assert_eq!(vec![ 20.0, 40.0 ], aligned[1].iter().map(|bar| bar.close).collect::<Vec<f64>>());
+   }
+
+   #[test]
+   fn verify_linear_regression() {
+      // y = 1 + 2x with residuals of +1, -1, -1, +1
+      let fit = linear_regression(&[ 0.0, 1.0, 2.0, 3.0 ], &[ 2.0, 2.0, 4.0, 8.0 ]).unwrap();
+
+      assert_close(2.0, fit.slope);
+      assert_close(1.0, fit.intercept);
+      assert_close(1.0 - 4.0 / 24.0, fit.r_squared);
+      assert_close((2.0_f64 / 5.0).sqrt(), fit.slope_error);
+      assert_close((2.0 * (0.25 + 2.25 / 5.0_f64)).sqrt(), fit.intercept_error);
+      assert_close(7.0, fit.predict(3.0));
+      assert_eq!(None, linear_regression(&[ 1.0, 1.0 ], &[ 1.0, 2.0 ]));
+   }
+
+   #[test]
+   fn verify_beta() {
+      let benchmark = [ 0.01, -0.02, 0.03, 0.01 ];
+      let returns: Vec<f64> = benchmark.iter().map(|value| 1.5 * value + 0.001).collect();
+
+      assert_close(1.5, beta(&returns, &benchmark));
+      assert_eq!(3, rolling_beta(&returns, &benchmark, 2).len());
+
+      let bars = vec![ bar(1, 100.0), bar(2, 103.0), bar(3, 97.0) ];
+      let index = vec![ bar(1, 10.0), bar(2, 10.1), bar(3, 9.9), bar(4, 10.0) ];
+      assert_close(bar_beta(&bars, &index[..3]), bar_beta(&bars, &index));
+   }
+
+   #[test]
+   fn verify_correlation() {
+      let x = [ 1.0, 2.0, 3.0, 4.0, 5.0 ];
+      let y = [ 1.0, 4.0, 9.0, 16.0, 100.0 ];
+
+      assert!(correlation(&x, &y, CorrelationMethod::Pearson) < 1.0);
+      assert_close(1.0, correlation(&x, &y, CorrelationMethod::Spearman));
+      assert_eq!(vec![ 1.0, 2.5, 2.5, 4.0 ], ranks(&[ 1.0, 3.0, 3.0, 7.0 ]));
+
+      let matrix = correlation_matrix(&[ x.to_vec(), y.to_vec(), x.iter().map(|value| -value).collect() ], CorrelationMethod::Spearman);
+      assert_eq!(1.0, matrix[1][1]);
+      assert_close(-1.0, matrix[0][2]);
+      assert_close(matrix[0][1], matrix[1][0]);
+   }
+}
\ No newline at end of file
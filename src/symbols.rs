@@ -0,0 +1,332 @@
+//! Parsing and formatting of instrument symbols.
+//!
+//! Symbols elsewhere in the crate are opaque strings - these types give structure to the common
+//! formats: OCC option symbols, futures contract codes and equity share classes.
+
+use std::fmt;
+use std::str::FromStr;
+use crate::options::OptionKind;
+
+/// The letters used for the delivery months of futures contracts, from January
+const MONTH_LETTERS: [char; 12] = [ 'F', 'G', 'H', 'J', 'K', 'M', 'N', 'Q', 'U', 'V', 'X', 'Z' ];
+
+/// The delivery months of contracts listed every month
+pub const MONTHLY: [u8; 12] = [ 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12 ];
+
+/// The delivery months of contracts listed every quarter - March, June, September and December
+pub const QUARTERLY: [u8; 4] = [ 3, 6, 9, 12 ];
+
+/// Why a symbol couldn't be parsed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SymbolError {
+   /// The symbol doesn't have the parts of the format
+   Malformed(String),
+
+   /// The root isn't 1 to 6 upper case letters or digits
+   InvalidRoot(String),
+
+   /// The expiry isn't a valid `YYMMDD` date
+   InvalidExpiry(String),
+
+   /// The option kind isn't `C` or `P`
+   InvalidOptionKind(char),
+
+   /// The strike isn't 8 digits
+   InvalidStrike(String),
+
+   /// The letter isn't a futures month code
+   InvalidMonth(char),
+
+   /// The year isn't 1 or 2 digits
+   InvalidYear(String),
+
+   /// The share class isn't a single upper case letter
+   InvalidClass(String)
+}
+impl fmt::Display for SymbolError {
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+         Self::Malformed(symbol) => write!(f, "'{}' isn't a recognised symbol format", symbol),
+         Self::InvalidRoot(root) => write!(f, "'{}' isn't a valid root", root),
+         Self::InvalidExpiry(expiry) => write!(f, "'{}' isn't a valid expiry", expiry),
+         Self::InvalidOptionKind(kind) => write!(f, "'{}' isn't C or P", kind),
+         Self::InvalidStrike(strike) => write!(f, "'{}' isn't a valid strike", strike),
+         Self::InvalidMonth(month) => write!(f, "'{}' isn't a futures month code", month),
+         Self::InvalidYear(year) => write!(f, "'{}' isn't a valid year", year),
+         Self::InvalidClass(class) => write!(f, "'{}' isn't a valid share class", class)
+      }
+   }
+}
+impl std::error::Error for SymbolError {}
+
+/// A calendar date
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Date {
+   pub year: u16,
+   pub month: u8,
+   pub day: u8
+}
+impl Date {
+   /// Creates a date - `None` if the day doesn't exist
+   pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+      if month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) { return None }
+      Some(Self { year, month, day })
+   }
+}
+
+/// An option contract identified by an OCC symbol - e.g. `AAPL  241220C00195000`
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionSymbol {
+   /// The root symbol of the underlying
+   pub root: String,
+
+   /// The expiry date
+   pub expiry: Date,
+
+   /// Whether the option is a call or a put
+   pub kind: OptionKind,
+
+   /// The strike price
+   pub strike: f64
+}
+impl OptionSymbol {
+   /// The symbol without padding the root - e.g. `AAPL241220C00195000`
+   pub fn compact(&self) -> String { format!("{}{}", self.root, self.suffix()) }
+
+   /// The expiry, kind and strike
+   fn suffix(&self) -> String {
+      let kind = match self.kind { OptionKind::Call => 'C', OptionKind::Put => 'P' };
+      let strike = (self.strike * 1_000.0).round() as u64;
+      format!("{:02}{:02}{:02}{}{:08}", self.expiry.year % 100, self.expiry.month, self.expiry.day, kind, strike)
+   }
+}
+impl FromStr for OptionSymbol {
+   type Err = SymbolError;
+
+   /// Parses the padded 21 character OCC format, or the compact format without padding
+   fn from_str(symbol: &str) -> Result<Self, Self::Err> {
+      let symbol = symbol.trim();
+      if !symbol.is_ascii() || symbol.len() < 16 { return Err(SymbolError::Malformed(symbol.to_string())) }
+
+      let (root, suffix) = symbol.split_at(symbol.len() - 15);
+      let (expiry, suffix) = suffix.split_at(6);
+      let (kind, strike) = suffix.split_at(1);
+
+      let invalid_expiry = || SymbolError::InvalidExpiry(expiry.to_string());
+      if !is_digits(expiry) { return Err(invalid_expiry()) }
+      let part = |range: std::ops::Range<usize>| expiry[range].parse::<u8>().map_err(|_| invalid_expiry());
+      let expiry = Date::new(2000 + part(0..2)? as u16, part(2..4)?, part(4..6)?).ok_or_else(invalid_expiry)?;
+
+      let kind = match kind {
+         "C" => OptionKind::Call,
+         "P" => OptionKind::Put,
+         other => return Err(SymbolError::InvalidOptionKind(other.chars().next().unwrap_or(' ')))
+      };
+      if !is_digits(strike) { return Err(SymbolError::InvalidStrike(strike.to_string())) }
+      let strike = strike.parse::<u64>().map_err(|_| SymbolError::InvalidStrike(strike.to_string()))? as f64 / 1_000.0;
+
+      Ok(Self { root: parse_root(root.trim_end())?, expiry, kind, strike })
+   }
+}
+impl fmt::Display for OptionSymbol {
+   /// Formats as the padded 21 character OCC symbol
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:<6}{}", self.root, self.suffix()) }
+}
+
+/// A futures contract identified by its root, delivery month and year - e.g. `ESZ6`
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct FuturesContract {
+   /// The root symbol of the product
+   pub root: String,
+
+   /// The delivery month, from 1 for January
+   pub month: u8,
+
+   /// The delivery year
+   pub year: u16
+}
+impl FuturesContract {
+   /// Parses a code with a 1 or 2 digit year, taking the year as the first with those digits in or
+   /// after the reference year
+   pub fn parse(code: &str, reference_year: u16) -> Result<Self, SymbolError> {
+      let code = code.trim();
+      let digits = code.chars().rev().take_while(|c| c.is_ascii_digit()).count();
+      if !code.is_ascii() || digits == 0 || digits > 2 || code.len() < digits + 2 {
+         return Err(SymbolError::Malformed(code.to_string()))
+      }
+
+      let (rest, year) = code.split_at(code.len() - digits);
+      let (root, letter) = rest.split_at(rest.len() - 1);
+      let letter = letter.chars().next().unwrap_or(' ');
+      let month = month_from_letter(letter).ok_or(SymbolError::InvalidMonth(letter))?;
+
+      let modulus = 10u16.pow(digits as u32);
+      let ending = year.parse::<u16>().map_err(|_| SymbolError::InvalidYear(year.to_string()))?;
+      let year = reference_year - reference_year % modulus + ending;
+      let year = if year < reference_year { year + modulus } else { year };
+
+      Ok(Self { root: parse_root(root)?, month, year })
+   }
+
+   /// The code with a 2 digit year - e.g. `ESZ26`
+   pub fn long_code(&self) -> String { format!("{}{}{:02}", self.root, self.letter(), self.year % 100) }
+
+   /// The letter of the delivery month
+   pub fn letter(&self) -> char { month_letter(self.month).unwrap_or('?') }
+
+   /// The next contract in a listing cycle of delivery months - e.g. [`QUARTERLY`]
+   pub fn next(&self, cycle: &[u8]) -> Self {
+      let (month, year) = match cycle.iter().copied().filter(|month| *month > self.month).min() {
+         Some(month) => (month, self.year),
+         None => (cycle.iter().copied().min().unwrap_or(self.month), self.year + 1)
+      };
+      Self { root: self.root.clone(), month, year }
+   }
+
+   /// The first contract in a listing cycle delivering in or after a month
+   pub fn front(root: &str, year: u16, month: u8, cycle: &[u8]) -> Self {
+      Self { root: root.to_string(), month: month.saturating_sub(1), year }.next(cycle)
+   }
+}
+impl fmt::Display for FuturesContract {
+   /// Formats as the code with a 1 digit year - e.g. `ESZ6`
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}{}{}", self.root, self.letter(), self.year % 10) }
+}
+
+/// An equity symbol with an optional share class - e.g. `BRK.B`
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Equity {
+   /// The root symbol of the company
+   pub root: String,
+
+   /// The share class
+   pub class: Option<char>
+}
+impl Equity {
+   /// Formats with a separator before the share class - e.g. `BRK/B`
+   pub fn with_separator(&self, separator: char) -> String {
+      match self.class {
+         Some(class) => format!("{}{}{}", self.root, separator, class),
+         None => self.root.clone()
+      }
+   }
+}
+impl FromStr for Equity {
+   type Err = SymbolError;
+
+   /// Parses a root followed by an optional share class after a `.`, `/`, `-` or space
+   fn from_str(symbol: &str) -> Result<Self, Self::Err> {
+      let symbol = symbol.trim();
+      let (root, class) = match symbol.find(['.', '/', '-', ' ']) {
+         Some(index) => (&symbol[..index], Some(&symbol[index + 1..])),
+         None => (symbol, None)
+      };
+
+      let class = match class {
+         Some(class) if class.len() == 1 && class.chars().all(|c| c.is_ascii_uppercase()) => class.chars().next(),
+         Some(class) => return Err(SymbolError::InvalidClass(class.to_string())),
+         None => None
+      };
+      Ok(Self { root: parse_root(root)?, class })
+   }
+}
+impl fmt::Display for Equity {
+   /// Formats with a `.` before the share class
+   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str(&self.with_separator('.')) }
+}
+
+/// The futures month code of a month, from 1 for January
+pub fn month_letter(month: u8) -> Option<char> {
+   MONTH_LETTERS.get(month.checked_sub(1)? as usize).copied()
+}
+
+/// The month of a futures month code, from 1 for January
+pub fn month_from_letter(letter: char) -> Option<u8> {
+   MONTH_LETTERS.iter().position(|month| *month == letter).map(|index| index as u8 + 1)
+}
+
+fn parse_root(root: &str) -> Result<String, SymbolError> {
+   let valid = !root.is_empty() && root.len() <= 6 && root.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+   if valid { Ok(root.to_string()) } else { Err(SymbolError::InvalidRoot(root.to_string())) }
+}
+
+fn is_digits(value: &str) -> bool { value.chars().all(|c| c.is_ascii_digit()) }
+
+#[allow(clippy::manual_is_multiple_of)]
+fn days_in_month(year: u16, month: u8) -> u8 {
+   match month {
+      2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+      2 => 28,
+      4 | 6 | 9 | 11 => 30,
+      _ => 31
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn verify_option_symbols() {
+      let symbol: OptionSymbol = "AAPL  241220C00195000".parse().unwrap();
+      assert_eq!("AAPL", symbol.root);
+      assert_eq!(Date::new(2024, 12, 20).unwrap(), symbol.expiry);
+      assert_eq!((OptionKind::Call, 195.0), (symbol.kind, symbol.strike));
+      assert_eq!("AAPL  241220C00195000", symbol.to_string());
+      assert_eq!("AAPL241220C00195000", symbol.compact());
+
+      let symbol: OptionSymbol = "SPXW240229P04012500".parse().unwrap();
+      assert_eq!(("SPXW", OptionKind::Put, 4012.5), (symbol.root.as_str(), symbol.kind, symbol.strike));
+      assert_eq!(29, symbol.expiry.day);
+
+      assert_eq!(Err(SymbolError::InvalidExpiry("230229".to_string())), "SPXW230229P04012500".parse::<OptionSymbol>());
+      assert_eq!(Err(SymbolError::InvalidOptionKind('X')), "AAPL  241220X00195000".parse::<OptionSymbol>());
+      assert_eq!(Err(SymbolError::InvalidStrike("0019500A".to_string())), "AAPL  241220C0019500A".parse::<OptionSymbol>());
+      assert_eq!(Err(SymbolError::InvalidRoot("aapl".to_string())), "aapl241220C00195000".parse::<OptionSymbol>());
+      assert!(matches!("241220C00195000".parse::<OptionSymbol>(), Err(SymbolError::Malformed(_))));
+   }
+
+   #[test]
+   fn verify_futures() {
+      let contract = FuturesContract::parse("ESZ6", 2026).unwrap();
+      assert_eq!(FuturesContract { root: "ES".to_string(), month: 12, year: 2026 }, contract);
+      assert_eq!(("ESZ6", "ESZ26"), (contract.to_string().as_str(), contract.long_code().as_str()));
+
+      // a year before the reference is taken as the next decade
+      assert_eq!(2035, FuturesContract::parse("CLF5", 2026).unwrap().year);
+      assert_eq!(2025, FuturesContract::parse("CLF25", 2020).unwrap().year);
+
+      assert_eq!(Err(SymbolError::InvalidMonth('A')), FuturesContract::parse("ESA6", 2026));
+      assert!(matches!(FuturesContract::parse("ESZ", 2026), Err(SymbolError::Malformed(_))));
+      assert!(matches!(FuturesContract::parse("Z6", 2026), Err(SymbolError::Malformed(_))));
+   }
+
+   #[test]
+   fn verify_rolls() {
+      let contract = FuturesContract::parse("ESZ6", 2026).unwrap();
+      assert_eq!("ESH7", contract.next(&QUARTERLY).to_string());
+      assert_eq!("ESF7", contract.next(&MONTHLY).to_string());
+      let codes: Vec<String> = (0..4).scan(contract, |contract, _| { *contract = contract.next(&QUARTERLY); Some(contract.to_string()) }).collect();
+      assert_eq!(vec![ "ESH7", "ESM7", "ESU7", "ESZ7" ], codes);
+
+      assert_eq!("ESM6", FuturesContract::front("ES", 2026, 4, &QUARTERLY).to_string());
+      assert_eq!("ESM6", FuturesContract::front("ES", 2026, 6, &QUARTERLY).to_string());
+      assert_eq!("CLF6", FuturesContract::front("CL", 2026, 1, &MONTHLY).to_string());
+
+      assert_eq!(Some('Z'), month_letter(12));
+      assert_eq!(None, month_letter(0));
+      assert_eq!(Some(6), month_from_letter('M'));
+   }
+
+   #[test]
+   fn verify_equities() {
+      let equity: Equity = "BRK/B".parse().unwrap();
+      assert_eq!(Equity { root: "BRK".to_string(), class: Some('B') }, equity);
+      assert_eq!(("BRK.B", "BRK-B".to_string()), (equity.to_string().as_str(), equity.with_separator('-')));
+      assert_eq!(None, "MSFT".parse::<Equity>().unwrap().class);
+
+      assert_eq!(Err(SymbolError::InvalidClass("BB".to_string())), "BRK.BB".parse::<Equity>());
+      assert_eq!(Err(SymbolError::InvalidRoot(String::new())), ".B".parse::<Equity>());
+   }
+}
\ No newline at end of file
@@ -0,0 +1,192 @@
+use super::{ Quote, Timestamped, TradingSession };
+use super::orders::Side;
+
+/// The best bid and ask for a symbol at a point in time - from a single exchange or the national best
+#[derive(Clone, Debug, PartialEq)]
+pub struct BookQuote {
+   /// The symbol for the quote
+   pub symbol: String,
+
+   /// The timestamp of the quote in millisecond accuracy
+   pub timestamp: i64,
+
+   /// The highest price a buyer is bidding
+   pub bid: f64,
+
+   /// The size available at the bid
+   pub bid_size: u64,
+
+   /// The lowest price a seller is asking
+   pub ask: f64,
+
+   /// The size available at the ask
+   pub ask_size: u64,
+
+   /// The code of the exchange quoting - empty for a consolidated quote
+   pub exchange: String
+}
+impl BookQuote {
+   /// The difference between the ask and the bid
+   pub fn spread(&self) -> f64 { self.ask - self.bid }
+
+   /// The spread as a fraction of the mid price
+   pub fn relative_spread(&self) -> f64 { self.spread() / self.mid() }
+
+   /// The price halfway between the bid and the ask
+   pub fn mid(&self) -> f64 { (self.bid + self.ask) / 2.0 }
+
+   /// The mid price weighted towards the side with less size
+   pub fn microprice(&self) -> f64 {
+      let (bid_size, ask_size) = (self.bid_size as f64, self.ask_size as f64);
+      if bid_size + ask_size == 0.0 { return self.mid() }
+      (self.bid * ask_size + self.ask * bid_size) / (bid_size + ask_size)
+   }
+
+   /// Whether the bid and the ask are the same price
+   pub fn is_locked(&self) -> bool { self.bid == self.ask }
+
+   /// Whether the bid is above the ask
+   pub fn is_crossed(&self) -> bool { self.bid > self.ask }
+
+   /// Converts to a quote at the mid price without any volume
+   pub fn to_quote(&self, session: TradingSession) -> Quote {
+      Quote { symbol: self.symbol.clone(), timestamp: self.timestamp, session, price: self.mid(), volume: 0 }
+   }
+}
+impl Timestamped for BookQuote {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+
+/// A single trade of a symbol
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tick {
+   /// The symbol traded
+   pub symbol: String,
+
+   /// The timestamp of the trade in millisecond accuracy
+   pub timestamp: i64,
+
+   /// The trading session of the trade - pre market / regular hours / after hours
+   pub session: TradingSession,
+
+   /// The price of the trade
+   pub price: f64,
+
+   /// The size of the trade
+   pub size: u64,
+
+   /// The code of the exchange the trade happened on
+   pub exchange: String,
+
+   /// The sale conditions reported with the trade
+   pub conditions: Vec<String>
+}
+impl Timestamped for Tick {
+   /// Gets the timestamp in millisecond accuracy
+   fn timestamp_millis(&self) -> i64 { self.timestamp }
+}
+impl From<&Tick> for Quote {
+   fn from(tick: &Tick) -> Self {
+      Quote { symbol: tick.symbol.clone(), timestamp: tick.timestamp, session: tick.session, price: tick.price, volume: tick.size }
+   }
+}
+
+/// Classifies trades as buyer or seller initiated using the Lee-Ready algorithm.
+///
+/// Trades above the mid of the prevailing quote are buys and those below are sells.  Trades at the
+/// mid (or without a quote) fall back to the tick test - a trade above the last different price is
+/// a buy and one below it is a sell.
+#[derive(Clone, Debug, Default)]
+pub struct LeeReady {
+   last_price: Option<f64>,
+
+   /// The direction of the last change in price
+   last_change: Option<Side>
+}
+impl LeeReady {
+   /// Creates a classifier that hasn't seen any trades
+   pub fn new() -> Self { Self::default() }
+
+   /// Classifies the next trade against the quote prevailing when it happened - `None` if it can't be classified
+   pub fn classify(&mut self, tick: &Tick, quote: Option<&BookQuote>) -> Option<Side> {
+      let tick_test = self.tick_test(tick.price);
+      match quote.filter(|quote| !quote.is_crossed()) {
+         Some(quote) if tick.price > quote.mid() => Some(Side::Buy),
+         Some(quote) if tick.price < quote.mid() => Some(Side::Sell),
+         _ => tick_test
+      }
+   }
+
+   /// Classifies trades sorted by timestamp against quotes sorted by timestamp, using the latest quote
+   /// at least `delay` milliseconds before each trade
+   pub fn classify_all(ticks: &[Tick], quotes: &[BookQuote], delay: i64) -> Vec<Option<Side>> {
+      let mut classifier = Self::new();
+      let mut next = 0;
+      ticks.iter().map(|tick| {
+         while next < quotes.len() && quotes[next].timestamp <= tick.timestamp - delay { next += 1 }
+         classifier.classify(tick, next.checked_sub(1).map(|index| &quotes[index]))
+      }).collect()
+   }
+
+   /// The direction of the price from the last different price
+   fn tick_test(&mut self, price: f64) -> Option<Side> {
+      if let Some(last) = self.last_price {
+         if price > last { self.last_change = Some(Side::Buy) }
+         if price < last { self.last_change = Some(Side::Sell) }
+      }
+
+      self.last_price = Some(price);
+      self.last_change
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn quote(timestamp: i64, bid: f64, ask: f64) -> BookQuote {
+      BookQuote { symbol: "ABC".to_string(), timestamp, bid, bid_size: 300, ask, ask_size: 100, exchange: String::new() }
+   }
+
+   fn tick(timestamp: i64, price: f64) -> Tick {
+      Tick {
+         symbol: "ABC".to_string(),
+         timestamp,
+         session: TradingSession::Regular,
+         price,
+         size: 100,
+         exchange: "Q".to_string(),
+         conditions: vec![ "@".to_string() ]
+      }
+   }
+
+   #[test]
+   fn verify_book_quote() {
+      let quote = quote(1, 10.0, 10.5);
+      assert_eq!(0.5, quote.spread());
+      assert_eq!(10.25, quote.mid());
+      assert_eq!(10.375, quote.microprice());
+      assert!(!quote.is_locked() && !quote.is_crossed());
+      assert_eq!(10.25, quote.to_quote(TradingSession::Regular).price);
+
+      let converted = Quote::from(&tick(2, 10.1));
+      assert_eq!((2, 10.1, 100), (converted.timestamp, converted.price, converted.volume));
+   }
+
+   #[test]
+   fn verify_lee_ready() {
+      let quotes = vec![ quote(0, 10.0, 10.2), quote(5_000, 10.2, 10.4) ];
+      let ticks = vec![ tick(1_000, 10.15), tick(2_000, 10.05), tick(3_000, 10.1), tick(4_000, 10.1), tick(6_000, 10.3), tick(11_000, 10.25) ];
+
+      // the third, fourth and fifth trades are at the mid so fall back to the tick test
+      let sides = LeeReady::classify_all(&ticks, &quotes, 1_000);
+      assert_eq!(vec![ Some(Side::Buy), Some(Side::Sell), Some(Side::Buy), Some(Side::Buy), Some(Side::Buy), Some(Side::Sell) ], sides);
+
+      let mut classifier = LeeReady::new();
+      assert_eq!(None, classifier.classify(&tick(0, 10.0), None));
+      assert_eq!(Some(Side::Sell), classifier.classify(&tick(1, 9.9), None));
+      assert_eq!(Some(Side::Sell), classifier.classify(&tick(2, 9.9), None));
+   }
+}
\ No newline at end of file
@@ -0,0 +1,335 @@
+//! Validation and cleaning of bar data from vendors.
+
+use std::cmp::Ordering;
+use crate::Bar;
+use crate::math::median;
+
+/// Scaling of the median absolute deviation so it's comparable to a standard deviation
+const MAD_SCALE: f64 = 0.6745;
+
+/// A problem found with a bar
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IssueKind {
+   /// One of the prices is NaN, infinite or negative
+   InvalidPrice,
+
+   /// The high is below the low
+   HighBelowLow,
+
+   /// The open is outside of the low / high range
+   OpenOutOfRange,
+
+   /// The close is outside of the low / high range
+   CloseOutOfRange,
+
+   /// The price moved from the previous close but there was no volume
+   ZeroVolumeMove,
+
+   /// The timestamp is the same as the previous bar
+   DuplicateTimestamp,
+
+   /// The timestamp is before the previous bar
+   OutOfOrder,
+
+   /// The return from the previous close is an outlier - with the score that flagged it
+   Outlier(f64)
+}
+
+/// A problem found with a bar, along with where it was found
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Issue {
+   /// The index of the bar in the validated series
+   pub index: usize,
+
+   /// The timestamp of the bar
+   pub timestamp: i64,
+
+   /// The problem with the bar
+   pub kind: IssueKind
+}
+
+/// All of the problems found in a series of bars
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport {
+   /// The problems in the order of the bars they were found in
+   pub issues: Vec<Issue>
+}
+impl ValidationReport {
+   /// Whether no problems were found
+   pub fn is_clean(&self) -> bool { self.issues.is_empty() }
+
+   /// Gets the indices of the bars with problems, without repeats
+   pub fn indices(&self) -> Vec<usize> {
+      let mut indices: Vec<usize> = self.issues.iter().map(|issue| issue.index).collect();
+      indices.dedup();
+      indices
+   }
+}
+
+/// The method used to find outlier returns
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutlierMethod {
+   /// Returns more than the given number of standard deviations from the mean
+   ZScore(f64),
+
+   /// Returns with a modified z-score (based on the median absolute deviation) above the given value - 3.5 is typical
+   MedianAbsoluteDeviation(f64)
+}
+
+/// What to do with bars whose high, low, open and close are inconsistent
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RangeRepair {
+   /// Removes the bar
+   Drop,
+
+   /// Widens the high and low to cover the open and close
+   Clamp
+}
+
+/// How a series of bars is cleaned up.
+///
+/// Bars with invalid prices are always removed, since there's nothing sensible to replace them with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RepairPolicy {
+   /// Sorts the bars by timestamp
+   pub reorder: bool,
+
+   /// Removes bars with duplicate timestamps, keeping the last one seen
+   pub dedupe: bool,
+
+   /// What to do with inconsistent high / low / open / close prices
+   pub range: RangeRepair,
+
+   /// Removes bars where the price moved without any volume
+   pub drop_zero_volume_moves: bool,
+
+   /// Removes the bars with outlier returns
+   pub drop_outliers: Option<OutlierMethod>
+}
+impl Default for RepairPolicy {
+   fn default() -> Self {
+      Self { reorder: true, dedupe: true, range: RangeRepair::Clamp, drop_zero_volume_moves: false, drop_outliers: None }
+   }
+}
+
+/// Checks a series of bars for problems, optionally looking for outlier returns
+pub fn validate(bars: &[Bar], outliers: Option<OutlierMethod>) -> ValidationReport {
+   let mut issues = vec![];
+
+   for (index, bar) in bars.iter().enumerate() {
+      let mut issue = |kind| issues.push(Issue { index, timestamp: bar.timestamp, kind });
+
+      if !has_valid_prices(bar) {
+         issue(IssueKind::InvalidPrice);
+         continue
+      }
+
+      if bar.high < bar.low { issue(IssueKind::HighBelowLow) }
+      if bar.open < bar.low || bar.open > bar.high { issue(IssueKind::OpenOutOfRange) }
+      if bar.close < bar.low || bar.close > bar.high { issue(IssueKind::CloseOutOfRange) }
+
+      if index > 0 {
+         let previous = &bars[index - 1];
+         match bar.timestamp.cmp(&previous.timestamp) {
+            Ordering::Equal => issue(IssueKind::DuplicateTimestamp),
+            Ordering::Less => issue(IssueKind::OutOfOrder),
+            Ordering::Greater => {}
+         }
+
+         if bar.volume == Some(0) && (bar.high != bar.low || bar.close != previous.close) { issue(IssueKind::ZeroVolumeMove) }
+      }
+   }
+
+   if let Some(method) = outliers {
+      for (index, score) in find_outliers(bars, method) {
+         issues.push(Issue { index, timestamp: bars[index].timestamp, kind: IssueKind::Outlier(score) });
+      }
+      issues.sort_by_key(|issue| issue.index);
+   }
+
+   ValidationReport { issues }
+}
+
+/// Cleans up a series of bars according to a policy
+pub fn repair(bars: &[Bar], policy: &RepairPolicy) -> Vec<Bar> {
+   let mut repaired: Vec<Bar> = bars.iter().copied().filter(has_valid_prices).collect();
+
+   // a stable sort keeps duplicates in the order they were seen, so the last one is still last
+   if policy.reorder { repaired.sort_by_key(|bar| bar.timestamp) }
+
+   if policy.dedupe {
+      let mut deduped: Vec<Bar> = Vec::with_capacity(repaired.len());
+      for bar in repaired {
+         match deduped.last_mut() {
+            Some(last) if last.timestamp == bar.timestamp => *last = bar,
+            _ => deduped.push(bar)
+         }
+      }
+      repaired = deduped;
+   }
+
+   repaired = match policy.range {
+      RangeRepair::Drop => repaired.into_iter().filter(has_consistent_range).collect(),
+      RangeRepair::Clamp => repaired.into_iter().map(clamp_range).collect()
+   };
+
+   if policy.drop_zero_volume_moves {
+      let mut previous_close: Option<f64> = None;
+      repaired.retain(|bar| {
+         let keep = !(bar.volume == Some(0) && previous_close.is_some_and(|close| bar.close != close || bar.high != bar.low));
+         if keep { previous_close = Some(bar.close) }
+         keep
+      });
+   }
+
+   if let Some(method) = policy.drop_outliers {
+      let outliers: Vec<usize> = find_outliers(&repaired, method).into_iter().map(|(index, _)| index).collect();
+      repaired = repaired.into_iter().enumerate().filter(|(index, _)| !outliers.contains(index)).map(|(_, bar)| bar).collect();
+   }
+
+   repaired
+}
+
+/// Finds the bars whose close to close return is an outlier, along with their score.
+///
+/// Bars with invalid prices or a high below the low have no return to or from them, so they can't
+/// hide or fake an outlier next to them.
+pub fn find_outliers(bars: &[Bar], method: OutlierMethod) -> Vec<(usize, f64)> {
+   let usable = |bar: &Bar| has_valid_prices(bar) && bar.high >= bar.low;
+   let (indices, returns): (Vec<usize>, Vec<f64>) = bars.windows(2)
+      .enumerate()
+      .filter(|(_, pair)| usable(&pair[0]) && usable(&pair[1]) && pair[0].close > 0.0)
+      .map(|(index, pair)| (index + 1, pair[1].close / pair[0].close - 1.0))
+      .unzip();
+   if returns.len() < 2 { return vec![] }
+
+   let (scores, threshold): (Vec<f64>, f64) = match method {
+      OutlierMethod::ZScore(threshold) => {
+         let count = returns.len() as f64;
+         let mean = returns.iter().sum::<f64>() / count;
+         let stdev = (returns.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / count).sqrt();
+         if stdev == 0.0 { return vec![] }
+         (returns.iter().map(|value| (value - mean) / stdev).collect(), threshold)
+      },
+      OutlierMethod::MedianAbsoluteDeviation(threshold) => {
+         let center = median(&returns);
+         let deviations: Vec<f64> = returns.iter().map(|value| (value - center).abs()).collect();
+         let mad = median(&deviations);
+         if mad == 0.0 { return vec![] }
+         (returns.iter().map(|value| MAD_SCALE * (value - center) / mad).collect(), threshold)
+      }
+   };
+
+   scores.into_iter()
+      .enumerate()
+      .filter(|(_, score)| score.abs() > threshold)
+      .map(|(index, score)| (indices[index], score))
+      .collect()
+}
+
+fn has_valid_prices(bar: &Bar) -> bool {
+   [ bar.open, bar.high, bar.low, bar.close ].iter().all(|price| price.is_finite() && *price >= 0.0)
+}
+
+fn has_consistent_range(bar: &Bar) -> bool {
+   bar.high >= bar.low && bar.open >= bar.low && bar.open <= bar.high && bar.close >= bar.low && bar.close <= bar.high
+}
+
+fn clamp_range(bar: Bar) -> Bar {
+   Bar {
+      high: bar.high.max(bar.low).max(bar.open).max(bar.close),
+      low: bar.low.min(bar.high).min(bar.open).min(bar.close),
+      ..bar
+   }
+}
+
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn bar(timestamp: i64, open: f64, high: f64, low: f64, close: f64) -> Bar {
+      Bar { timestamp, open, high, low, close, volume: Some(100) }
+   }
+
+   fn kinds(report: &ValidationReport) -> Vec<(usize, IssueKind)> {
+      report.issues.iter().map(|issue| (issue.index, issue.kind)).collect()
+   }
+
+   #[test]
+   fn verify_validate() {
+      let bars = vec![
+         bar(1, 10.0, 11.0, 9.0, 10.0),
+         bar(2, 10.0, 9.0, 11.0, 10.0),
+         bar(3, 10.0, 11.0, 9.0, 12.0),
+         bar(3, f64::NAN, 11.0, 9.0, 10.0),
+         bar(2, 10.0, 11.0, 9.0, 10.0),
+         Bar { volume: Some(0), ..bar(5, 10.0, 11.0, 9.0, 10.5) }
+      ];
+
+      let report = validate(&bars, None);
+      assert_eq!(vec![
+         (1, IssueKind::HighBelowLow), (1, IssueKind::OpenOutOfRange), (1, IssueKind::CloseOutOfRange),
+         (2, IssueKind::CloseOutOfRange),
+         (3, IssueKind::InvalidPrice),
+         (4, IssueKind::OutOfOrder),
+         (5, IssueKind::ZeroVolumeMove)
+      ], kinds(&report));
+      assert_eq!(vec![ 1, 2, 3, 4, 5 ], report.indices());
+      assert!(validate(&bars[..1], None).is_clean());
+   }
+
+   #[test]
+   fn verify_repair_default() {
+      let bars = vec![
+         bar(3, 10.0, 11.0, 9.0, 10.0),
+         bar(1, 10.0, 11.0, 9.0, 12.0),
+         bar(2, -1.0, 11.0, 9.0, 10.0),
+         bar(3, 10.0, 11.0, 9.0, 10.5)
+      ];
+
+      let repaired = repair(&bars, &RepairPolicy::default());
+      assert_eq!(2, repaired.len());
+      assert_eq!((1, 12.0), (repaired[0].timestamp, repaired[0].high));
+      assert_eq!((3, 10.5), (repaired[1].timestamp, repaired[1].close));
+   }
+
+   #[test]
+   fn verify_repair_drop() {
+      let bars = vec![ bar(1, 10.0, 11.0, 9.0, 10.0), bar(2, 10.0, 11.0, 9.0, 12.0) ];
+      let policy = RepairPolicy { range: RangeRepair::Drop, ..RepairPolicy::default() };
+
+      assert_eq!(1, repair(&bars, &policy).len());
+   }
+
+   #[test]
+   fn verify_outliers() {
+      let closes = [ 100.0, 101.0, 100.0, 101.0, 100.0, 150.0, 151.0, 150.0, 151.0 ];
+      let bars: Vec<Bar> = closes.iter().enumerate().map(|(index, close)| bar(index as i64, *close, *close, *close, *close)).collect();
+
+      let outliers = find_outliers(&bars, OutlierMethod::MedianAbsoluteDeviation(3.5));
+      assert_eq!(vec![ 5 ], outliers.iter().map(|(index, _)| *index).collect::<Vec<usize>>());
+
+      let outliers = find_outliers(&bars, OutlierMethod::ZScore(2.0));
+      assert_eq!(vec![ 5 ], outliers.iter().map(|(index, _)| *index).collect::<Vec<usize>>());
+
+      let policy = RepairPolicy { drop_outliers: Some(OutlierMethod::ZScore(2.0)), ..RepairPolicy::default() };
+      assert_eq!(8, repair(&bars, &policy).len());
+   }
+
+   #[test]
+   fn verify_outliers_around_invalid_bars() {
+      let closes = [ 100.0, 101.0, 100.0, f64::NAN, 101.0, 100.0, 150.0, 151.0, 150.0, 151.0 ];
+      let bars: Vec<Bar> = closes.iter().enumerate().map(|(index, close)| bar(index as i64, *close, *close, *close, *close)).collect();
+
+      // the NaN bar is an invalid price rather than turning every score into NaN
+      for method in [ OutlierMethod::ZScore(2.0), OutlierMethod::MedianAbsoluteDeviation(3.5) ].iter() {
+         assert_eq!(vec![ 6 ], find_outliers(&bars, *method).iter().map(|(index, _)| *index).collect::<Vec<usize>>());
+      }
+
+      let report = validate(&bars, Some(OutlierMethod::ZScore(2.0)));
+      assert_eq!(vec![ 3, 6 ], report.indices());
+      assert_eq!(IssueKind::InvalidPrice, report.issues[0].kind);
+      assert!(matches!(report.issues[1].kind, IssueKind::Outlier(score) if score > 2.0));
+   }
+}
\ No newline at end of file